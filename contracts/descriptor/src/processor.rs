@@ -56,7 +56,7 @@ impl Processor {
                 let counter = Counter::new();
                 counter.create(program_id, payer, counter_ai, system_program)?;
                 msg!("Ai {:?}", counter_ai);
-                assert(counter_ai.owner.eq(program_id), "Counter owner program not assigned")?;
+                assert(counter_ai.owner.get().eq(program_id), "Counter owner program not assigned")?;
                 assert(counter_ai.data_len() == counter.size(), "Counter data not allocated")
             },
             DescriptorInstruction::CreateAccountPDA => {
@@ -65,7 +65,7 @@ impl Processor {
                 let counter = Counter::new();
                 assert(counter_ai.is_writable, "Counter not writable")?;
                 counter.create_pda(program_id, payer, counter_ai, system_program)?;
-                assert(counter_ai.owner.eq(program_id), "Counter owner program not assigned")?;
+                assert(counter_ai.owner.get().eq(program_id), "Counter owner program not assigned")?;
                 assert(counter_ai.data_len() == counter.size(), "Counter data not allocated")
             },
             DescriptorInstruction::VerifySigner => {
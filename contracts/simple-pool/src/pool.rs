@@ -133,7 +133,7 @@ impl Pool {
             &self.owner.to_bytes(),
             &self.mint_left.to_bytes(),
             &self.mint_right.to_bytes(),
-            &pool_ai.owner.to_bytes(),
+            &pool_ai.owner.get().to_bytes(),
             &[self.bump]]];
 
         invoke_signed(idx, infos, seeds)
@@ -0,0 +1,21 @@
+extern crate rustc_version;
+use rustc_version::{version_meta, Channel};
+
+fn main() {
+    // Same switch as solana-frozen-abi, its derives only compile on nightly with specialization enabled here too
+    println!("cargo:rustc-check-cfg=cfg(RUSTC_WITH_SPECIALIZATION)");
+    println!("cargo:rustc-check-cfg=cfg(RUSTC_WITHOUT_SPECIALIZATION)");
+    println!("cargo:rustc-check-cfg=cfg(RUSTC_NEEDS_PROC_MACRO_HYGIENE)");
+    match version_meta().unwrap().channel {
+        Channel::Stable | Channel::Beta => {
+            println!("cargo:rustc-cfg=RUSTC_WITHOUT_SPECIALIZATION");
+        }
+        Channel::Nightly => {
+            println!("cargo:rustc-cfg=RUSTC_WITH_SPECIALIZATION");
+        }
+        Channel::Dev => {
+            println!("cargo:rustc-cfg=RUSTC_WITH_SPECIALIZATION");
+            println!("cargo:rustc-cfg=RUSTC_NEEDS_PROC_MACRO_HYGIENE");
+        }
+    }
+}
@@ -1,14 +1,12 @@
-use std::sync::atomic::Ordering;
 use {
     crate::{
         clock::Epoch, debug_account_data::*, entrypoint::MAX_PERMITTED_DATA_INCREASE,
         program_error::ProgramError, pubkey::Pubkey,
     },
     std::{
-        cell::{Ref, RefCell, RefMut},
+        cell::{Cell, Ref, RefCell, RefMut},
         fmt,
         rc::Rc,
-        slice::from_raw_parts_mut,
     },
 };
 use crate::instruction::AccountMeta;
@@ -21,7 +19,7 @@ pub struct AccountInfo<'a> {
     pub key: &'a Pubkey,
     pub lamports: Rc<RefCell<&'a mut u64>>,
     pub data: Rc<RefCell<&'a mut [u8]>>,
-    pub owner: &'a Pubkey,
+    pub owner: Rc<Cell<Pubkey>>, // Shared by the duplicates, assign writes through it
     pub rent_epoch: Epoch,
     pub is_signer: bool,
     pub is_writable: bool,
@@ -33,7 +31,7 @@ impl<'a> fmt::Debug for AccountInfo<'a> {
         let mut f = f.debug_struct("AccountInfo");
 
         f.field("key", &self.key)
-            .field("owner", &self.owner)
+            .field("owner", &self.owner.get())
             .field("is_signer", &self.is_signer)
             .field("is_writable", &self.is_writable)
             .field("executable", &self.executable)
@@ -67,12 +65,6 @@ impl<'a> AccountInfo<'a> {
         Ok(**self.try_borrow_lamports()?)
     }
 
-    pub unsafe fn original_data_len(&self) -> usize {
-        let key_ptr = self.key as *const _ as *const u8;
-        let original_data_len_ptr = key_ptr.offset(-4) as *const u32;
-        *original_data_len_ptr as usize
-    }
-
     pub fn data_len(&self) -> usize {
         self.data.borrow().len()
     }
@@ -113,34 +105,17 @@ impl<'a> AccountInfo<'a> {
             .map_err(|_| ProgramError::AccountBorrowFailed)
     }
 
-    pub fn realloc(&self, new_len: usize, zero_init: bool) -> Result<(), ProgramError> {
-        let old_len = self.data_len();
-
-        // Return early if length hasn't changed
-        if new_len == old_len {
+    // The runtime checks the new length against the length the account had when the program was entered
+    pub fn realloc(&self, new_len: usize, _zero_init: bool) -> Result<(), ProgramError> {
+        let mut data = self.try_borrow_mut_data()?;
+        if new_len == data.len() {
             return Ok(());
         }
+        // Grown bytes always read as zero
+        syscalls::with(|syscalls| syscalls.realloc(&mut data, new_len, MAX_PERMITTED_DATA_INCREASE))?;
+        drop(data);
 
-        if new_len.saturating_sub(old_len) > MAX_PERMITTED_DATA_INCREASE {
-            return Err(ProgramError::InvalidRealloc);
-        }
-
-        let min_lamports = unsafe {
-            let mut scope = syscalls!();
-            let data_ptr = scope.get_data_ptr(self.key);
-            let vec = data_ptr.load(Ordering::Relaxed).as_mut().unwrap();
-
-            if old_len > new_len {
-                vec.drain(new_len..old_len);
-            }
-            else {
-                vec.extend(vec![0; new_len - old_len].iter());
-            }
-
-            scope.set_data(self, vec.clone());
-            scope.rent_exempt_for_size(new_len)
-        };
-
+        let min_lamports = syscalls::with(|syscalls| syscalls.rent_exempt_for_size(new_len));
         if self.lamports() < min_lamports {
             return Err(ProgramError::AccountNotRentExempt);
         }
@@ -149,11 +124,7 @@ impl<'a> AccountInfo<'a> {
     }
 
     pub fn assign(&self, new_owner: &Pubkey) {
-        // Set the non-mut owner field
-        unsafe {
-            let pubkey_ptr = self.owner as *const Pubkey as *mut Pubkey;
-            std::ptr::replace(pubkey_ptr, new_owner.clone());
-        }
+        self.owner.set(*new_owner);
     }
 
     pub fn new(
@@ -172,7 +143,7 @@ impl<'a> AccountInfo<'a> {
             is_writable,
             lamports: Rc::new(RefCell::new(lamports)),
             data: Rc::new(RefCell::new(data)),
-            owner,
+            owner: Rc::new(Cell::new(*owner)),
             executable,
             rent_epoch,
         }
@@ -199,7 +170,7 @@ impl<'a> AccountInfo<'a> {
             is_writable: meta.is_writable,
             lamports: self.lamports.clone(),
             data: self.data.clone(),
-            owner: self.owner,
+            owner: self.owner.clone(),
             executable: self.executable,
             rent_epoch: self.rent_epoch
         }
@@ -0,0 +1,46 @@
+use {
+    crate::{
+        instruction::Instruction,
+        borsh0_10::try_from_slice_unchecked,
+    },
+    borsh::{BorshDeserialize, BorshSerialize},
+};
+
+crate::declare_id!("ComputeBudget111111111111111111111111111111");
+
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+pub enum ComputeBudgetInstruction {
+    Unused,
+    /// Request a specific transaction-wide program heap region size in bytes.
+    /// The value requested must be a multiple of 1024.
+    RequestHeapFrame(u32),
+    /// Set a specific compute unit limit that the transaction is allowed to consume.
+    SetComputeUnitLimit(u32),
+    /// Set a compute unit price in "micro-lamports" to pay a higher transaction
+    /// fee for higher transaction prioritization.
+    SetComputeUnitPrice(u64),
+    /// Set a specific transaction-wide account data size limit, in bytes, is allowed to load.
+    SetLoadedAccountsDataSizeLimit(u32),
+}
+
+impl ComputeBudgetInstruction {
+    pub fn request_heap_frame(bytes: u32) -> Instruction {
+        Instruction::new_with_borsh(id(), &Self::RequestHeapFrame(bytes), vec![])
+    }
+
+    pub fn set_compute_unit_limit(units: u32) -> Instruction {
+        Instruction::new_with_borsh(id(), &Self::SetComputeUnitLimit(units), vec![])
+    }
+
+    pub fn set_compute_unit_price(micro_lamports: u64) -> Instruction {
+        Instruction::new_with_borsh(id(), &Self::SetComputeUnitPrice(micro_lamports), vec![])
+    }
+
+    pub fn set_loaded_accounts_data_size_limit(bytes: u32) -> Instruction {
+        Instruction::new_with_borsh(id(), &Self::SetLoadedAccountsDataSizeLimit(bytes), vec![])
+    }
+
+    pub fn unpack(data: &[u8]) -> Option<Self> {
+        try_from_slice_unchecked(data).ok()
+    }
+}
@@ -17,7 +17,12 @@ pub const BPF_ALIGN_OF_U128: usize = 8;
 pub type ProgramResult = ResultGeneric<(), ProgramError>;
 
 pub type ProcessInstruction =
-    fn(program_id: &Pubkey, accounts: &[AccountInfo], instruction_data: &[u8], Box<dyn Syscalls>) -> ProgramResult;
+    for<'a> fn(program_id: &'a Pubkey, accounts: &'a [AccountInfo<'a>], instruction_data: &'a [u8], &mut dyn Syscalls) -> ProgramResult;
+
+// Symbol the entrypoint! macro exports from a library built without the inline feature
+#[allow(improper_ctypes_definitions)]
+pub type ExternalProcessInstruction =
+    for<'a> extern "C" fn(program_id: &'a Pubkey, accounts: &'a [AccountInfo<'a>], instruction_data: &'a [u8], &mut dyn Syscalls) -> ProgramResult;
 
 
 #[macro_export]
@@ -25,35 +30,15 @@ macro_rules! entrypoint {
     ($process_instruction:ident) => {
 
         #[cfg(not(feature="inline"))]
-        /// # Safety
         #[no_mangle]
-        pub unsafe extern "C" fn entrypoint<'g>(program_id: &'g $crate::pubkey::Pubkey, accounts: &'g [$crate::account_info::AccountInfo<'g>], instruction_data: &'g [u8], mut syscalls: Box<dyn $crate::syscalls::Syscalls>) -> ProgramResult {
-            let default_syscalls = $crate::syscalls::SYSCALLS.swap(&mut syscalls, std::sync::atomic::Ordering::Relaxed);
-
-            let res = $process_instruction(program_id, accounts, instruction_data);
-
-            let _ = $crate::syscalls::SYSCALLS.swap(default_syscalls, std::sync::atomic::Ordering::Relaxed);
-            std::mem::forget(syscalls);
-
-            res
+        #[allow(improper_ctypes_definitions)]
+        pub extern "C" fn entrypoint<'a>(program_id: &'a $crate::pubkey::Pubkey, accounts: &'a [$crate::account_info::AccountInfo<'a>], instruction_data: &'a [u8], syscalls: &mut dyn $crate::syscalls::Syscalls) -> $crate::entrypoint::ProgramResult {
+            $crate::syscalls::enter(syscalls, || $process_instruction(program_id, accounts, instruction_data))
         }
 
         #[cfg(feature="inline")]
-        pub fn entrypoint(program_id: &$crate::pubkey::Pubkey, accounts: &[$crate::account_info::AccountInfo], instruction_data: &[u8], mut syscalls: Box<dyn $crate::syscalls::Syscalls>) -> ProgramResult {
-            unsafe {
-                let default_syscalls = $crate::syscalls::SYSCALLS.swap(&mut syscalls, std::sync::atomic::Ordering::Relaxed);
-
-                let res = $process_instruction( // Allow no generics in fn declaration
-                    std::mem::transmute::<&Pubkey, &'static Pubkey>(program_id),
-                    std::mem::transmute::<&[AccountInfo], &'static [AccountInfo<'static>]>(accounts),
-                    std::mem::transmute::<&[u8], &'static [u8]>(instruction_data)
-                );
-
-                let _ = $crate::syscalls::SYSCALLS.swap(default_syscalls, std::sync::atomic::Ordering::Relaxed);
-                std::mem::forget(syscalls);
-
-                res
-            }
+        pub fn entrypoint<'a>(program_id: &'a $crate::pubkey::Pubkey, accounts: &'a [$crate::account_info::AccountInfo<'a>], instruction_data: &'a [u8], syscalls: &mut dyn $crate::syscalls::Syscalls) -> $crate::entrypoint::ProgramResult {
+            $crate::syscalls::enter(syscalls, || $process_instruction(program_id, accounts, instruction_data))
         }
     };
 }
@@ -394,7 +394,7 @@ pub struct ProcessedSiblingInstruction {
 /// Then B's processed sibling instruction list is: `[A]`
 /// Then F's processed sibling instruction list is: `[E, C]`
 pub fn get_processed_sibling_instruction(index: usize) -> Option<Instruction> {
-    syscalls!().get_processed_sibling_instruction(index)
+    syscalls::with(|syscalls| syscalls.get_processed_sibling_instruction(index))
 }
 
 // Stack height when processing transaction-level instructions
@@ -404,5 +404,5 @@ pub const TRANSACTION_LEVEL_STACK_HEIGHT: usize = 1;
 /// TRANSACTION_LEVEL_STACK_HEIGHT, fist invoked inner instruction is height
 /// TRANSACTION_LEVEL_STACK_HEIGHT + 1, etc...
 pub fn get_stack_height() -> usize {
-    syscalls!().get_stack_height()
+    syscalls::with(|syscalls| syscalls.get_stack_height())
 }
@@ -1,6 +1,10 @@
+#![allow(incomplete_features)]
+#![cfg_attr(RUSTC_WITH_SPECIALIZATION, feature(specialization))]
+#![cfg_attr(RUSTC_NEEDS_PROC_MACRO_HYGIENE, feature(proc_macro_hygiene))]
 extern crate self as solana_program;
 
 pub mod account_info;
+pub mod address_lookup_table;
 pub mod alt_bn128;
 pub(crate) mod atomic_u64;
 pub mod big_mod_exp;
@@ -9,6 +13,7 @@ pub mod borsh;
 pub mod borsh0_10;
 pub mod borsh0_9;
 pub mod bpf_loader;
+pub mod bpf_loader_upgradeable;
 pub mod debug_account_data;
 pub mod decode_error;
 pub mod entrypoint;
@@ -16,7 +21,9 @@ pub mod hash;
 pub mod instruction;
 pub mod keccak;
 pub mod lamports;
+pub mod loader_upgradeable_instruction;
 pub mod log;
+pub mod message;
 pub mod native_token;
 pub mod program;
 pub mod program_error;
@@ -31,6 +38,7 @@ pub mod syscalls;
 pub mod system_instruction;
 pub mod system_program;
 pub mod clock;
+pub mod compute_budget;
 pub mod sysvar;
 pub mod rent;
 pub mod program_utils;
@@ -29,18 +29,18 @@ macro_rules! msg {
 /// Print a string to the log.
 #[inline]
 pub fn sol_log(message: &str) {
-    syscalls!().sol_log(message)
+    syscalls::with(|syscalls| syscalls.sol_log(message))
 }
 
 /// Print 64-bit values represented as hexadecimal to the log.
 #[inline]
 pub fn sol_log_64(arg1: u64, arg2: u64, arg3: u64, arg4: u64, arg5: u64) {
-    syscalls!().sol_log_64(arg1, arg2, arg3, arg4, arg5)
+    syscalls::with(|syscalls| syscalls.sol_log_64(arg1, arg2, arg3, arg4, arg5))
 }
 
 /// Print some slices as base64.
 pub fn sol_log_data(data: &[&[u8]]) {
-    syscalls!().sol_log_data(data)
+    syscalls::with(|syscalls| syscalls.sol_log_data(data))
 }
 
 #[allow(dead_code)]
@@ -67,7 +67,7 @@ pub fn sol_log_params(accounts: &[AccountInfo], data: &[u8]) {
         msg!("- Account data length");
         sol_log_64(0, 0, 0, 0, account.data_len() as u64);
         msg!("- Owner");
-        account.owner.log();
+        account.owner.get().log();
     }
     msg!("Instruction data");
     sol_log_slice(data);
@@ -76,5 +76,5 @@ pub fn sol_log_params(accounts: &[AccountInfo], data: &[u8]) {
 /// Print the remaining compute units available to the program.
 #[inline]
 pub fn sol_log_compute_units() {
-    syscalls!().sol_log_compute_units()
+    syscalls::with(|syscalls| syscalls.sol_log_compute_units())
 }
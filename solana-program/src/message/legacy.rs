@@ -0,0 +1,186 @@
+//! The original and current Solana message format.
+
+use {
+    crate::{
+        hash::Hash,
+        instruction::{AccountMeta, CompiledInstruction, Instruction},
+        message::MessageHeader,
+        pubkey::Pubkey,
+        sanitize::{Sanitize, SanitizeError},
+        short_vec,
+    },
+    std::collections::BTreeMap,
+};
+
+/// A Solana transaction message (legacy).
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone, AbiExample)]
+#[serde(rename_all = "camelCase")]
+pub struct Message {
+    /// The message header, identifying signed and read-only `account_keys`.
+    pub header: MessageHeader,
+
+    /// All the account keys used by this transaction.
+    #[serde(with = "short_vec")]
+    pub account_keys: Vec<Pubkey>,
+
+    /// The id of a recent ledger entry.
+    pub recent_blockhash: Hash,
+
+    /// Programs that will be executed in sequence and committed in one atomic transaction if all
+    /// succeed.
+    #[serde(with = "short_vec")]
+    pub instructions: Vec<CompiledInstruction>,
+}
+
+impl Sanitize for Message {
+    fn sanitize(&self) -> Result<(), SanitizeError> {
+        // signing area and read-only non-signing area should not overlap
+        if self.header.num_required_signatures as usize
+            + self.header.num_readonly_unsigned_accounts as usize
+            > self.account_keys.len()
+        {
+            return Err(SanitizeError::IndexOutOfBounds);
+        }
+
+        // there should be at least 1 RW fee-payer account.
+        if self.header.num_readonly_signed_accounts >= self.header.num_required_signatures {
+            return Err(SanitizeError::IndexOutOfBounds);
+        }
+
+        for ci in &self.instructions {
+            if ci.program_id_index as usize >= self.account_keys.len() {
+                return Err(SanitizeError::IndexOutOfBounds);
+            }
+            // A program cannot be a payer.
+            if ci.program_id_index == 0 {
+                return Err(SanitizeError::IndexOutOfBounds);
+            }
+            for ai in &ci.accounts {
+                if *ai as usize >= self.account_keys.len() {
+                    return Err(SanitizeError::IndexOutOfBounds);
+                }
+            }
+        }
+        self.account_keys.sanitize()?;
+        self.recent_blockhash.sanitize()?;
+        self.instructions.sanitize()?;
+        Ok(())
+    }
+}
+
+impl Message {
+    /// Create a new `Message`, the payer comes first and signs.
+    pub fn new(instructions: &[Instruction], payer: Option<&Pubkey>) -> Self {
+        Self::new_with_blockhash(instructions, payer, &Hash::default())
+    }
+
+    pub fn new_with_blockhash(
+        instructions: &[Instruction],
+        payer: Option<&Pubkey>,
+        blockhash: &Hash,
+    ) -> Self {
+        // (is_signer, is_writable) of every key, ordered as the sdk orders them
+        let mut keys: BTreeMap<Pubkey, (bool, bool)> = BTreeMap::new();
+        for instruction in instructions {
+            keys.entry(instruction.program_id).or_insert((false, false));
+            for meta in instruction.accounts.iter() {
+                let entry = keys.entry(meta.pubkey).or_insert((false, false));
+                entry.0 |= meta.is_signer;
+                entry.1 |= meta.is_writable;
+            }
+        }
+        if let Some(payer) = payer {
+            keys.remove(payer);
+        }
+
+        let group = |signer: bool, writable: bool| -> Vec<Pubkey> {
+            keys.iter()
+                .filter(|(_, flags)| **flags == (signer, writable))
+                .map(|(key, _)| *key)
+                .collect()
+        };
+        let writable_signers: Vec<Pubkey> = payer.into_iter().copied().chain(group(true, true)).collect();
+        let readonly_signers = group(true, false);
+        let writable_non_signers = group(false, true);
+        let readonly_non_signers = group(false, false);
+
+        let header = MessageHeader {
+            num_required_signatures: (writable_signers.len() + readonly_signers.len()) as u8,
+            num_readonly_signed_accounts: readonly_signers.len() as u8,
+            num_readonly_unsigned_accounts: readonly_non_signers.len() as u8,
+        };
+        let account_keys: Vec<Pubkey> = writable_signers.into_iter()
+            .chain(readonly_signers)
+            .chain(writable_non_signers)
+            .chain(readonly_non_signers)
+            .collect();
+
+        let position = |key: &Pubkey| account_keys.iter().position(|k| k == key).unwrap() as u8;
+        let instructions = instructions.iter()
+            .map(|instruction| CompiledInstruction {
+                program_id_index: position(&instruction.program_id),
+                accounts: instruction.accounts.iter().map(|meta| position(&meta.pubkey)).collect(),
+                data: instruction.data.clone(),
+            })
+            .collect();
+
+        Self {
+            header,
+            account_keys,
+            recent_blockhash: *blockhash,
+            instructions,
+        }
+    }
+
+    /// Serialize the message, these are the bytes signers sign.
+    pub fn serialize(&self) -> Vec<u8> {
+        bincode::serialize(self).unwrap()
+    }
+
+    pub fn is_signer(&self, i: usize) -> bool {
+        i < self.header.num_required_signatures as usize
+    }
+
+    pub fn is_writable_index(&self, i: usize) -> bool {
+        let num_required_signatures = self.header.num_required_signatures as usize;
+        let num_signed_writable = num_required_signatures
+            .saturating_sub(self.header.num_readonly_signed_accounts as usize);
+        let num_unsigned_writable = self.account_keys.len()
+            .saturating_sub(num_required_signatures)
+            .saturating_sub(self.header.num_readonly_unsigned_accounts as usize);
+        if i < num_required_signatures {
+            i < num_signed_writable
+        } else {
+            i - num_required_signatures < num_unsigned_writable
+        }
+    }
+
+    pub fn is_key_called_as_program(&self, key_index: usize) -> bool {
+        self.instructions.iter().any(|ix| ix.program_id_index as usize == key_index)
+    }
+
+    /// Called programs are demoted to read-only, as the runtime does.
+    pub fn is_writable(&self, i: usize) -> bool {
+        self.is_writable_index(i) && !self.is_key_called_as_program(i)
+    }
+
+    /// Instructions with their keys and privileges restored.
+    pub fn decompile_instructions(&self) -> Vec<Instruction> {
+        self.instructions.iter()
+            .map(|ix| Instruction {
+                program_id: self.account_keys[ix.program_id_index as usize],
+                accounts: ix.accounts.iter()
+                    .map(|i| {
+                        let i = *i as usize;
+                        AccountMeta {
+                            pubkey: self.account_keys[i],
+                            is_signer: self.is_signer(i),
+                            is_writable: self.is_writable(i),
+                        }
+                    })
+                    .collect(),
+                data: ix.data.clone(),
+            })
+            .collect()
+    }
+}
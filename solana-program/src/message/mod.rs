@@ -0,0 +1,30 @@
+//! Sequences of instructions executed within a single transaction.
+
+pub mod legacy;
+pub mod v0;
+mod versions;
+
+pub use legacy::Message;
+pub use versions::{VersionedMessage, MESSAGE_VERSION_PREFIX};
+
+/// The length of a message header in bytes.
+pub const MESSAGE_HEADER_LENGTH: usize = 3;
+
+/// Describes the organization of a `Message`'s account keys.
+///
+/// Keys are ordered writable signers, readonly signers, writable
+/// non-signers, then readonly non-signers.
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone, Copy, AbiExample)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageHeader {
+    /// The number of signatures required for this message to be considered
+    /// valid. The signers of those signatures must match the first
+    /// `num_required_signatures` of the account keys.
+    pub num_required_signatures: u8,
+    /// The last `num_readonly_signed_accounts` of the signed keys are read-only
+    /// accounts.
+    pub num_readonly_signed_accounts: u8,
+    /// The last `num_readonly_unsigned_accounts` of the unsigned keys are
+    /// read-only accounts.
+    pub num_readonly_unsigned_accounts: u8,
+}
@@ -0,0 +1,328 @@
+//! Version 0 messages, which can load accounts from address lookup tables.
+
+use {
+    crate::{
+        address_lookup_table::AddressLookupTableAccount,
+        hash::Hash,
+        instruction::{AccountMeta, CompiledInstruction, Instruction},
+        message::MessageHeader,
+        pubkey::Pubkey,
+        sanitize::{Sanitize, SanitizeError},
+        short_vec,
+    },
+    std::collections::BTreeMap,
+};
+
+/// Address table lookups describe an on-chain address lookup table to use
+/// for loading more readonly and writable accounts in a single tx.
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone, AbiExample)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageAddressTableLookup {
+    /// Address lookup table account key
+    pub account_key: Pubkey,
+    /// List of indexes used to load writable account addresses
+    #[serde(with = "short_vec")]
+    pub writable_indexes: Vec<u8>,
+    /// List of indexes used to load readonly account addresses
+    #[serde(with = "short_vec")]
+    pub readonly_indexes: Vec<u8>,
+}
+
+/// A Solana transaction message (v0).
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone, AbiExample)]
+#[serde(rename_all = "camelCase")]
+pub struct Message {
+    /// The message header, identifying signed and read-only `account_keys`.
+    /// Header values only describe static `account_keys`, they do not describe
+    /// any additional account keys loaded via address table lookups.
+    pub header: MessageHeader,
+
+    /// List of accounts loaded by this transaction.
+    #[serde(with = "short_vec")]
+    pub account_keys: Vec<Pubkey>,
+
+    /// The blockhash of a recent block.
+    pub recent_blockhash: Hash,
+
+    /// Instructions that invoke a designated program, are executed in sequence,
+    /// and committed in one atomic transaction if all succeed.
+    ///
+    /// Account indexes address static keys first, then the writable and
+    /// readonly addresses loaded from `address_table_lookups`, in that order.
+    #[serde(with = "short_vec")]
+    pub instructions: Vec<CompiledInstruction>,
+
+    /// List of address table lookups used to load additional accounts
+    /// for this transaction.
+    #[serde(with = "short_vec")]
+    pub address_table_lookups: Vec<MessageAddressTableLookup>,
+}
+
+/// Errors raised while compiling instructions into a `Message`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum CompileError {
+    AccountIndexOverflow,
+    AddressTableLookupIndexOverflow,
+}
+
+impl Sanitize for Message {
+    fn sanitize(&self) -> Result<(), SanitizeError> {
+        let num_static_account_keys = self.account_keys.len();
+        if usize::from(self.header.num_required_signatures)
+            .saturating_add(usize::from(self.header.num_readonly_unsigned_accounts))
+            > num_static_account_keys
+        {
+            return Err(SanitizeError::IndexOutOfBounds);
+        }
+
+        // there should be at least 1 RW fee-payer account.
+        if self.header.num_readonly_signed_accounts >= self.header.num_required_signatures {
+            return Err(SanitizeError::InvalidValue);
+        }
+
+        let num_dynamic_account_keys = {
+            let mut total_lookup_keys: usize = 0;
+            for lookup in &self.address_table_lookups {
+                let num_lookup_indexes = lookup
+                    .writable_indexes
+                    .len()
+                    .saturating_add(lookup.readonly_indexes.len());
+
+                // each lookup table must be used to load at least one account
+                if num_lookup_indexes == 0 {
+                    return Err(SanitizeError::InvalidValue);
+                }
+
+                total_lookup_keys = total_lookup_keys.saturating_add(num_lookup_indexes);
+            }
+            total_lookup_keys
+        };
+
+        // this is redundant with the above sanitization checks which require that:
+        // 1) the header describes at least 1 RW account
+        // 2) the header doesn't describe more account keys than the number of account keys
+        if num_static_account_keys == 0 {
+            return Err(SanitizeError::InvalidValue);
+        }
+
+        // the combined number of static and dynamic account keys must be <= 256
+        // since account indices are encoded as `u8`
+        let total_account_keys = num_static_account_keys.saturating_add(num_dynamic_account_keys);
+        if total_account_keys > 256 {
+            return Err(SanitizeError::IndexOutOfBounds);
+        }
+
+        for ci in &self.instructions {
+            // A program cannot be a payer, nor can it be loaded from a lookup table.
+            if ci.program_id_index == 0 || ci.program_id_index as usize >= num_static_account_keys {
+                return Err(SanitizeError::IndexOutOfBounds);
+            }
+            for ai in &ci.accounts {
+                if *ai as usize >= total_account_keys {
+                    return Err(SanitizeError::IndexOutOfBounds);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Message {
+    /// Compile instructions into a v0 message. Keys found in one of the lookup
+    /// tables are loaded from it, unless they sign or are invoked as programs.
+    pub fn try_compile(
+        payer: &Pubkey,
+        instructions: &[Instruction],
+        address_lookup_table_accounts: &[AddressLookupTableAccount],
+        recent_blockhash: Hash,
+    ) -> Result<Self, CompileError> {
+        // (is_signer, is_writable, is_invoked) of every key, ordered as the sdk orders them
+        let mut keys: BTreeMap<Pubkey, (bool, bool, bool)> = BTreeMap::new();
+        for instruction in instructions {
+            keys.entry(instruction.program_id).or_insert((false, false, false)).2 = true;
+            for meta in instruction.accounts.iter() {
+                let entry = keys.entry(meta.pubkey).or_insert((false, false, false));
+                entry.0 |= meta.is_signer;
+                entry.1 |= meta.is_writable;
+            }
+        }
+        keys.remove(payer);
+
+        let mut address_table_lookups = Vec::new();
+        let mut loaded_writable = Vec::new();
+        let mut loaded_readonly = Vec::new();
+        for table in address_lookup_table_accounts {
+            let mut lookup = MessageAddressTableLookup {
+                account_key: table.key,
+                writable_indexes: Vec::new(),
+                readonly_indexes: Vec::new(),
+            };
+            let lookupable: Vec<(Pubkey, bool)> = keys.iter()
+                .filter(|(_, (signer, _, invoked))| !signer && !invoked)
+                .map(|(key, (_, writable, _))| (*key, *writable))
+                .collect();
+            for (key, writable) in lookupable {
+                let index = match table.addresses.iter().position(|address| address == &key) {
+                    Some(index) => u8::try_from(index).map_err(|_| CompileError::AddressTableLookupIndexOverflow)?,
+                    None => continue,
+                };
+                keys.remove(&key);
+                if writable {
+                    lookup.writable_indexes.push(index);
+                    loaded_writable.push(key);
+                } else {
+                    lookup.readonly_indexes.push(index);
+                    loaded_readonly.push(key);
+                }
+            }
+            if !lookup.writable_indexes.is_empty() || !lookup.readonly_indexes.is_empty() {
+                address_table_lookups.push(lookup);
+            }
+        }
+
+        let group = |signer: bool, writable: bool| -> Vec<Pubkey> {
+            keys.iter()
+                .filter(|(_, flags)| (flags.0, flags.1) == (signer, writable))
+                .map(|(key, _)| *key)
+                .collect()
+        };
+        let writable_signers: Vec<Pubkey> = std::iter::once(*payer).chain(group(true, true)).collect();
+        let readonly_signers = group(true, false);
+        let writable_non_signers = group(false, true);
+        let readonly_non_signers = group(false, false);
+
+        let header = MessageHeader {
+            num_required_signatures: (writable_signers.len() + readonly_signers.len()) as u8,
+            num_readonly_signed_accounts: readonly_signers.len() as u8,
+            num_readonly_unsigned_accounts: readonly_non_signers.len() as u8,
+        };
+        let account_keys: Vec<Pubkey> = writable_signers.into_iter()
+            .chain(readonly_signers)
+            .chain(writable_non_signers)
+            .chain(readonly_non_signers)
+            .collect();
+
+        let all_keys: Vec<&Pubkey> = account_keys.iter()
+            .chain(loaded_writable.iter())
+            .chain(loaded_readonly.iter())
+            .collect();
+        if all_keys.len() > 256 {
+            return Err(CompileError::AccountIndexOverflow);
+        }
+        let position = |key: &Pubkey| all_keys.iter().position(|k| *k == key).unwrap() as u8;
+        let instructions = instructions.iter()
+            .map(|instruction| CompiledInstruction {
+                program_id_index: position(&instruction.program_id),
+                accounts: instruction.accounts.iter().map(|meta| position(&meta.pubkey)).collect(),
+                data: instruction.data.clone(),
+            })
+            .collect();
+
+        Ok(Self {
+            header,
+            account_keys,
+            recent_blockhash,
+            instructions,
+            address_table_lookups,
+        })
+    }
+}
+
+/// Addresses resolved from the lookup tables of a message.
+#[derive(Clone, Default, Debug, PartialEq, Eq)]
+pub struct LoadedAddresses {
+    /// List of addresses for writable loaded accounts
+    pub writable: Vec<Pubkey>,
+    /// List of addresses for read-only loaded accounts
+    pub readonly: Vec<Pubkey>,
+}
+
+impl LoadedAddresses {
+    pub fn len(&self) -> usize {
+        self.writable.len().saturating_add(self.readonly.len())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// A v0 message together with the addresses its lookups resolved to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoadedMessage {
+    pub message: Message,
+    pub loaded_addresses: LoadedAddresses,
+}
+
+impl LoadedMessage {
+    pub fn new(message: Message, loaded_addresses: LoadedAddresses) -> Self {
+        Self {
+            message,
+            loaded_addresses,
+        }
+    }
+
+    /// Static keys, then loaded writable keys, then loaded readonly keys.
+    pub fn account_keys(&self) -> Vec<Pubkey> {
+        self.message.account_keys.iter()
+            .chain(self.loaded_addresses.writable.iter())
+            .chain(self.loaded_addresses.readonly.iter())
+            .copied()
+            .collect()
+    }
+
+    pub fn is_signer(&self, i: usize) -> bool {
+        i < self.message.header.num_required_signatures as usize
+    }
+
+    fn is_writable_index(&self, i: usize) -> bool {
+        let header = &self.message.header;
+        let num_account_keys = self.message.account_keys.len();
+        let num_signed_accounts = usize::from(header.num_required_signatures);
+        if i >= num_account_keys {
+            let loaded_addresses_index = i.saturating_sub(num_account_keys);
+            loaded_addresses_index < self.loaded_addresses.writable.len()
+        } else if i >= num_signed_accounts {
+            let num_unsigned_accounts = num_account_keys.saturating_sub(num_signed_accounts);
+            let num_writable_unsigned_accounts = num_unsigned_accounts
+                .saturating_sub(usize::from(header.num_readonly_unsigned_accounts));
+            let unsigned_account_index = i.saturating_sub(num_signed_accounts);
+            unsigned_account_index < num_writable_unsigned_accounts
+        } else {
+            let num_writable_signed_accounts = num_signed_accounts
+                .saturating_sub(usize::from(header.num_readonly_signed_accounts));
+            i < num_writable_signed_accounts
+        }
+    }
+
+    pub fn is_key_called_as_program(&self, key_index: usize) -> bool {
+        self.message.instructions.iter().any(|ix| ix.program_id_index as usize == key_index)
+    }
+
+    /// Called programs are demoted to read-only, as the runtime does.
+    pub fn is_writable(&self, i: usize) -> bool {
+        self.is_writable_index(i) && !self.is_key_called_as_program(i)
+    }
+
+    /// Instructions with their keys and privileges restored.
+    pub fn decompile_instructions(&self) -> Vec<Instruction> {
+        let account_keys = self.account_keys();
+        self.message.instructions.iter()
+            .map(|ix| Instruction {
+                program_id: account_keys[ix.program_id_index as usize],
+                accounts: ix.accounts.iter()
+                    .map(|i| {
+                        let i = *i as usize;
+                        AccountMeta {
+                            pubkey: account_keys[i],
+                            is_signer: self.is_signer(i),
+                            is_writable: self.is_writable(i),
+                        }
+                    })
+                    .collect(),
+                data: ix.data.clone(),
+            })
+            .collect()
+    }
+}
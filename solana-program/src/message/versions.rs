@@ -0,0 +1,112 @@
+//! Messages of either format, told apart by their first byte.
+
+use crate::{
+    hash::Hash,
+    instruction::CompiledInstruction,
+    message::{legacy, v0, MessageHeader},
+    pubkey::Pubkey,
+    sanitize::{Sanitize, SanitizeError},
+};
+
+/// Bit mask that indicates whether a serialized message is versioned.
+pub const MESSAGE_VERSION_PREFIX: u8 = 0x80;
+
+/// Either a legacy message or a v0 message.
+///
+/// Legacy messages start with `num_required_signatures`, which can't have the
+/// high bit set, versioned messages start with the version OR'd with
+/// `MESSAGE_VERSION_PREFIX`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum VersionedMessage {
+    Legacy(legacy::Message),
+    V0(v0::Message),
+}
+
+impl VersionedMessage {
+    pub fn header(&self) -> &MessageHeader {
+        match self {
+            Self::Legacy(message) => &message.header,
+            Self::V0(message) => &message.header,
+        }
+    }
+
+    pub fn static_account_keys(&self) -> &[Pubkey] {
+        match self {
+            Self::Legacy(message) => &message.account_keys,
+            Self::V0(message) => &message.account_keys,
+        }
+    }
+
+    pub fn recent_blockhash(&self) -> &Hash {
+        match self {
+            Self::Legacy(message) => &message.recent_blockhash,
+            Self::V0(message) => &message.recent_blockhash,
+        }
+    }
+
+    pub fn set_recent_blockhash(&mut self, recent_blockhash: Hash) {
+        match self {
+            Self::Legacy(message) => message.recent_blockhash = recent_blockhash,
+            Self::V0(message) => message.recent_blockhash = recent_blockhash,
+        }
+    }
+
+    pub fn instructions(&self) -> &[CompiledInstruction] {
+        match self {
+            Self::Legacy(message) => &message.instructions,
+            Self::V0(message) => &message.instructions,
+        }
+    }
+
+    /// Lookups of v0 messages, legacy ones have none.
+    pub fn address_table_lookups(&self) -> Option<&[v0::MessageAddressTableLookup]> {
+        match self {
+            Self::Legacy(_) => None,
+            Self::V0(message) => Some(&message.address_table_lookups),
+        }
+    }
+
+    /// Serialize the message, these are the bytes signers sign.
+    pub fn serialize(&self) -> Vec<u8> {
+        match self {
+            Self::Legacy(message) => message.serialize(),
+            Self::V0(message) => {
+                let mut bytes = vec![MESSAGE_VERSION_PREFIX];
+                bytes.extend(bincode::serialize(message).unwrap());
+                bytes
+            }
+        }
+    }
+
+    /// Decode a message of either version, bytes left over are an error.
+    pub fn deserialize(bytes: &[u8]) -> Result<Self, SanitizeError> {
+        let prefix = *bytes.first().ok_or(SanitizeError::InvalidValue)?;
+        let message = if prefix & MESSAGE_VERSION_PREFIX == 0 {
+            Self::Legacy(bincode::deserialize(bytes).map_err(|_| SanitizeError::InvalidValue)?)
+        } else {
+            match prefix & !MESSAGE_VERSION_PREFIX {
+                0 => Self::V0(bincode::deserialize(&bytes[1..]).map_err(|_| SanitizeError::InvalidValue)?),
+                _ => return Err(SanitizeError::InvalidValue),
+            }
+        };
+        if message.serialize().len() != bytes.len() {
+            return Err(SanitizeError::InvalidValue);
+        }
+        Ok(message)
+    }
+}
+
+impl Sanitize for VersionedMessage {
+    fn sanitize(&self) -> Result<(), SanitizeError> {
+        match self {
+            Self::Legacy(message) => message.sanitize(),
+            Self::V0(message) => message.sanitize(),
+        }
+    }
+}
+
+impl From<legacy::Message> for VersionedMessage {
+    fn from(message: legacy::Message) -> Self {
+        Self::Legacy(message)
+    }
+}
@@ -38,7 +38,7 @@ pub fn invoke_signed_unchecked(
     account_infos: &[AccountInfo],
     signers_seeds: &[&[&[u8]]],
 ) -> ProgramResult {
-    syscalls!().invoke_signed_unchecked(instruction, account_infos, signers_seeds)
+    syscalls::with(|syscalls| syscalls.invoke_signed_unchecked(instruction, account_infos, signers_seeds))
 }
 
 /// Maximum size that can be set using [`set_return_data`].
@@ -46,10 +46,10 @@ pub const MAX_RETURN_DATA: usize = 1024;
 
 /// Set the running program's return data.
 pub fn set_return_data(data: &[u8]) {
-    syscalls!().set_return_data(data)
+    syscalls::with(|syscalls| syscalls.set_return_data(data))
 }
 
 /// Get the return data from an invoked program.
 pub fn get_return_data() -> Option<(Pubkey, Vec<u8>)> {
-    syscalls!().get_return_data()
+    syscalls::with(|syscalls| syscalls.get_return_data())
 }
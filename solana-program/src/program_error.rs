@@ -59,6 +59,23 @@ pub enum ProgramError {
     MaxInstructionTraceLengthExceeded,
     #[error("Builtin programs must consume compute units")]
     BuiltinProgramsMustConsumeComputeUnits,
+    #[error("Invalid account owner")]
+    InvalidAccountOwner,
+    #[error("Program arithmetic overflowed")]
+    ArithmeticOverflow,
+    #[error("Account is immutable")]
+    Immutable,
+    #[error("Incorrect authority provided")]
+    IncorrectAuthority,
+    // Syscall failures, the runtime reports them as ProgramFailedToComplete
+    #[error("Invoked an instruction with data that is too large")]
+    MaxInstructionDataLenExceeded,
+    #[error("Invoked an instruction with too many accounts")]
+    MaxInstructionAccountsExceeded,
+    #[error("Invoked an instruction with too many account info's")]
+    MaxInstructionAccountInfosExceeded,
+    #[error("Return data too large")]
+    ReturnDataTooLarge,
 }
 
 pub trait PrintProgramError {
@@ -107,6 +124,16 @@ impl PrintProgramError for ProgramError {
             Self::BuiltinProgramsMustConsumeComputeUnits => {
                 msg!("Error: BuiltinProgramsMustConsumeComputeUnits")
             }
+            Self::InvalidAccountOwner => msg!("Error: InvalidAccountOwner"),
+            Self::ArithmeticOverflow => msg!("Error: ArithmeticOverflow"),
+            Self::Immutable => msg!("Error: Immutable"),
+            Self::IncorrectAuthority => msg!("Error: IncorrectAuthority"),
+            Self::MaxInstructionDataLenExceeded => msg!("Error: MaxInstructionDataLenExceeded"),
+            Self::MaxInstructionAccountsExceeded => msg!("Error: MaxInstructionAccountsExceeded"),
+            Self::MaxInstructionAccountInfosExceeded => {
+                msg!("Error: MaxInstructionAccountInfosExceeded")
+            }
+            Self::ReturnDataTooLarge => msg!("Error: ReturnDataTooLarge"),
         }
     }
 }
@@ -141,6 +168,14 @@ pub const MAX_ACCOUNTS_DATA_ALLOCATIONS_EXCEEDED: u64 = to_builtin!(19);
 pub const INVALID_ACCOUNT_DATA_REALLOC: u64 = to_builtin!(20);
 pub const MAX_INSTRUCTION_TRACE_LENGTH_EXCEEDED: u64 = to_builtin!(21);
 pub const BUILTIN_PROGRAMS_MUST_CONSUME_COMPUTE_UNITS: u64 = to_builtin!(22);
+pub const INVALID_ACCOUNT_OWNER: u64 = to_builtin!(23);
+pub const ARITHMETIC_OVERFLOW: u64 = to_builtin!(24);
+pub const IMMUTABLE: u64 = to_builtin!(25);
+pub const INCORRECT_AUTHORITY: u64 = to_builtin!(26);
+pub const MAX_INSTRUCTION_DATA_LEN_EXCEEDED: u64 = to_builtin!(27);
+pub const MAX_INSTRUCTION_ACCOUNTS_EXCEEDED: u64 = to_builtin!(28);
+pub const MAX_INSTRUCTION_ACCOUNT_INFOS_EXCEEDED: u64 = to_builtin!(29);
+pub const RETURN_DATA_TOO_LARGE: u64 = to_builtin!(30);
 // Warning: Any new program errors added here must also be:
 // - Added to the below conversions
 // - Added as an equivalent to InstructionError
@@ -177,6 +212,16 @@ impl From<ProgramError> for u64 {
             ProgramError::BuiltinProgramsMustConsumeComputeUnits => {
                 BUILTIN_PROGRAMS_MUST_CONSUME_COMPUTE_UNITS
             }
+            ProgramError::InvalidAccountOwner => INVALID_ACCOUNT_OWNER,
+            ProgramError::ArithmeticOverflow => ARITHMETIC_OVERFLOW,
+            ProgramError::Immutable => IMMUTABLE,
+            ProgramError::IncorrectAuthority => INCORRECT_AUTHORITY,
+            ProgramError::MaxInstructionDataLenExceeded => MAX_INSTRUCTION_DATA_LEN_EXCEEDED,
+            ProgramError::MaxInstructionAccountsExceeded => MAX_INSTRUCTION_ACCOUNTS_EXCEEDED,
+            ProgramError::MaxInstructionAccountInfosExceeded => {
+                MAX_INSTRUCTION_ACCOUNT_INFOS_EXCEEDED
+            }
+            ProgramError::ReturnDataTooLarge => RETURN_DATA_TOO_LARGE,
             ProgramError::Custom(error) => {
                 if error == 0 {
                     CUSTOM_ZERO
@@ -215,6 +260,14 @@ impl From<u64> for ProgramError {
             BUILTIN_PROGRAMS_MUST_CONSUME_COMPUTE_UNITS => {
                 Self::BuiltinProgramsMustConsumeComputeUnits
             }
+            INVALID_ACCOUNT_OWNER => Self::InvalidAccountOwner,
+            ARITHMETIC_OVERFLOW => Self::ArithmeticOverflow,
+            IMMUTABLE => Self::Immutable,
+            INCORRECT_AUTHORITY => Self::IncorrectAuthority,
+            MAX_INSTRUCTION_DATA_LEN_EXCEEDED => Self::MaxInstructionDataLenExceeded,
+            MAX_INSTRUCTION_ACCOUNTS_EXCEEDED => Self::MaxInstructionAccountsExceeded,
+            MAX_INSTRUCTION_ACCOUNT_INFOS_EXCEEDED => Self::MaxInstructionAccountInfosExceeded,
+            RETURN_DATA_TOO_LARGE => Self::ReturnDataTooLarge,
             _ => Self::Custom(error as u32),
         }
     }
@@ -253,6 +306,10 @@ impl TryFrom<InstructionError> for ProgramError {
             Self::Error::BuiltinProgramsMustConsumeComputeUnits => {
                 Ok(Self::BuiltinProgramsMustConsumeComputeUnits)
             }
+            Self::Error::InvalidAccountOwner => Ok(Self::InvalidAccountOwner),
+            Self::Error::ArithmeticOverflow => Ok(Self::ArithmeticOverflow),
+            Self::Error::Immutable => Ok(Self::Immutable),
+            Self::Error::IncorrectAuthority => Ok(Self::IncorrectAuthority),
             _ => Err(error),
         }
     }
@@ -289,6 +346,14 @@ where
             BUILTIN_PROGRAMS_MUST_CONSUME_COMPUTE_UNITS => {
                 Self::BuiltinProgramsMustConsumeComputeUnits
             }
+            INVALID_ACCOUNT_OWNER => Self::InvalidAccountOwner,
+            ARITHMETIC_OVERFLOW => Self::ArithmeticOverflow,
+            IMMUTABLE => Self::Immutable,
+            INCORRECT_AUTHORITY => Self::IncorrectAuthority,
+            MAX_INSTRUCTION_DATA_LEN_EXCEEDED
+            | MAX_INSTRUCTION_ACCOUNTS_EXCEEDED
+            | MAX_INSTRUCTION_ACCOUNT_INFOS_EXCEEDED
+            | RETURN_DATA_TOO_LARGE => Self::ProgramFailedToComplete,
             _ => {
                 // A valid custom error has no bits set in the upper 32
                 if error >> BUILTIN_BIT_SHIFT == 0 {
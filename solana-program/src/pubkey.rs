@@ -265,7 +265,7 @@ impl Pubkey {
 
     /// Log a `Pubkey` from a program
     pub fn log(&self) {
-        syscalls!().sol_log(&self.to_string())
+        syscalls::with(|syscalls| syscalls.sol_log(&self.to_string()))
     }
 }
 
@@ -1,12 +1,5 @@
-pub mod default;
-
-use std::ops::{Deref, DerefMut};
-use std::sync::atomic::AtomicPtr;
-use std::sync::Mutex;
-use {
-    std::sync::{Arc, RwLock},
-    crate::syscalls::default::DefaultSyscalls,
-};
+use std::cell::Cell;
+use std::ptr;
 use solana_program::clock::Clock;
 use crate::account_info::AccountInfo;
 use crate::entrypoint::ProgramResult;
@@ -15,15 +8,37 @@ use crate::pubkey::Pubkey;
 use crate::rent::Rent;
 
 
-lazy_static::lazy_static! {
-    pub static ref SYSCALLS: AtomicPtr<Box<dyn Syscalls>> = AtomicPtr::new(DefaultSyscalls::new_ref());
+thread_local! {
+    // Syscalls of the program running on this thread, set by its entrypoint for as long as it runs
+    static ACTIVE: Cell<*mut ()> = const { Cell::new(ptr::null_mut()) };
 }
 
-#[macro_export]
-macro_rules! syscalls {
-    () => {
-        unsafe { $crate::syscalls::SYSCALLS.load(std::sync::atomic::Ordering::Relaxed).as_mut().unwrap() }
+// Puts the slot back on return and on unwind
+struct Restore(*mut ());
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        ACTIVE.with(|active| active.set(self.0));
+    }
+}
+
+// Makes the syscalls reachable from the program code run by the closure
+pub fn enter<R>(syscalls: &mut dyn Syscalls, run: impl FnOnce() -> R) -> R {
+    let mut slot: &mut dyn Syscalls = syscalls;
+    let previous = ACTIVE.with(|active| active.replace(&mut slot as *mut &mut dyn Syscalls as *mut ()));
+    let _restore = Restore(previous);
+    run()
+}
+
+// The slot is emptied during the call, a nested call has to come through another entrypoint
+pub fn with<R>(call: impl FnOnce(&mut dyn Syscalls) -> R) -> R {
+    let slot = ACTIVE.with(|active| active.replace(ptr::null_mut())) as *mut &mut dyn Syscalls;
+    if slot.is_null() {
+        panic!("Syscalls was not provided");
     }
+    let _restore = Restore(slot as *mut ());
+    // Set by an enter still running, no other reference to it is alive while the slot is empty
+    call(unsafe { &mut **slot })
 }
 
 #[macro_export]
@@ -37,7 +52,7 @@ macro_rules! not_supported {
 }
 
 
-pub trait Syscalls: Sync + Send {
+pub trait Syscalls {
     fn get_processed_sibling_instruction(&mut self, index: usize) -> Option<Instruction>;
     fn get_stack_height(&mut self) -> usize;
     fn sol_log(&mut self, message: &str);
@@ -50,21 +65,9 @@ pub trait Syscalls: Sync + Send {
     fn get_clock(&mut self) -> Clock;
     fn get_rent(&mut self) -> Rent;
 
-    fn set_owner(&mut self, to: &Pubkey, owner: &Pubkey);
-    fn set_data(&mut self, info: &AccountInfo, data: Vec<u8>);
-    fn set_lamports(&mut self, to: &Pubkey, amount: u64);
+    // Resizes the data of an account of the running program, up to max_increase past its length at entry
+    fn realloc(&mut self, data: &mut &mut [u8], new_len: usize, max_increase: usize) -> ProgramResult;
 
     fn get_program_id(&self) -> Pubkey;
-    fn finalize_system_invoke<'a>(&mut self, accounts: &'a [AccountInfo<'a>]) -> ProgramResult;
     fn rent_exempt_for_size(&mut self, size: usize) -> u64;
-    fn get_data_ptr(&mut self, key: &Pubkey) -> AtomicPtr<Vec<u8>>;
 }
-
-
-
-
-
-
-
-
-
@@ -27,7 +27,7 @@ impl Sysvar for Clock {
             return Err(ProgramError::UnsupportedSysvar);
         }
 
-        Ok(syscalls!().get_clock())
+        Ok(syscalls::with(|syscalls| syscalls.get_clock()))
     }
 
     fn to_account_info(&self, account_info: &mut AccountInfo) -> Option<()> {
@@ -36,6 +36,6 @@ impl Sysvar for Clock {
 
 
     fn get() -> Result<Self, ProgramError> {
-        Ok(syscalls!().get_clock())
+        Ok(syscalls::with(|syscalls| syscalls.get_clock()))
     }
 }
@@ -31,7 +31,7 @@ impl Sysvar for Rent {
             return Err(ProgramError::UnsupportedSysvar);
         }
 
-        Ok(syscalls!().get_rent())
+        Ok(syscalls::with(|syscalls| syscalls.get_rent()))
     }
 
     fn to_account_info(&self, account_info: &mut AccountInfo) -> Option<()> {
@@ -39,6 +39,6 @@ impl Sysvar for Rent {
     }
 
     fn get() -> Result<Self, ProgramError> {
-        Ok(syscalls!().get_rent())
+        Ok(syscalls::with(|syscalls| syscalls.get_rent()))
     }
 }
@@ -88,7 +88,7 @@ fn process_create_associated_token_account(
     }
 
     if create_mode == CreateMode::Idempotent
-        && associated_token_account_info.owner == spl_token_program_id
+        && associated_token_account_info.owner.get() == *spl_token_program_id
     {
         let ata_data = associated_token_account_info.data.borrow();
 
@@ -104,7 +104,7 @@ fn process_create_associated_token_account(
             return Ok(());
         }
     }
-    if *associated_token_account_info.owner != system_program::id() {
+    if associated_token_account_info.owner.get() != system_program::id() {
         return Err(ProgramError::IllegalOwner);
     }
 
@@ -215,7 +215,7 @@ pub fn process_recover_nested(program_id: &Pubkey, accounts: &[AccountInfo]) ->
         return Err(ProgramError::MissingRequiredSignature);
     }
 
-    if owner_token_mint_info.owner != spl_token_program_id {
+    if owner_token_mint_info.owner.get() != *spl_token_program_id {
         msg!("Owner mint not owned by provided token program");
         return Err(ProgramError::IllegalOwner);
     }
@@ -224,7 +224,7 @@ pub fn process_recover_nested(program_id: &Pubkey, accounts: &[AccountInfo]) ->
     // without a double-borrow
     let (amount, decimals) = {
         // Check owner associated token account data
-        if owner_associated_token_account_info.owner != spl_token_program_id {
+        if owner_associated_token_account_info.owner.get() != *spl_token_program_id {
             msg!("Owner associated token account not owned by provided token program, recreate the owner associated token account first");
             return Err(ProgramError::IllegalOwner);
         }
@@ -236,7 +236,7 @@ pub fn process_recover_nested(program_id: &Pubkey, accounts: &[AccountInfo]) ->
         }
 
         // Check nested associated token account data
-        if nested_associated_token_account_info.owner != spl_token_program_id {
+        if nested_associated_token_account_info.owner.get() != *spl_token_program_id {
             msg!("Nested associated token account not owned by provided token program");
             return Err(ProgramError::IllegalOwner);
         }
@@ -249,7 +249,7 @@ pub fn process_recover_nested(program_id: &Pubkey, accounts: &[AccountInfo]) ->
         let amount = nested_account.amount;
 
         // Check nested token mint data
-        if nested_token_mint_info.owner != spl_token_program_id {
+        if nested_token_mint_info.owner.get() != *spl_token_program_id {
             msg!("Nested mint account not owned by provided token program");
             return Err(ProgramError::IllegalOwner);
         }
@@ -956,7 +956,7 @@ impl Processor {
 
     /// Checks that the account is owned by the expected program
     pub fn check_account_owner(program_id: &Pubkey, account_info: &AccountInfo) -> ProgramResult {
-        if !Self::cmp_pubkeys(program_id, account_info.owner) {
+        if !Self::cmp_pubkeys(program_id, &account_info.owner.get()) {
             Err(ProgramError::IncorrectProgramId)
         } else {
             Ok(())
@@ -979,7 +979,7 @@ impl Processor {
         if !Self::cmp_pubkeys(expected_owner, owner_account_info.key) {
             return Err(TokenError::OwnerMismatch.into());
         }
-        if Self::cmp_pubkeys(program_id, owner_account_info.owner)
+        if Self::cmp_pubkeys(program_id, &owner_account_info.owner.get())
             && owner_account_info.data_len() == Multisig::get_packed_len()
         {
             let multisig = Multisig::unpack(&owner_account_info.data.borrow())?;
@@ -2,12 +2,25 @@ mod system_program;
 mod program_utils;
 mod external;
 mod inline;
+mod locator;
+mod mock;
+mod registry;
+mod upgradeable_loader;
+mod compute_budget;
+mod address_lookup_table;
 
 pub use external::ExternalPackage;
 pub use system_program::SystemProgramPackage;
+pub use compute_budget::ComputeBudgetPackage;
+pub use address_lookup_table::AddressLookupTablePackage;
 pub use inline::InlinePackage;
+pub use locator::{PackageLocator, PackageError};
+pub use mock::{AccountMutation, FnPackage, MockPackage, MockResponse};
+pub use registry::{package_handle, PackageRegistry};
+pub use upgradeable_loader::{UpgradeableLoaderPackage, UpgradeablePackage};
 
 
+use std::sync::Arc;
 use solana_program::{
     account_info::AccountInfo,
     entrypoint::ProgramResult,
@@ -16,6 +29,13 @@ use solana_program::{
 };
 
 
-pub trait Package {
-    fn execute<'e>(&self, accounts: &'e [AccountInfo<'e>], instruction_data: &'e [u8], program_id: &'e Pubkey, syscalls: Box<dyn Syscalls>) -> ProgramResult;
+pub trait Package: Send + Sync {
+    fn execute<'e>(&self, accounts: &'e [AccountInfo<'e>], instruction_data: &'e [u8], program_id: &'e Pubkey, syscalls: &mut dyn Syscalls) -> ProgramResult;
+}
+
+// Environments forked from one another run the same package
+impl Package for Arc<dyn Package> {
+    fn execute<'e>(&self, accounts: &'e [AccountInfo<'e>], instruction_data: &'e [u8], program_id: &'e Pubkey, syscalls: &mut dyn Syscalls) -> ProgramResult {
+        self.as_ref().execute(accounts, instruction_data, program_id, syscalls)
+    }
 }
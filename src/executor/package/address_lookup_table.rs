@@ -0,0 +1,243 @@
+use solana_program::{
+    account_info::AccountInfo,
+    address_lookup_table::{
+        instruction::ProgramInstruction,
+        state::{AddressLookupTable, LookupTableMeta, LookupTableStatus, LOOKUP_TABLE_MAX_ADDRESSES, LOOKUP_TABLE_META_SIZE, MAX_RECENT_SLOTS},
+    },
+    clock::Slot,
+    entrypoint::ProgramResult,
+    instruction::InstructionError,
+    pubkey::Pubkey,
+    syscalls::Syscalls,
+};
+use crate::executor::Package;
+use crate::executor::package::program_utils::{convert_instruction_error, limited_deserialize, set_data, set_lamports};
+
+
+// Tables activate and deactivate with the slot of the cluster settings, recent slots are the last 512
+pub struct AddressLookupTablePackage {}
+
+impl Package for AddressLookupTablePackage {
+    fn execute<'e>(&self, accounts: &'e [AccountInfo<'e>], instruction_data: &'e [u8], program_id: &'e Pubkey, syscalls: &mut dyn Syscalls) -> ProgramResult {
+        Self::processor(accounts, instruction_data, program_id, syscalls)
+            .map_err(convert_instruction_error)
+    }
+}
+
+impl AddressLookupTablePackage {
+    fn processor<'e>(accounts: &'e [AccountInfo<'e>], instruction_data: &'e [u8], program_id: &Pubkey, syscalls: &mut dyn Syscalls) -> Result<(), InstructionError> {
+        let instruction: ProgramInstruction = limited_deserialize(instruction_data)?;
+
+        match instruction {
+            ProgramInstruction::CreateLookupTable { recent_slot, bump_seed } => {
+                Self::check_number_of_instruction_accounts(accounts, 3)?;
+                Self::create_lookup_table(&accounts[0], &accounts[1], &accounts[2], recent_slot, bump_seed, program_id, syscalls)
+            }
+            ProgramInstruction::FreezeLookupTable => {
+                Self::check_number_of_instruction_accounts(accounts, 2)?;
+                Self::freeze_lookup_table(&accounts[0], &accounts[1], program_id, syscalls)
+            }
+            ProgramInstruction::ExtendLookupTable { new_addresses } => {
+                Self::check_number_of_instruction_accounts(accounts, 2)?;
+                Self::extend_lookup_table(&accounts[0], &accounts[1], accounts.get(2), new_addresses, program_id, syscalls)
+            }
+            ProgramInstruction::DeactivateLookupTable => {
+                Self::check_number_of_instruction_accounts(accounts, 2)?;
+                Self::deactivate_lookup_table(&accounts[0], &accounts[1], program_id, syscalls)
+            }
+            ProgramInstruction::CloseLookupTable => {
+                Self::check_number_of_instruction_accounts(accounts, 3)?;
+                Self::close_lookup_table(&accounts[0], &accounts[1], &accounts[2], program_id, syscalls)
+            }
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn create_lookup_table<'a>(lookup_table: &'a AccountInfo<'a>, authority: &'a AccountInfo<'a>, payer: &'a AccountInfo<'a>, recent_slot: Slot, bump_seed: u8, program_id: &Pubkey, syscalls: &mut dyn Syscalls) -> Result<(), InstructionError> {
+        if !lookup_table.data_is_empty() {
+            syscalls.sol_log("Table account must not be allocated");
+            return Err(InstructionError::AccountAlreadyInitialized);
+        }
+        if !payer.is_signer {
+            syscalls.sol_log("Payer account must be a signer");
+            return Err(InstructionError::MissingRequiredSignature);
+        }
+
+        let current_slot = syscalls.get_clock().slot;
+        if recent_slot > current_slot || current_slot - recent_slot >= MAX_RECENT_SLOTS {
+            syscalls.sol_log(&format!("{} is not a recent slot", recent_slot));
+            return Err(InstructionError::InvalidInstructionData);
+        }
+
+        let derived_address = Pubkey::create_program_address(
+            &[authority.key.as_ref(), &recent_slot.to_le_bytes(), &[bump_seed]],
+            program_id,
+        ).map_err(|_| InstructionError::InvalidArgument)?;
+        if !derived_address.eq(lookup_table.key) {
+            syscalls.sol_log(&format!("Table address must match derived address: {}", derived_address));
+            return Err(InstructionError::InvalidArgument);
+        }
+
+        Self::fund(lookup_table, Some(payer), LOOKUP_TABLE_META_SIZE, syscalls)?;
+        let mut data = vec![0; LOOKUP_TABLE_META_SIZE];
+        AddressLookupTable::overwrite_meta_data(&mut data, LookupTableMeta::new(*authority.key))?;
+        set_data(lookup_table, data, syscalls)?;
+        lookup_table.assign(program_id);
+        Ok(())
+    }
+
+    fn freeze_lookup_table<'a>(lookup_table: &'a AccountInfo<'a>, authority: &'a AccountInfo<'a>, program_id: &Pubkey, syscalls: &mut dyn Syscalls) -> Result<(), InstructionError> {
+        let (mut meta, addresses) = Self::load_table(lookup_table, program_id)?;
+        Self::check_authority(&meta, authority, syscalls)?;
+        if meta.deactivation_slot != Slot::MAX {
+            syscalls.sol_log("Deactivated tables cannot be frozen");
+            return Err(InstructionError::InvalidArgument);
+        }
+        if addresses.is_empty() {
+            syscalls.sol_log("Empty lookup tables cannot be frozen");
+            return Err(InstructionError::InvalidInstructionData);
+        }
+
+        meta.authority = None;
+        Self::store_table(lookup_table, meta, &addresses, syscalls)
+    }
+
+    fn extend_lookup_table<'a>(lookup_table: &'a AccountInfo<'a>, authority: &'a AccountInfo<'a>, payer: Option<&'a AccountInfo<'a>>, new_addresses: Vec<Pubkey>, program_id: &Pubkey, syscalls: &mut dyn Syscalls) -> Result<(), InstructionError> {
+        let (mut meta, mut addresses) = Self::load_table(lookup_table, program_id)?;
+        Self::check_authority(&meta, authority, syscalls)?;
+        if meta.deactivation_slot != Slot::MAX {
+            syscalls.sol_log("Deactivated tables cannot be extended");
+            return Err(InstructionError::InvalidArgument);
+        }
+        if addresses.len() >= LOOKUP_TABLE_MAX_ADDRESSES {
+            syscalls.sol_log("Lookup table is full and cannot contain more addresses");
+            return Err(InstructionError::InvalidArgument);
+        }
+        if new_addresses.is_empty() {
+            syscalls.sol_log("Must extend with at least one address");
+            return Err(InstructionError::InvalidInstructionData);
+        }
+        let new_len = addresses.len().saturating_add(new_addresses.len());
+        if new_len > LOOKUP_TABLE_MAX_ADDRESSES {
+            syscalls.sol_log(&format!(
+                "Extended lookup table length {} would exceed max capacity of {}",
+                new_len, LOOKUP_TABLE_MAX_ADDRESSES,
+            ));
+            return Err(InstructionError::InvalidInstructionData);
+        }
+
+        // Addresses added in the current slot only become usable in the next one
+        let current_slot = syscalls.get_clock().slot;
+        if current_slot != meta.last_extended_slot {
+            meta.last_extended_slot = current_slot;
+            meta.last_extended_slot_start_index = addresses.len() as u8;
+        }
+        addresses.extend(new_addresses);
+
+        Self::fund(lookup_table, payer, LOOKUP_TABLE_META_SIZE + new_len * 32, syscalls)?;
+        Self::store_table(lookup_table, meta, &addresses, syscalls)
+    }
+
+    fn deactivate_lookup_table<'a>(lookup_table: &'a AccountInfo<'a>, authority: &'a AccountInfo<'a>, program_id: &Pubkey, syscalls: &mut dyn Syscalls) -> Result<(), InstructionError> {
+        let (mut meta, addresses) = Self::load_table(lookup_table, program_id)?;
+        Self::check_authority(&meta, authority, syscalls)?;
+        if meta.deactivation_slot != Slot::MAX {
+            syscalls.sol_log("Lookup table is already deactivated");
+            return Err(InstructionError::InvalidArgument);
+        }
+
+        meta.deactivation_slot = syscalls.get_clock().slot;
+        Self::store_table(lookup_table, meta, &addresses, syscalls)
+    }
+
+    fn close_lookup_table<'a>(lookup_table: &'a AccountInfo<'a>, authority: &'a AccountInfo<'a>, recipient: &'a AccountInfo<'a>, program_id: &Pubkey, syscalls: &mut dyn Syscalls) -> Result<(), InstructionError> {
+        let (meta, _) = Self::load_table(lookup_table, program_id)?;
+        Self::check_authority(&meta, authority, syscalls)?;
+        if lookup_table.key.eq(recipient.key) {
+            syscalls.sol_log("Lookup table cannot be the recipient of reclaimed lamports");
+            return Err(InstructionError::InvalidArgument);
+        }
+
+        match meta.status(syscalls.get_clock().slot) {
+            LookupTableStatus::Activated => {
+                syscalls.sol_log("Lookup table is not deactivated");
+                Err(InstructionError::InvalidArgument)
+            }
+            LookupTableStatus::Deactivating { remaining_blocks } => {
+                syscalls.sol_log(&format!("Table cannot be closed until it's fully deactivated in {} blocks", remaining_blocks));
+                Err(InstructionError::InvalidArgument)
+            }
+            LookupTableStatus::Deactivated => {
+                set_lamports(recipient, recipient.lamports() + lookup_table.lamports())?;
+                set_lamports(lookup_table, 0)?;
+                set_data(lookup_table, Vec::new(), syscalls)?;
+                Ok(())
+            }
+        }
+    }
+
+    fn load_table<'a>(lookup_table: &'a AccountInfo<'a>, program_id: &Pubkey) -> Result<(LookupTableMeta, Vec<Pubkey>), InstructionError> {
+        if !lookup_table.owner.get().eq(program_id) {
+            return Err(InstructionError::InvalidAccountOwner);
+        }
+        let data = lookup_table.data.borrow();
+        let table = AddressLookupTable::deserialize(&data)?;
+        Ok((table.meta, table.addresses.into_owned()))
+    }
+
+    fn store_table<'a>(lookup_table: &'a AccountInfo<'a>, meta: LookupTableMeta, addresses: &[Pubkey], syscalls: &mut dyn Syscalls) -> Result<(), InstructionError> {
+        let mut data = vec![0; LOOKUP_TABLE_META_SIZE];
+        AddressLookupTable::overwrite_meta_data(&mut data, meta)?;
+        addresses.iter().for_each(|address| data.extend_from_slice(address.as_ref()));
+        set_data(lookup_table, data, syscalls)?;
+        Ok(())
+    }
+
+    fn check_authority<'a>(meta: &LookupTableMeta, authority: &'a AccountInfo<'a>, syscalls: &mut dyn Syscalls) -> Result<(), InstructionError> {
+        match meta.authority {
+            None => {
+                syscalls.sol_log("Lookup table is frozen");
+                Err(InstructionError::Immutable)
+            }
+            Some(expected) if !expected.eq(authority.key) => {
+                syscalls.sol_log("Incorrect lookup table authority provided");
+                Err(InstructionError::IncorrectAuthority)
+            }
+            Some(_) if !authority.is_signer => {
+                syscalls.sol_log("Authority account must be a signer");
+                Err(InstructionError::MissingRequiredSignature)
+            }
+            Some(_) => Ok(()),
+        }
+    }
+
+    // Tops the table up to rent-exemption for its new size, the payer covers the difference
+    fn fund<'a>(lookup_table: &'a AccountInfo<'a>, payer: Option<&'a AccountInfo<'a>>, size: usize, syscalls: &mut dyn Syscalls) -> Result<(), InstructionError> {
+        let required_lamports = syscalls.rent_exempt_for_size(size)
+            .max(1)
+            .saturating_sub(lookup_table.lamports());
+        if required_lamports == 0 {
+            return Ok(());
+        }
+
+        let payer = payer.ok_or(InstructionError::NotEnoughAccountKeys)?;
+        if !payer.is_signer {
+            syscalls.sol_log("Payer account must be a signer");
+            return Err(InstructionError::MissingRequiredSignature);
+        }
+        if payer.lamports() < required_lamports {
+            syscalls.sol_log(&format!("Transfer: insufficient lamports {}, need {}", payer.lamports(), required_lamports));
+            return Err(InstructionError::InsufficientFunds);
+        }
+        set_lamports(payer, payer.lamports() - required_lamports)?;
+        set_lamports(lookup_table, lookup_table.lamports() + required_lamports)?;
+        Ok(())
+    }
+
+    fn check_number_of_instruction_accounts<'e>(accounts: &[AccountInfo<'e>], count: usize) -> Result<(), InstructionError> {
+        if accounts.len() < count {
+            return Err(InstructionError::NotEnoughAccountKeys);
+        }
+        Ok(())
+    }
+}
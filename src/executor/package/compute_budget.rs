@@ -0,0 +1,22 @@
+use solana_program::{
+    account_info::AccountInfo,
+    compute_budget::ComputeBudgetInstruction,
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    syscalls::Syscalls,
+};
+use crate::executor::Package;
+
+
+// Requests are applied by the transaction before execution, the program only checks the data
+pub struct ComputeBudgetPackage {}
+
+impl Package for ComputeBudgetPackage {
+    fn execute<'e>(&self, _accounts: &'e [AccountInfo<'e>], instruction_data: &'e [u8], _program_id: &'e Pubkey, _syscalls: &mut dyn Syscalls) -> ProgramResult {
+        match ComputeBudgetInstruction::unpack(instruction_data) {
+            Some(ComputeBudgetInstruction::Unused) | None => Err(ProgramError::InvalidInstructionData),
+            Some(_) => Ok(()),
+        }
+    }
+}
@@ -1,15 +1,13 @@
-use std::ffi::{OsStr, OsString};
-use crate::executor::Package;
+use std::ffi::OsStr;
+use std::path::Path;
+use crate::executor::{Package, PackageError, PackageLocator};
 use solana_program::{
     account_info::AccountInfo,
-    entrypoint::{ProgramResult, ProcessInstruction},
+    entrypoint::{ExternalProcessInstruction, ProgramResult},
     pubkey::Pubkey,
     syscalls::Syscalls,
 };
-use libloading::{Library, library_filename, Symbol};
-use std::path::Path;
-use resolve_path::PathResolveExt;
-use std::ops::Deref;
+use libloading::{Library, Symbol};
 
 
 pub struct ExternalPackage {
@@ -18,30 +16,46 @@ pub struct ExternalPackage {
 
 impl ExternalPackage {
     pub fn new(package_name: &str) -> Self {
-        let path_str = format!("target/debug/lib{}.so", package_name.replace("-", "_"));
-        let path_cow = path_str.resolve();
-        let path: &Path = path_cow.deref();
-        Self::new_from_path(
-            path.as_os_str()
-        )
+        Self::try_new(package_name)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    pub fn try_new(package_name: &str) -> Result<Self, PackageError> {
+        Self::from_locator(&PackageLocator::new(package_name))
+    }
+
+    pub fn from_locator(locator: &PackageLocator) -> Result<Self, PackageError> {
+        let path = locator.locate()?;
+        Self::try_new_from_path(path.as_os_str())
     }
 
     pub fn new_from_path(path: &OsStr) -> Self  {
+        Self::try_new_from_path(path)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    pub fn try_new_from_path(path: &OsStr) -> Result<Self, PackageError> {
         let lib = unsafe {
-            Library::new(path.clone())
-        }.expect(&format!("Missing Program {:?}", path));
+            Library::new(path)
+        }.map_err(|err| PackageError::LoadFailed {
+            path: Path::new(path).to_path_buf(),
+            message: err.to_string(),
+        })?;
 
-        let _: Symbol<ProcessInstruction> = unsafe {
+        let entrypoint: Result<Symbol<ExternalProcessInstruction>, _> = unsafe {
             lib.get(b"entrypoint")
-        }.expect(&format!("Not a Program {:?}", path));
+        };
+        if entrypoint.is_err() {
+            return Err(PackageError::MissingEntrypoint { path: Path::new(path).to_path_buf() });
+        }
 
-        Self { lib }
+        Ok(Self { lib })
     }
 }
 
 impl Package for ExternalPackage {
-    fn execute<'e>(&self, accounts: &'e [AccountInfo<'e>], instruction_data: &'e [u8], program_id: &'e Pubkey, syscalls: Box<dyn Syscalls>) -> ProgramResult {
-        let mut entrypoint: Symbol<ProcessInstruction> = unsafe { self.lib.get(b"entrypoint").unwrap() };
+    fn execute<'e>(&self, accounts: &'e [AccountInfo<'e>], instruction_data: &'e [u8], program_id: &'e Pubkey, syscalls: &mut dyn Syscalls) -> ProgramResult {
+        let entrypoint: Symbol<ExternalProcessInstruction> = unsafe { self.lib.get(b"entrypoint").unwrap() };
         entrypoint(program_id, accounts, instruction_data, syscalls)
     }
-}
\ No newline at end of file
+}
@@ -18,7 +18,7 @@ impl InlinePackage {
 }
 
 impl Package for InlinePackage {
-    fn execute<'e>(&self, accounts: &'e [AccountInfo<'e>], instruction_data: &'e [u8], program_id: &'e Pubkey, syscalls: Box<dyn Syscalls>) -> ProgramResult {
+    fn execute<'e>(&self, accounts: &'e [AccountInfo<'e>], instruction_data: &'e [u8], program_id: &'e Pubkey, syscalls: &mut dyn Syscalls) -> ProgramResult {
         (self.entrypoint)(program_id, accounts, instruction_data, syscalls)
     }
 }
@@ -0,0 +1,271 @@
+use std::env;
+use std::ffi::OsStr;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use libloading::library_filename;
+use serde_json::Value;
+
+
+#[derive(Debug)]
+pub enum PackageError {
+    NotFound { package: String, tried: Vec<PathBuf> },
+    BuildFailed { package: String, message: String },
+    LoadFailed { path: PathBuf, message: String },
+    MissingEntrypoint { path: PathBuf },
+}
+
+impl fmt::Display for PackageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PackageError::NotFound { package, tried } => {
+                write!(f, "Missing Program {}, tried:", package)?;
+                for path in tried.iter() {
+                    write!(f, "\n    {}", path.display())?;
+                }
+                Ok(())
+            }
+            PackageError::BuildFailed { package, message } => write!(f, "Failed to build {}: {}", package, message),
+            PackageError::LoadFailed { path, message } => write!(f, "Failed to load {}: {}", path.display(), message),
+            PackageError::MissingEntrypoint { path } => write!(f, "Not a Program {}", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for PackageError {}
+
+
+// Resolves the dynamic library of a contract crate the same way cargo lays it out:
+// target dir from CARGO_TARGET_DIR or cargo metadata, profile from the running binary
+pub struct PackageLocator {
+    package_name: String,
+    manifest_dir: Option<PathBuf>,
+    target_dir: Option<PathBuf>,
+    profile: Option<String>,
+    build_if_missing: bool,
+}
+
+impl PackageLocator {
+    pub fn new(package_name: &str) -> Self {
+        Self {
+            package_name: package_name.to_string(),
+            manifest_dir: None,
+            target_dir: None,
+            profile: None,
+            build_if_missing: false,
+        }
+    }
+
+    pub fn manifest_dir<P: AsRef<Path>>(mut self, dir: P) -> Self {
+        self.manifest_dir = Some(dir.as_ref().to_path_buf());
+        self
+    }
+
+    pub fn target_dir<P: AsRef<Path>>(mut self, dir: P) -> Self {
+        self.target_dir = Some(dir.as_ref().to_path_buf());
+        self
+    }
+
+    pub fn profile(mut self, profile: &str) -> Self {
+        self.profile = Some(profile.to_string());
+        self
+    }
+
+    pub fn build_if_missing(mut self, build: bool) -> Self {
+        self.build_if_missing = build;
+        self
+    }
+
+    pub fn locate(&self) -> Result<PathBuf, PackageError> {
+        let metadata = self.metadata();
+        match self.find(metadata.as_ref()) {
+            Ok(path) => Ok(path),
+            Err(err) if self.build_if_missing => {
+                self.build()?;
+                self.find(metadata.as_ref()).map_err(|_| err)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    fn find(&self, metadata: Option<&Value>) -> Result<PathBuf, PackageError> {
+        let file_name = library_filename(self.lib_name(metadata));
+        let mut tried = Vec::new();
+
+        for target_dir in self.target_dirs(metadata) {
+            for profile in self.profiles() {
+                let profile_dir = target_dir.join(profile_dir_name(&profile));
+                for dir in [profile_dir.clone(), profile_dir.join("deps")] {
+                    let path = dir.join(&file_name);
+                    if path.is_file() {
+                        return Ok(path);
+                    }
+                    if !tried.contains(&path) {
+                        tried.push(path);
+                    }
+                }
+            }
+        }
+
+        Err(PackageError::NotFound { package: self.package_name.clone(), tried })
+    }
+
+    fn build(&self) -> Result<(), PackageError> {
+        let cargo = env::var_os("CARGO").unwrap_or_else(|| "cargo".into());
+        let mut command = Command::new(cargo);
+        command.args(["build", "--lib", "-p", &self.package_name]);
+        if let Some(profile) = self.profiles().first() {
+            command.args(["--profile", cargo_profile_name(profile)]);
+        }
+        if let Some(target_dir) = &self.target_dir {
+            command.arg("--target-dir").arg(target_dir);
+        }
+        if let Some(manifest_dir) = self.working_dir() {
+            command.current_dir(manifest_dir);
+        }
+
+        let output = command.output().map_err(|err| PackageError::BuildFailed {
+            package: self.package_name.clone(),
+            message: err.to_string(),
+        })?;
+        if !output.status.success() {
+            return Err(PackageError::BuildFailed {
+                package: self.package_name.clone(),
+                message: String::from_utf8_lossy(&output.stderr).to_string(),
+            });
+        }
+
+        Ok(())
+    }
+
+    fn working_dir(&self) -> Option<PathBuf> {
+        self.manifest_dir.clone()
+            .or_else(|| env::var_os("CARGO_MANIFEST_DIR").map(PathBuf::from))
+    }
+
+    fn metadata(&self) -> Option<Value> {
+        let cargo = env::var_os("CARGO").unwrap_or_else(|| "cargo".into());
+        let mut command = Command::new(cargo);
+        command.args(["metadata", "--format-version", "1", "--no-deps", "--offline"]);
+        if let Some(manifest_dir) = self.working_dir() {
+            command.current_dir(manifest_dir);
+        }
+
+        let output = command.output().ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        serde_json::from_slice(&output.stdout).ok()
+    }
+
+    // Library target name of the package, "my-contract" builds into "libmy_contract"
+    fn lib_name(&self, metadata: Option<&Value>) -> String {
+        let normalized = self.package_name.replace('-', "_");
+        let packages = metadata
+            .and_then(|metadata| metadata["packages"].as_array())
+            .into_iter()
+            .flatten();
+
+        for package in packages {
+            let name = package["name"].as_str().unwrap_or_default();
+            if name.replace('-', "_") != normalized {
+                continue;
+            }
+            let targets = package["targets"].as_array().into_iter().flatten();
+            for target in targets {
+                let is_cdylib = target["crate_types"].as_array()
+                    .map(|types| types.iter().any(|t| t == "cdylib"))
+                    .unwrap_or(false);
+                if let (true, Some(target_name)) = (is_cdylib, target["name"].as_str()) {
+                    return target_name.replace('-', "_");
+                }
+            }
+        }
+
+        normalized
+    }
+
+    fn target_dirs(&self, metadata: Option<&Value>) -> Vec<PathBuf> {
+        let mut dirs: Vec<PathBuf> = Vec::new();
+        let mut push = |dir: PathBuf| {
+            if !dirs.contains(&dir) {
+                dirs.push(dir);
+            }
+        };
+
+        if let Some(dir) = &self.target_dir {
+            push(dir.clone());
+        }
+        if let Some(dir) = env::var_os("CARGO_TARGET_DIR") {
+            push(self.absolute(PathBuf::from(dir)));
+        }
+        if let Some(dir) = metadata.and_then(|metadata| metadata["target_directory"].as_str()) {
+            push(PathBuf::from(dir));
+        }
+        if let Some((dir, _)) = current_exe_layout() {
+            push(dir);
+        }
+        push(self.absolute(PathBuf::from("target")));
+
+        dirs
+    }
+
+    fn profiles(&self) -> Vec<String> {
+        let mut candidates: Vec<String> = Vec::new();
+        if let Some(profile) = &self.profile {
+            candidates.push(profile.clone());
+        }
+        if let Some((_, profile)) = current_exe_layout() {
+            candidates.push(profile);
+        }
+        candidates.push("debug".to_string());
+        candidates.push("release".to_string());
+
+        let mut profiles: Vec<String> = Vec::new();
+        for profile in candidates {
+            if !profiles.contains(&profile) {
+                profiles.push(profile);
+            }
+        }
+        profiles
+    }
+
+    fn absolute(&self, path: PathBuf) -> PathBuf {
+        if path.is_absolute() {
+            return path;
+        }
+        match self.working_dir() {
+            Some(dir) => dir.join(path),
+            None => path,
+        }
+    }
+}
+
+// Cargo stores the "dev" and "test" profiles under "debug", "bench" under "release"
+fn profile_dir_name(profile: &str) -> &str {
+    match profile {
+        "dev" | "test" => "debug",
+        "bench" => "release",
+        profile => profile,
+    }
+}
+
+// Inverse of profile_dir_name for "cargo build --profile"
+fn cargo_profile_name(profile: &str) -> &str {
+    match profile {
+        "debug" => "dev",
+        profile => profile,
+    }
+}
+
+// Test binaries live in <target>/<profile>/deps/<name>
+fn current_exe_layout() -> Option<(PathBuf, String)> {
+    let exe = env::current_exe().ok()?;
+    let mut dir = exe.parent()?;
+    if dir.file_name() == Some(OsStr::new("deps")) {
+        dir = dir.parent()?;
+    }
+    let profile = dir.file_name()?.to_str()?.to_string();
+    let target_dir = dir.parent()?.to_path_buf();
+    Some((target_dir, profile))
+}
@@ -0,0 +1,224 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    syscalls::{self, Syscalls},
+};
+use crate::executor::Package;
+
+
+pub type PackageFn = dyn Fn(&Pubkey, &[AccountInfo], &[u8]) -> ProgramResult + Send + Sync;
+
+// Package built from a closure with the signature of a contract processor,
+// syscalls are installed the same way the entrypoint! macro does
+pub struct FnPackage {
+    function: Box<PackageFn>,
+}
+
+impl FnPackage {
+    pub fn new<F>(function: F) -> Self
+        where F: Fn(&Pubkey, &[AccountInfo], &[u8]) -> ProgramResult + Send + Sync + 'static
+    {
+        Self { function: Box::new(function) }
+    }
+}
+
+impl Package for FnPackage {
+    fn execute<'e>(&self, accounts: &'e [AccountInfo<'e>], instruction_data: &'e [u8], program_id: &'e Pubkey, syscalls: &mut dyn Syscalls) -> ProgramResult {
+        syscalls::enter(syscalls, || (self.function)(program_id, accounts, instruction_data))
+    }
+}
+
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AccountMutation {
+    pub pubkey: Pubkey,
+    pub lamports: Option<u64>,
+    pub owner: Option<Pubkey>,
+    pub data: Option<Vec<u8>>,
+}
+
+// Scripted outcome of a single call to a MockPackage
+#[derive(Clone, Debug, PartialEq)]
+pub struct MockResponse {
+    pub result: ProgramResult,
+    pub return_data: Option<Vec<u8>>,
+    pub logs: Vec<String>,
+    pub mutations: Vec<AccountMutation>,
+}
+
+impl MockResponse {
+    pub fn ok() -> Self {
+        Self {
+            result: Ok(()),
+            return_data: None,
+            logs: Vec::new(),
+            mutations: Vec::new(),
+        }
+    }
+
+    pub fn err(err: ProgramError) -> Self {
+        Self {
+            result: Err(err),
+            ..Self::ok()
+        }
+    }
+
+    pub fn with_return_data(mut self, data: &[u8]) -> Self {
+        self.return_data = Some(Vec::from(data));
+        self
+    }
+
+    pub fn with_log(mut self, message: &str) -> Self {
+        self.logs.push(message.to_string());
+        self
+    }
+
+    pub fn with_lamports(mut self, pubkey: &Pubkey, lamports: u64) -> Self {
+        self.mutation(pubkey).lamports = Some(lamports);
+        self
+    }
+
+    pub fn with_owner(mut self, pubkey: &Pubkey, owner: &Pubkey) -> Self {
+        self.mutation(pubkey).owner = Some(*owner);
+        self
+    }
+
+    pub fn with_data(mut self, pubkey: &Pubkey, data: Vec<u8>) -> Self {
+        self.mutation(pubkey).data = Some(data);
+        self
+    }
+
+    fn mutation(&mut self, pubkey: &Pubkey) -> &mut AccountMutation {
+        let position = self.mutations.iter().position(|mutation| mutation.pubkey.eq(pubkey));
+        let index = match position {
+            Some(index) => index,
+            None => {
+                self.mutations.push(AccountMutation { pubkey: *pubkey, lamports: None, owner: None, data: None });
+                self.mutations.len() - 1
+            }
+        };
+        &mut self.mutations[index]
+    }
+}
+
+
+#[derive(Default)]
+struct MockState {
+    calls: Vec<Instruction>,
+    responses: VecDeque<MockResponse>,
+    default_response: Option<MockResponse>,
+}
+
+// Recording mock, clones share the same call log and response script
+#[derive(Clone, Default)]
+pub struct MockPackage {
+    state: Arc<Mutex<MockState>>,
+}
+
+impl MockPackage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Responses are consumed one per call, then the default response (success) is used
+    pub fn respond(&self, response: MockResponse) -> &Self {
+        self.state.lock().unwrap().responses.push_back(response);
+        self
+    }
+
+    pub fn respond_always(&self, response: MockResponse) -> &Self {
+        self.state.lock().unwrap().default_response = Some(response);
+        self
+    }
+
+    pub fn calls(&self) -> Vec<Instruction> {
+        self.state.lock().unwrap().calls.clone()
+    }
+
+    pub fn call(&self, index: usize) -> Instruction {
+        self.calls().get(index).cloned()
+            .unwrap_or_else(|| panic!("Mock was not called {} times", index + 1))
+    }
+
+    fn next_response(&self, instruction: Instruction) -> MockResponse {
+        let mut state = self.state.lock().unwrap();
+        state.calls.push(instruction);
+        match state.responses.pop_front() {
+            Some(response) => response,
+            None => state.default_response.clone().unwrap_or_else(MockResponse::ok),
+        }
+    }
+}
+
+impl Package for MockPackage {
+    fn execute<'e>(&self, accounts: &'e [AccountInfo<'e>], instruction_data: &'e [u8], program_id: &'e Pubkey, syscalls: &mut dyn Syscalls) -> ProgramResult {
+        let instruction = Instruction {
+            program_id: *program_id,
+            accounts: accounts.iter()
+                .map(|info| AccountMeta {
+                    pubkey: *info.key,
+                    is_signer: info.is_signer,
+                    is_writable: info.is_writable,
+                })
+                .collect(),
+            data: Vec::from(instruction_data),
+        };
+        let response = self.next_response(instruction);
+
+        for message in response.logs.iter() {
+            syscalls.sol_log(message);
+        }
+        for mutation in response.mutations.iter() {
+            let info = accounts.iter()
+                .find(|info| info.key.eq(&mutation.pubkey))
+                .unwrap_or_else(|| panic!("Undefined Account {}", mutation.pubkey));
+            if let Some(lamports) = mutation.lamports {
+                **info.lamports.borrow_mut() = lamports;
+            }
+            if let Some(owner) = &mutation.owner {
+                info.assign(owner);
+            }
+            if let Some(data) = &mutation.data {
+                let mut info_data = info.data.borrow_mut();
+                syscalls.realloc(&mut info_data, data.len(), usize::MAX)?;
+                info_data.copy_from_slice(data);
+            }
+        }
+        if let Some(data) = &response.return_data {
+            syscalls.set_return_data(data);
+        }
+        response.result
+    }
+}
+
+// +++++++++ Suit Methods +++++++++
+impl MockPackage {
+    pub fn expect_calls(&self, count: usize) {
+        let calls = self.calls();
+        if calls.len() != count {
+            println!("Mock called {} times, expected {}: {:?}", calls.len(), count, calls);
+            assert_eq!(calls.len(), count);
+        }
+    }
+
+    pub fn expect_call(&self, index: usize, instruction: &Instruction) {
+        let call = self.call(index);
+        if !call.eq(instruction) {
+            println!("Mock call {} not matches {:?} != {:?}", index, call, instruction);
+            assert_eq!(&call, instruction);
+        }
+    }
+
+    pub fn expect_called_with(&self, instruction: &Instruction) {
+        let calls = self.calls();
+        if !calls.contains(instruction) {
+            println!("Mock was not called with {:?}", instruction);
+            assert!(calls.contains(instruction));
+        }
+    }
+}
@@ -1,9 +1,10 @@
+use solana_program::account_info::AccountInfo;
 use solana_program::instruction::InstructionError;
 use solana_program::program_error::ProgramError;
+use solana_program::syscalls::Syscalls;
+use crate::runtime::PACKET_DATA_SIZE;
 
 
-pub const PACKET_DATA_SIZE: usize = 1280 - 40 - 8;
-
 pub fn limited_deserialize<T>(instruction_data: &[u8]) -> Result<T, InstructionError>
     where
         T: serde::de::DeserializeOwned,
@@ -20,4 +21,17 @@ pub fn convert_instruction_error(err: InstructionError) -> ProgramError {
         Ok(err) => err,
         Err(_) => ProgramError::Custom(0x0)
     }
-}
\ No newline at end of file
+}
+
+// Native programs write through the infos like any program, data isn't held to the realloc limit
+pub fn set_data(info: &AccountInfo, data: Vec<u8>, syscalls: &mut dyn Syscalls) -> Result<(), InstructionError> {
+    let mut info_data = info.try_borrow_mut_data().map_err(|_| InstructionError::AccountBorrowFailed)?;
+    syscalls.realloc(&mut info_data, data.len(), usize::MAX).map_err(|_| InstructionError::InvalidRealloc)?;
+    info_data.copy_from_slice(&data);
+    Ok(())
+}
+
+pub fn set_lamports(info: &AccountInfo, lamports: u64) -> Result<(), InstructionError> {
+    **info.try_borrow_mut_lamports().map_err(|_| InstructionError::AccountBorrowFailed)? = lamports;
+    Ok(())
+}
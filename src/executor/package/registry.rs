@@ -0,0 +1,66 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use crate::executor::Package;
+
+
+// Loader accounts (buffers, program data) can't hold native code,
+// so they store a handle to a package registered with their environment instead of an ELF
+pub const PACKAGE_MAGIC: &[u8; 8] = b"FULGPKG\0";
+pub const PACKAGE_HANDLE_LEN: usize = PACKAGE_MAGIC.len() + 8;
+
+pub fn package_handle(bytes: &[u8]) -> Option<u64> {
+    if bytes.len() < PACKAGE_HANDLE_LEN || !bytes.starts_with(PACKAGE_MAGIC) {
+        return None;
+    }
+    Some(u64::from_le_bytes(bytes[PACKAGE_MAGIC.len()..PACKAGE_HANDLE_LEN].try_into().unwrap()))
+}
+
+fn handle_bytes(handle: u64) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(PACKAGE_HANDLE_LEN);
+    bytes.extend_from_slice(PACKAGE_MAGIC);
+    bytes.extend_from_slice(&handle.to_le_bytes());
+    bytes
+}
+
+// Packages of the loader accounts of one environment, forks get their own copy
+#[derive(Clone, Default)]
+pub struct PackageRegistry {
+    packages: BTreeMap<u64, Arc<dyn Package>>,
+    next_handle: u64,
+}
+
+impl PackageRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Bytes to store in place of the program
+    pub fn register(&mut self, package: Arc<dyn Package>) -> Vec<u8> {
+        let handle = self.next_handle;
+        self.bind(handle, package);
+        handle_bytes(handle)
+    }
+
+    // Restored accounts keep their handles, the packages are supplied again
+    pub fn bind(&mut self, handle: u64, package: Arc<dyn Package>) {
+        self.next_handle = self.next_handle.max(handle + 1);
+        self.packages.insert(handle, package);
+    }
+
+    pub fn resolve(&self, bytes: &[u8]) -> Option<Arc<dyn Package>> {
+        self.packages.get(&package_handle(bytes)?).cloned()
+    }
+
+    // Drops the packages no loader account refers to anymore
+    pub fn retain(&mut self, handles: &[u64]) {
+        self.packages.retain(|handle, _| handles.contains(handle));
+    }
+
+    pub fn len(&self) -> usize {
+        self.packages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.packages.is_empty()
+    }
+}
@@ -1,4 +1,3 @@
-use std::mem::forget;
 use borsh::BorshSerialize;
 use crate::{executor::package::program_utils::{
     limited_deserialize,
@@ -14,22 +13,20 @@ use solana_program::{
     program_error::ProgramError
 };
 use crate::executor::Package;
-use crate::executor::package::program_utils::convert_instruction_error;
+use crate::executor::package::program_utils::{convert_instruction_error, set_data, set_lamports};
 
 
 pub struct SystemProgramPackage {}
 
 impl Package for SystemProgramPackage {
-    fn execute<'e>(&self, accounts: &'e [AccountInfo<'e>], instruction_data: &'e [u8], _program_id: &'e Pubkey, mut syscalls: Box<dyn Syscalls>) -> ProgramResult {
-        let res = Self::processor(accounts, instruction_data, &mut syscalls)
-        .map_err(|err| convert_instruction_error(err));
-        forget(syscalls); // Owned by test scope
-        res
+    fn execute<'e>(&self, accounts: &'e [AccountInfo<'e>], instruction_data: &'e [u8], _program_id: &'e Pubkey, syscalls: &mut dyn Syscalls) -> ProgramResult {
+        Self::processor(accounts, instruction_data, syscalls)
+            .map_err(|err| convert_instruction_error(err))
     }
 }
 
 impl SystemProgramPackage {
-    fn processor<'e>(accounts: &'e [AccountInfo<'e>], instruction_data: &'e [u8], mut syscalls: &mut Box<dyn Syscalls>) -> Result<(), InstructionError> {
+    fn processor<'e>(accounts: &'e [AccountInfo<'e>], instruction_data: &'e [u8], syscalls: &mut dyn Syscalls) -> Result<(), InstructionError> {
         let instruction: SystemInstruction = limited_deserialize(instruction_data)?;
 
         match instruction {
@@ -61,13 +58,13 @@ impl SystemProgramPackage {
         }
     }
 
-    fn allocate<'a>(info: &'a AccountInfo<'a>, space: u64, syscalls: &mut Box<dyn Syscalls>) -> Result<(), InstructionError> {
+    fn allocate<'a>(info: &'a AccountInfo<'a>, space: u64, syscalls: &mut dyn Syscalls) -> Result<(), InstructionError> {
         if !info.is_signer {
             syscalls.sol_log(&format!("Allocate: 'to' account {:?} must sign", info.key));
             return Err(InstructionError::MissingRequiredSignature);
         }
 
-        if !info.data_is_empty() || !system_program::check_id(info.owner) {
+        if !info.data_is_empty() || !system_program::check_id(&info.owner.get()) {
             syscalls.sol_log(&format!("Allocate: account {:?} already in use", info.key));
             return Err(SystemError::AccountAlreadyInUse.into());
         }
@@ -81,13 +78,12 @@ impl SystemProgramPackage {
             return Err(SystemError::InvalidAccountDataLength.into());
         }
 
-        // info.realloc(space as usize, true).unwrap();
-        syscalls.set_data(info, vec![0; space as usize]);
+        set_data(info, vec![0; space as usize], syscalls)?;
         Ok(())
     }
 
-    fn assign<'a>(info: &'a AccountInfo<'a>, owner: &Pubkey, syscalls: &mut Box<dyn Syscalls>) -> Result<(), InstructionError> {
-        if info.owner.eq(owner) {
+    fn assign<'a>(info: &'a AccountInfo<'a>, owner: &Pubkey, syscalls: &mut dyn Syscalls) -> Result<(), InstructionError> {
+        if info.owner.get().eq(owner) {
             return Ok(());
         }
 
@@ -96,11 +92,11 @@ impl SystemProgramPackage {
             return Err(InstructionError::MissingRequiredSignature);
         }
 
-        syscalls.set_owner(&info.key, owner);
+        info.assign(owner);
         Ok(())
     }
 
-    fn create_account<'a>(from: &'a AccountInfo<'a>, to: &'a AccountInfo<'a>, lamports: u64, space: u64, owner: &Pubkey, syscalls: &mut Box<dyn Syscalls>) -> Result<(), InstructionError> {
+    fn create_account<'a>(from: &'a AccountInfo<'a>, to: &'a AccountInfo<'a>, lamports: u64, space: u64, owner: &Pubkey, syscalls: &mut dyn Syscalls) -> Result<(), InstructionError> {
         if to.lamports() > 0 {
             syscalls.sol_log(&format!(
                 "Create Account: account {:?} already in use",
@@ -114,7 +110,7 @@ impl SystemProgramPackage {
         Self::transfer(from, to, lamports, syscalls)
     }
 
-    fn transfer<'a>(from: &'a AccountInfo<'a>, to: &'a AccountInfo<'a>, lamports: u64, syscalls: &mut Box<dyn Syscalls>) -> Result<(), InstructionError> {
+    fn transfer<'a>(from: &'a AccountInfo<'a>, to: &'a AccountInfo<'a>, lamports: u64, syscalls: &mut dyn Syscalls) -> Result<(), InstructionError> {
         if !from.is_signer {
             syscalls.sol_log(&format!("Transfer: `from` account {:?} must sign", from.key));
             return Err(InstructionError::MissingRequiredSignature);
@@ -138,8 +134,8 @@ impl SystemProgramPackage {
             return Err(SystemError::ResultWithNegativeLamports.into());
         }
 
-        syscalls.set_lamports(&from.key, from.lamports() - lamports);
-        syscalls.set_lamports(&to.key, to.lamports() + lamports);
+        set_lamports(from, from.lamports() - lamports)?;
+        set_lamports(to, to.lamports() + lamports)?;
 
         Ok(())
     }
@@ -0,0 +1,376 @@
+use std::sync::Arc;
+use solana_program::{
+    account_info::AccountInfo,
+    bpf_loader_upgradeable::{deserialize_state, UpgradeableLoaderState},
+    entrypoint::ProgramResult,
+    instruction::InstructionError,
+    loader_upgradeable_instruction::UpgradeableLoaderInstruction,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    syscalls::Syscalls,
+    sysvar::{clock::Clock, rent::Rent, SysvarId},
+};
+use crate::executor::{Package, PackageRegistry};
+use crate::executor::package::program_utils::{convert_instruction_error, limited_deserialize, set_data, set_lamports};
+
+
+// Package deployed in a program data account, resolved when the program is loaded,
+// so an upgrade takes effect from the next transaction
+pub struct UpgradeablePackage {
+    deployed: Result<Arc<dyn Package>, &'static str>,
+}
+
+impl UpgradeablePackage {
+    pub fn new(programdata: &[u8], packages: &PackageRegistry) -> Self {
+        let deployed = match deserialize_state(programdata) {
+            Ok(UpgradeableLoaderState::ProgramData { .. }) => {
+                UpgradeableLoaderState::program_bytes(programdata)
+                    .and_then(|bytes| packages.resolve(bytes))
+                    .ok_or("Program is not deployed")
+            }
+            _ => Err("Program has been closed"),
+        };
+        Self { deployed }
+    }
+}
+
+impl Package for UpgradeablePackage {
+    fn execute<'e>(&self, accounts: &'e [AccountInfo<'e>], instruction_data: &'e [u8], program_id: &'e Pubkey, syscalls: &mut dyn Syscalls) -> ProgramResult {
+        match &self.deployed {
+            Ok(package) => package.execute(accounts, instruction_data, program_id, syscalls),
+            Err(message) => {
+                syscalls.sol_log(message);
+                Err(ProgramError::InvalidAccountData)
+            }
+        }
+    }
+}
+
+
+pub struct UpgradeableLoaderPackage {}
+
+impl Package for UpgradeableLoaderPackage {
+    fn execute<'e>(&self, accounts: &'e [AccountInfo<'e>], instruction_data: &'e [u8], program_id: &'e Pubkey, syscalls: &mut dyn Syscalls) -> ProgramResult {
+        Self::processor(accounts, instruction_data, program_id, syscalls)
+            .map_err(convert_instruction_error)
+    }
+}
+
+impl UpgradeableLoaderPackage {
+    fn processor<'e>(accounts: &'e [AccountInfo<'e>], instruction_data: &'e [u8], program_id: &Pubkey, syscalls: &mut dyn Syscalls) -> Result<(), InstructionError> {
+        let instruction: UpgradeableLoaderInstruction = limited_deserialize(instruction_data)?;
+
+        match instruction {
+            UpgradeableLoaderInstruction::InitializeBuffer => {
+                Self::check_number_of_instruction_accounts(accounts, 2)?;
+                Self::initialize_buffer(&accounts[0], &accounts[1], syscalls)
+            }
+            UpgradeableLoaderInstruction::Write { offset, bytes } => {
+                Self::check_number_of_instruction_accounts(accounts, 2)?;
+                Self::write(&accounts[0], &accounts[1], offset as usize, &bytes, syscalls)
+            }
+            UpgradeableLoaderInstruction::Upgrade => {
+                Self::check_number_of_instruction_accounts(accounts, 7)?;
+                Self::check_sysvar::<Rent>(&accounts[4])?;
+                Self::check_sysvar::<Clock>(&accounts[5])?;
+                Self::upgrade(&accounts[0], &accounts[1], &accounts[2], &accounts[3], &accounts[6], program_id, syscalls)
+            }
+            UpgradeableLoaderInstruction::SetAuthority => {
+                Self::check_number_of_instruction_accounts(accounts, 2)?;
+                Self::set_authority(&accounts[0], &accounts[1], accounts.get(2), false, syscalls)
+            }
+            UpgradeableLoaderInstruction::SetAuthorityChecked => {
+                Self::check_number_of_instruction_accounts(accounts, 3)?;
+                Self::set_authority(&accounts[0], &accounts[1], accounts.get(2), true, syscalls)
+            }
+            UpgradeableLoaderInstruction::Close => {
+                Self::check_number_of_instruction_accounts(accounts, 2)?;
+                Self::close(&accounts[0], &accounts[1], accounts.get(2), accounts.get(3), program_id, syscalls)
+            }
+            _ => {
+                syscalls.sol_log(&format!("Unimplemented instruction {:?}", instruction));
+                Err(InstructionError::GenericError)
+            }
+        }
+    }
+
+    fn initialize_buffer<'a>(buffer: &'a AccountInfo<'a>, authority: &'a AccountInfo<'a>, syscalls: &mut dyn Syscalls) -> Result<(), InstructionError> {
+        let state = deserialize_state(&buffer.data.borrow())?;
+        if state != UpgradeableLoaderState::Uninitialized {
+            syscalls.sol_log("Buffer account already initialized");
+            return Err(InstructionError::AccountAlreadyInitialized);
+        }
+
+        Self::store_state(buffer, UpgradeableLoaderState::Buffer {
+            authority_address: Some(*authority.key),
+        }, syscalls)
+    }
+
+    fn write<'a>(buffer: &'a AccountInfo<'a>, authority: &'a AccountInfo<'a>, offset: usize, bytes: &[u8], syscalls: &mut dyn Syscalls) -> Result<(), InstructionError> {
+        let state = deserialize_state(&buffer.data.borrow())?;
+        match state {
+            UpgradeableLoaderState::Buffer { authority_address } => {
+                Self::check_authority(authority_address, authority, "Buffer", syscalls)?;
+            }
+            _ => {
+                syscalls.sol_log("Invalid Buffer account");
+                return Err(InstructionError::InvalidAccountData);
+            }
+        }
+
+        let start = UpgradeableLoaderState::size_of_buffer_metadata() + offset;
+        let end = start.saturating_add(bytes.len());
+        let mut data = buffer.try_borrow_mut_data().map_err(|_| InstructionError::AccountBorrowFailed)?;
+        if data.len() < end {
+            syscalls.sol_log(&format!("Write overflow: {} < {}", data.len(), end));
+            return Err(InstructionError::AccountDataTooSmall);
+        }
+        data[start..end].copy_from_slice(bytes);
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn upgrade<'a>(programdata: &'a AccountInfo<'a>, program: &'a AccountInfo<'a>, buffer: &'a AccountInfo<'a>, spill: &'a AccountInfo<'a>, authority: &'a AccountInfo<'a>, loader_id: &Pubkey, syscalls: &mut dyn Syscalls) -> Result<(), InstructionError> {
+        // Verify Program account
+        if !program.executable {
+            syscalls.sol_log("Program account not executable");
+            return Err(InstructionError::AccountNotExecutable);
+        }
+        if !program.is_writable {
+            syscalls.sol_log("Program account not writeable");
+            return Err(InstructionError::InvalidArgument);
+        }
+        if !program.owner.get().eq(loader_id) {
+            syscalls.sol_log("Program account not owned by loader");
+            return Err(InstructionError::IncorrectProgramId);
+        }
+        let state = deserialize_state(&program.data.borrow())?;
+        match state {
+            UpgradeableLoaderState::Program { programdata_address } if programdata_address.eq(programdata.key) => {}
+            UpgradeableLoaderState::Program { .. } => {
+                syscalls.sol_log("Program and ProgramData account mismatch");
+                return Err(InstructionError::InvalidArgument);
+            }
+            _ => {
+                syscalls.sol_log("Invalid Program account");
+                return Err(InstructionError::InvalidAccountData);
+            }
+        }
+
+        // Verify Buffer account
+        let state = deserialize_state(&buffer.data.borrow())?;
+        match state {
+            UpgradeableLoaderState::Buffer { authority_address } => {
+                if authority_address != Some(*authority.key) {
+                    syscalls.sol_log("Buffer and upgrade authority don't match");
+                    return Err(InstructionError::IncorrectAuthority);
+                }
+                if !authority.is_signer {
+                    syscalls.sol_log("Upgrade authority did not sign");
+                    return Err(InstructionError::MissingRequiredSignature);
+                }
+            }
+            _ => {
+                syscalls.sol_log("Invalid Buffer account");
+                return Err(InstructionError::InvalidArgument);
+            }
+        }
+        let program_bytes = UpgradeableLoaderState::program_bytes(&buffer.data.borrow())
+            .unwrap_or_default()
+            .to_vec();
+        if program_bytes.is_empty() {
+            syscalls.sol_log("Buffer account too small");
+            return Err(InstructionError::InvalidAccountData);
+        }
+
+        // Verify ProgramData account
+        let programdata_len = programdata.data_len();
+        if programdata_len < UpgradeableLoaderState::size_of_programdata(program_bytes.len()) {
+            syscalls.sol_log("ProgramData account not large enough");
+            return Err(InstructionError::AccountDataTooSmall);
+        }
+        let state = deserialize_state(&programdata.data.borrow())?;
+        let upgrade_authority_address = match state {
+            UpgradeableLoaderState::ProgramData { upgrade_authority_address, .. } => {
+                Self::check_authority(upgrade_authority_address, authority, "Program", syscalls)?;
+                upgrade_authority_address
+            }
+            _ => {
+                syscalls.sol_log("Invalid ProgramData account");
+                return Err(InstructionError::InvalidAccountData);
+            }
+        };
+
+        // Update the ProgramData account and record the slot of the upgrade
+        let slot = syscalls.get_clock().slot;
+        let mut data = bincode::serialize(&UpgradeableLoaderState::ProgramData {
+            slot,
+            upgrade_authority_address,
+        }).map_err(|_| InstructionError::InvalidAccountData)?;
+        data.extend_from_slice(&program_bytes);
+        data.resize(programdata_len, 0);
+        set_data(programdata, data, syscalls)?;
+
+        // Fund ProgramData to rent-exemption, spill the rest
+        let programdata_balance_required = 1.max(syscalls.rent_exempt_for_size(programdata_len));
+        let spilled = (programdata.lamports() + buffer.lamports())
+            .checked_sub(programdata_balance_required)
+            .ok_or(InstructionError::InsufficientFunds)?;
+        set_lamports(spill, spill.lamports() + spilled)?;
+        set_lamports(buffer, 0)?;
+        set_lamports(programdata, programdata_balance_required)?;
+
+        let mut buffer_data = buffer.data.borrow().to_vec();
+        buffer_data.truncate(UpgradeableLoaderState::size_of_buffer(0));
+        set_data(buffer, buffer_data, syscalls)?;
+
+        syscalls.sol_log(&format!("Upgraded program {:?}", program.key));
+        Ok(())
+    }
+
+    fn set_authority<'a>(account: &'a AccountInfo<'a>, present_authority: &'a AccountInfo<'a>, new_authority: Option<&'a AccountInfo<'a>>, checked: bool, syscalls: &mut dyn Syscalls) -> Result<(), InstructionError> {
+        if checked && !new_authority.map(|info| info.is_signer).unwrap_or(false) {
+            syscalls.sol_log("New authority did not sign");
+            return Err(InstructionError::MissingRequiredSignature);
+        }
+        let new_authority_address = new_authority.map(|info| *info.key);
+
+        let state = deserialize_state(&account.data.borrow())?;
+        match state {
+            UpgradeableLoaderState::Buffer { authority_address } => {
+                if new_authority_address.is_none() {
+                    syscalls.sol_log("Buffer authority is not optional");
+                    return Err(InstructionError::IncorrectAuthority);
+                }
+                Self::check_authority(authority_address, present_authority, "Buffer", syscalls)?;
+                Self::store_state(account, UpgradeableLoaderState::Buffer {
+                    authority_address: new_authority_address,
+                }, syscalls)?;
+            }
+            UpgradeableLoaderState::ProgramData { slot, upgrade_authority_address } => {
+                Self::check_authority(upgrade_authority_address, present_authority, "Program", syscalls)?;
+                Self::store_state(account, UpgradeableLoaderState::ProgramData {
+                    slot,
+                    upgrade_authority_address: new_authority_address,
+                }, syscalls)?;
+            }
+            _ => {
+                syscalls.sol_log("Account does not support authorities");
+                return Err(InstructionError::InvalidArgument);
+            }
+        }
+
+        syscalls.sol_log(&format!("New authority {:?}", new_authority_address));
+        Ok(())
+    }
+
+    fn close<'a>(account: &'a AccountInfo<'a>, recipient: &'a AccountInfo<'a>, authority: Option<&'a AccountInfo<'a>>, program: Option<&'a AccountInfo<'a>>, loader_id: &Pubkey, syscalls: &mut dyn Syscalls) -> Result<(), InstructionError> {
+        if account.key.eq(recipient.key) {
+            syscalls.sol_log("Recipient is the same as the account being closed");
+            return Err(InstructionError::InvalidArgument);
+        }
+
+        let state = deserialize_state(&account.data.borrow())?;
+        match state {
+            UpgradeableLoaderState::Uninitialized => {
+                Self::drain(account, recipient)?;
+                syscalls.sol_log(&format!("Closed Uninitialized {:?}", account.key));
+            }
+            UpgradeableLoaderState::Buffer { authority_address } => {
+                let authority = authority.ok_or(InstructionError::NotEnoughAccountKeys)?;
+                Self::check_authority(authority_address, authority, "Buffer", syscalls)?;
+                Self::drain(account, recipient)?;
+                Self::store_state(account, UpgradeableLoaderState::Uninitialized, syscalls)?;
+                syscalls.sol_log(&format!("Closed Buffer {:?}", account.key));
+            }
+            UpgradeableLoaderState::ProgramData { upgrade_authority_address, .. } => {
+                let authority = authority.ok_or(InstructionError::NotEnoughAccountKeys)?;
+                let program = program.ok_or(InstructionError::NotEnoughAccountKeys)?;
+                if !program.is_writable {
+                    syscalls.sol_log("Program account is not writable");
+                    return Err(InstructionError::InvalidArgument);
+                }
+                if !program.owner.get().eq(loader_id) {
+                    syscalls.sol_log("Program account not owned by loader");
+                    return Err(InstructionError::IncorrectProgramId);
+                }
+                let state = deserialize_state(&program.data.borrow())?;
+                match state {
+                    UpgradeableLoaderState::Program { programdata_address } if programdata_address.eq(account.key) => {}
+                    UpgradeableLoaderState::Program { .. } => {
+                        syscalls.sol_log("ProgramData account does not match ProgramData account");
+                        return Err(InstructionError::InvalidArgument);
+                    }
+                    _ => {
+                        syscalls.sol_log("Invalid Program account");
+                        return Err(InstructionError::InvalidArgument);
+                    }
+                }
+
+                Self::check_authority(upgrade_authority_address, authority, "Program", syscalls)?;
+                Self::drain(account, recipient)?;
+                Self::store_state(account, UpgradeableLoaderState::Uninitialized, syscalls)?;
+                syscalls.sol_log(&format!("Closed Program {:?}", program.key));
+            }
+            _ => {
+                syscalls.sol_log("Account does not support closing");
+                return Err(InstructionError::InvalidArgument);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn check_authority<'a>(expected: Option<Pubkey>, authority: &'a AccountInfo<'a>, kind: &str, syscalls: &mut dyn Syscalls) -> Result<(), InstructionError> {
+        match expected {
+            None => {
+                syscalls.sol_log(&format!("{} is immutable", kind));
+                Err(InstructionError::Immutable)
+            }
+            Some(expected) if !expected.eq(authority.key) => {
+                syscalls.sol_log(&format!("Incorrect {} authority provided", kind.to_lowercase()));
+                Err(InstructionError::IncorrectAuthority)
+            }
+            Some(_) if !authority.is_signer => {
+                syscalls.sol_log(&format!("{} authority did not sign", kind));
+                Err(InstructionError::MissingRequiredSignature)
+            }
+            Some(_) => Ok(()),
+        }
+    }
+
+    fn check_sysvar<'a, S: SysvarId>(info: &'a AccountInfo<'a>) -> Result<(), InstructionError> {
+        if !S::check_id(info.key) {
+            return Err(InstructionError::InvalidArgument);
+        }
+        Ok(())
+    }
+
+    fn drain<'a>(account: &'a AccountInfo<'a>, recipient: &'a AccountInfo<'a>) -> Result<(), InstructionError> {
+        set_lamports(recipient, recipient.lamports() + account.lamports())?;
+        set_lamports(account, 0)
+    }
+
+    // Rewrites the state header, keeping program bytes unless the account is uninitialized
+    fn store_state<'a>(info: &'a AccountInfo<'a>, state: UpgradeableLoaderState, syscalls: &mut dyn Syscalls) -> Result<(), InstructionError> {
+        let header = bincode::serialize(&state).map_err(|_| InstructionError::InvalidAccountData)?;
+        let mut data = info.data.borrow().to_vec();
+        if state == UpgradeableLoaderState::Uninitialized {
+            data = header;
+        } else {
+            if data.len() < header.len() {
+                data.resize(header.len(), 0);
+            }
+            data[..header.len()].copy_from_slice(&header);
+        }
+        set_data(info, data, syscalls)?;
+        Ok(())
+    }
+
+    fn check_number_of_instruction_accounts<'e>(accounts: &[AccountInfo<'e>], count: usize) -> Result<(), InstructionError> {
+        if accounts.len() < count {
+            return Err(InstructionError::NotEnoughAccountKeys);
+        }
+        Ok(())
+    }
+}
@@ -1,10 +1,8 @@
 use std::collections::HashMap;
-use std::mem::forget;
-use std::sync::atomic::AtomicPtr;
 use solana_program::{
     pubkey::Pubkey,
     account_info::AccountInfo,
-    entrypoint::ProgramResult,
+    entrypoint::{ProgramResult, MAX_PERMITTED_DATA_INCREASE},
     instruction::Instruction,
     rent::Rent,
     clock::Clock,
@@ -13,10 +11,12 @@ use solana_program::{
 };
 use base64::prelude::BASE64_STANDARD;
 use base64::Engine;
-use crate::runtime::Scope;
+use crate::runtime::{untracked, FaultMode, Frame, FrameAccount, InvokeStage, RuntimeEvent, Scope};
 
 
 pub const MAX_RETURN_DATA: usize = 1024;
+pub const MAX_CPI_INSTRUCTION_DATA_LEN: usize = 10 * 1024;
+pub const MAX_CPI_INSTRUCTION_ACCOUNTS: usize = u8::MAX as usize;
 
 
 impl Syscalls for Scope {
@@ -29,6 +29,7 @@ impl Syscalls for Scope {
     }
 
     fn sol_log(&mut self, message: &str) {
+        let _untracked = untracked();
         self.receipt.push_msg(format!("Program logged: \"{message}\""));
     }
 
@@ -39,6 +40,7 @@ impl Syscalls for Scope {
     }
 
     fn sol_log_data(&mut self, data: &[&[u8]]) {
+        let _untracked = untracked();
         let mut s = "Program data:".to_string();
         let mut v = Vec::new();
         for bytes in data.iter() {
@@ -46,18 +48,36 @@ impl Syscalls for Scope {
             s.push_str(&BASE64_STANDARD.encode(*bytes));
             v.push(Vec::from(*bytes));
         }
-        self.receipt.log_datas.push((self.receipt.active_program(), v));
+        self.receipt.push_data(v);
         self.receipt.push_msg(s);
     }
 
     fn sol_log_compute_units(&mut self) {
-        self.receipt.push_msg("Compute unist mot available".to_string());
+        let _untracked = untracked();
+        // Programs aren't metered, the whole limit is left as the consumed lines report
+        let remaining = self.receipt.compute_budget.compute_unit_limit;
+        self.receipt.push_msg(format!("Program consumption: {} units remaining", remaining));
     }
 
     fn invoke_signed_unchecked(&mut self, instruction: &Instruction, account_infos: &[AccountInfo], signers_seeds: &[&[&[u8]]]) -> ProgramResult {
-        let instruction = unsafe { std::mem::transmute::<&Instruction, &'static Instruction>(instruction) };
-        let account_infos = unsafe { std::mem::transmute::<&[AccountInfo], &'static [AccountInfo<'static>]>(account_infos) };
         let inline_program: bool = instruction.program_id.eq(&solana_program::system_program::ID);
+        let _untracked = untracked();
+
+        // Account infos are capped by the transaction account lock limit, as on chain
+        let max_account_infos = self.settings.transaction_account_lock_limit;
+        let exceeded = if instruction.data.len() > MAX_CPI_INSTRUCTION_DATA_LEN {
+            Some((ProgramError::MaxInstructionDataLenExceeded, instruction.data.len(), MAX_CPI_INSTRUCTION_DATA_LEN))
+        } else if instruction.accounts.len() > MAX_CPI_INSTRUCTION_ACCOUNTS {
+            Some((ProgramError::MaxInstructionAccountsExceeded, instruction.accounts.len(), MAX_CPI_INSTRUCTION_ACCOUNTS))
+        } else if account_infos.len() > max_account_infos {
+            Some((ProgramError::MaxInstructionAccountInfosExceeded, account_infos.len(), max_account_infos))
+        } else {
+            None
+        };
+        if let Some((err, len, max)) = exceeded {
+            self.receipt.abort(format!("{} ({} > {})", err, len, max), err.clone());
+            return Err(err);
+        }
 
         let mut signed: Option<Pubkey> = None;
         if signers_seeds.len() > 0 {
@@ -77,7 +97,7 @@ impl Syscalls for Scope {
             .map(|info| (info.key, info))
             .collect();
 
-        let mut accounts: Vec<AccountInfo> = Vec::with_capacity(instruction.accounts.len());
+        let mut frame = Frame::default();
         for account_meta in instruction.accounts.iter() {
             let account_ref = account_refs.get(&account_meta.pubkey);
             if account_ref.is_none() {
@@ -99,11 +119,7 @@ impl Syscalls for Scope {
                 return Err(ProgramError::InvalidInstructionData);
             }
 
-            accounts.push(self.replicate_info(
-                account_ref,
-                account_meta,
-                !inline_program
-            ));
+            frame.add(FrameAccount::from_info(account_ref)?, account_meta.is_signer, account_meta.is_writable);
         }
 
         if !account_refs.contains_key(&instruction.program_id) {
@@ -118,50 +134,48 @@ impl Syscalls for Scope {
         self.receipt.log_program_invoked(&instruction.program_id);
         self.receipt.return_data = None;
 
-
-        println!("\n==== Before ====");
-        for meta in self.metas.iter() {
-            println!("Meta {}", meta.1.to_string());
-        }
-        println!("++ Old Accounts ++");
-        for acc in accounts.iter() {
-            println!("Account {:?}", acc);
-        }
-        println!("++ New Accounts ++");
-        for acc in account_infos.iter() {
-            println!("Account {:?}", acc);
-        }
-        println!("\n");
-
-        let res = unsafe {
-            self.get_package(&instruction.program_id)
-                .execute(
-            unsafe { std::mem::transmute::<&[AccountInfo], &'static [AccountInfo<'static>]>(accounts.as_slice()) },
-                instruction.data.as_slice(),
-                &instruction.program_id,
-                self.clone()
-            )
+        let fault = self.faults.on_invoke(instruction, self.receipt.call_stack.len());
+        if let Some(fault) = fault.clone().filter(|fault| fault.mode == FaultMode::FailBeforeExecution) {
+            let err = fault.error.clone();
+            self.receipt.log_fault_injected(fault);
+            self.receipt.log_program_failed(err.clone());
+            self.receipt.call_stack.pop();
+            return Err(err);
+        }
+
+        let res = self.run_frame(&mut frame, &instruction.program_id, &instruction.data, |scope, accounts, program_id, data| {
+            scope.receipt.tracer.emit(RuntimeEvent::Accounts {
+                stage: InvokeStage::Before,
+                program_id,
+                accounts,
+            });
+
+            let heap = if inline_program { None } else { scope.enter_heap_frame() };
+            let res = scope.get_package(program_id).execute(accounts, data, program_id, scope);
+            scope.exit_heap_frame(heap);
+
+            scope.receipt.tracer.emit(RuntimeEvent::Accounts {
+                stage: InvokeStage::After,
+                program_id,
+                accounts,
+            });
+            res
+        });
+        let res = match self.receipt.take_abort() {
+            Some(err) => Err(err),
+            None => res,
         };
-
-        println!("==== After ====");
-        for meta in self.metas.iter() {
-            println!("Meta {}", meta.1.to_string());
-        }
-        println!("++ Old Accounts ++");
-        for acc in accounts.iter() {
-            println!("Account {:?}", acc);
-        }
-        println!("++ New Accounts ++");
-        for acc in account_infos.iter() {
-            println!("Account {:?}", acc);
-        }
-        println!("\n");
-
-        if instruction.program_id.eq(&solana_program::system_program::ID) {
-            self.finalize_system_invoke(
-                unsafe { std::mem::transmute::<&[AccountInfo], &'static [AccountInfo<'static>]>(accounts.as_slice()) }
-            )?;
-        }
+        let res = match fault {
+            Some(fault) => {
+                let err = fault.error.clone();
+                self.receipt.log_fault_injected(fault);
+                Err(err)
+            }
+            None => res,
+        };
+        // A failed call leaves the caller's accounts as they were
+        let res = res.and_then(|_| frame.accounts.iter()
+            .try_for_each(|account| self.update_caller(account_refs[&account.pubkey], account)));
 
         match &res {
             Ok(_) => {self.receipt.log_program_succeed();}
@@ -173,14 +187,18 @@ impl Syscalls for Scope {
     }
 
     fn set_return_data(&mut self, data: &[u8]) {
+        let _untracked = untracked();
         if data.len() > MAX_RETURN_DATA {
-            panic!("Return Data exceeded length");
+            let err = ProgramError::ReturnDataTooLarge;
+            self.receipt.abort(format!("{} ({} > {})", err, data.len(), MAX_RETURN_DATA), err);
+            return;
         }
-        self.receipt.return_data = Some((self.receipt.active_program(), Vec::from(data)))
+        self.receipt.set_return_data(data)
     }
 
+    // Empty return data reads as none, as the sdk reports it on chain
     fn get_return_data(&mut self) -> Option<(Pubkey, Vec<u8>)> {
-        self.receipt.return_data.clone()
+        self.receipt.return_data.clone().filter(|(_, data)| !data.is_empty())
     }
 
     fn get_clock(&mut self) -> Clock {
@@ -191,55 +209,39 @@ impl Syscalls for Scope {
         self.settings.as_rent()
     }
 
-    fn set_owner(&mut self, to: &Pubkey, owner: &Pubkey) {
-        if !self.metas.contains_key(to) {
-            panic!("Undefined Account {}", to.to_string());
-        }
-        let mut meta = self.metas.get_mut(to).unwrap();
-        meta.set_owner(owner);
-    }
-
-    fn set_data(&mut self, info: &AccountInfo, data: Vec<u8>) {
-        if !self.metas.contains_key(info.key) {
-            panic!("Undefined Account {}", info.key.to_string());
-        }
-        let mut meta = self.metas.get_mut(info.key).unwrap();
-        meta.set_data(info, data);
-    }
-
-    fn set_lamports(&mut self, to: &Pubkey, amount: u64) {
-        if !self.metas.contains_key(to) {
-            panic!("Undefined Account {}", to.to_string());
-        }
-        let mut meta = self.metas.get_mut(to).unwrap();
-        meta.set_lamports(amount);
+    fn realloc(&mut self, data: &mut &mut [u8], new_len: usize, max_increase: usize) -> ProgramResult {
+        let _untracked = untracked();
+        self.frames.last_mut()
+            .and_then(|buffers| buffers.iter_mut().find(|buffer| buffer.holds(data)))
+            .ok_or(ProgramError::InvalidRealloc)?
+            .resize(data, new_len, max_increase)
     }
 
     fn get_program_id(&self) -> Pubkey {
         self.receipt.active_program()
     }
 
-    fn finalize_system_invoke(&mut self, accounts: &[AccountInfo]) -> ProgramResult {
-        for acc in accounts.iter() {
-            if acc.lamports() < self.rent_exempt_for_size(acc.data_len()) {
-                self.receipt.push_msg(format!("Account {} is not rent exempt", acc.key.to_string()));
-                return Err(ProgramError::AccountNotRentExempt);
-            }
-        }
-
-        Ok(())
-    }
-
     fn rent_exempt_for_size(&mut self, size: usize) -> u64 {
-        let rent = self.get_rent();
-        rent.lamports_per_byte_year * (rent.exemption_threshold as u64) * (size as u64)
+        self.get_rent().minimum_balance(size)
     }
-    
-    fn get_data_ptr(&mut self, key: &Pubkey) -> AtomicPtr<Vec<u8>> {
-        if !self.metas.contains_key(key) {
-            panic!("Undefined Account {}", key.to_string());
+}
+
+impl Scope {
+    // Changes of the callee land in the info of the caller, the data grows within the caller's own limit
+    fn update_caller(&mut self, info: &AccountInfo, account: &FrameAccount) -> ProgramResult {
+        if info.try_lamports()? != account.lamports {
+            **info.try_borrow_mut_lamports()? = account.lamports;
         }
-        let mut meta = self.metas.get_mut(key).unwrap();
-        meta.get_data_ptr()
+        if info.owner.get() != account.owner {
+            info.assign(&account.owner);
+        }
+        if info.try_borrow_data()?.as_ref() != account.data.as_slice() {
+            let mut data = info.try_borrow_mut_data()?;
+            if data.len() != account.data.len() {
+                self.realloc(&mut data, account.data.len(), MAX_PERMITTED_DATA_INCREASE)?;
+            }
+            data.copy_from_slice(&account.data);
+        }
+        Ok(())
     }
 }
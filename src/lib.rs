@@ -1,6 +1,7 @@
 mod runtime;
 mod executor;
 mod suit;
+mod rpc;
 
 mod solana_program {
     pub use solana_program::*;
@@ -0,0 +1,650 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, MutexGuard};
+use base64::prelude::BASE64_STANDARD;
+use base64::Engine;
+use serde_json::{json, Value};
+use solana_program::{
+    address_lookup_table::{self, state::AddressLookupTable},
+    instruction::InstructionError,
+    message::VersionedMessage,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+use crate::runtime::{Account, AccountQuery, Environment, Receipt, Transaction, TransactionError};
+
+
+// Blocks a blockhash stays valid for, reported with it
+const MAX_PROCESSING_AGE: u64 = 150;
+
+#[derive(Debug)]
+pub(crate) struct RpcError {
+    code: i64,
+    message: String,
+    data: Option<Value>,
+}
+
+impl RpcError {
+    fn new(code: i64, message: String) -> Self {
+        Self { code, message, data: None }
+    }
+
+    fn invalid_params(message: String) -> Self {
+        Self::new(-32602, message)
+    }
+
+    fn simulation_failed(err: &TransactionError, data: Value) -> Self {
+        Self {
+            code: -32002,
+            message: format!("Transaction simulation failed: {:?}", err),
+            data: Some(data),
+        }
+    }
+
+    fn to_json(&self) -> Value {
+        let mut error = json!({ "code": self.code, "message": self.message });
+        if let Some(data) = &self.data {
+            error["data"] = data.clone();
+        }
+        error
+    }
+}
+
+// What getTransaction and getSignatureStatuses report about a landed transaction
+struct ProcessedTransaction {
+    slot: u64,
+    block_time: i64,
+    wire: Vec<u8>,
+    message: VersionedMessage,
+    loaded: (Vec<Pubkey>, Vec<Pubkey>),
+    err: Option<TransactionError>,
+    fee: u64,
+    pre_balances: Vec<u64>,
+    post_balances: Vec<u64>,
+    logs: Vec<String>,
+    return_data: Option<(Pubkey, Vec<u8>)>,
+}
+
+pub(crate) struct RpcState {
+    pub(crate) env: Arc<Mutex<Environment>>,
+    transactions: Mutex<HashMap<String, ProcessedTransaction>>,
+}
+
+impl RpcState {
+    pub(crate) fn new(env: Arc<Mutex<Environment>>) -> Self {
+        Self { env, transactions: Mutex::new(HashMap::new()) }
+    }
+
+    pub(crate) fn environment(&self) -> MutexGuard<'_, Environment> {
+        self.env.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn transactions(&self) -> MutexGuard<'_, HashMap<String, ProcessedTransaction>> {
+        self.transactions.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    // A request or a batch of them
+    pub(crate) fn handle(&self, body: &[u8]) -> String {
+        let response = match serde_json::from_slice::<Value>(body) {
+            Ok(Value::Array(requests)) => Value::Array(requests.iter().map(|request| self.respond(request)).collect()),
+            Ok(request) => self.respond(&request),
+            Err(err) => error_response(Value::Null, RpcError::new(-32700, format!("Parse error: {}", err))),
+        };
+        response.to_string()
+    }
+
+    fn respond(&self, request: &Value) -> Value {
+        let id = request.get("id").cloned().unwrap_or(Value::Null);
+        let method = match request.get("method").and_then(Value::as_str) {
+            Some(method) => method,
+            None => return error_response(id, RpcError::new(-32600, "Invalid request".to_string())),
+        };
+        let params = match request.get("params") {
+            None | Some(Value::Null) => Vec::new(),
+            Some(Value::Array(params)) => params.clone(),
+            Some(_) => return error_response(id, RpcError::invalid_params("Params must be an array".to_string())),
+        };
+        match self.call(method, &params) {
+            Ok(result) => json!({ "jsonrpc": "2.0", "result": result, "id": id }),
+            Err(err) => error_response(id, err),
+        }
+    }
+
+    fn call(&self, method: &str, params: &[Value]) -> Result<Value, RpcError> {
+        match method {
+            "getAccountInfo" => self.get_account_info(params),
+            "getMultipleAccounts" => self.get_multiple_accounts(params),
+            "getBalance" => self.get_balance(params),
+            "getLatestBlockhash" => self.get_latest_blockhash(),
+            "sendTransaction" => self.send_transaction(params),
+            "simulateTransaction" => self.simulate_transaction(params),
+            "getSignatureStatuses" => self.get_signature_statuses(params),
+            "getProgramAccounts" => self.get_program_accounts(params),
+            "getTransaction" => self.get_transaction(params),
+            "requestAirdrop" => self.request_airdrop(params),
+            "getMinimumBalanceForRentExemption" => {
+                let size = param_u64(params, 0)? as usize;
+                Ok(json!(self.environment().settings().as_rent().minimum_balance(size)))
+            }
+            "getSlot" | "getBlockHeight" => Ok(json!(self.environment().settings().slot)),
+            "getHealth" => Ok(json!("ok")),
+            _ => Err(RpcError::new(-32601, format!("Method not found: {}", method))),
+        }
+    }
+
+    // ++++++ Accounts +++++
+
+    fn get_account_info(&self, params: &[Value]) -> Result<Value, RpcError> {
+        let pubkey = param_pubkey(params, 0)?;
+        let config = AccountConfig::parse(params.get(1))?;
+        let mut env = self.environment();
+        let account = load_account(&mut env, &pubkey).map(|(account, executable)| config.encode(&account, executable));
+        Ok(with_context(&env, account.unwrap_or(Value::Null)))
+    }
+
+    fn get_multiple_accounts(&self, params: &[Value]) -> Result<Value, RpcError> {
+        let pubkeys = params.first()
+            .and_then(Value::as_array)
+            .ok_or_else(|| RpcError::invalid_params("Expected an array of pubkeys".to_string()))?
+            .iter()
+            .map(parse_pubkey)
+            .collect::<Result<Vec<Pubkey>, RpcError>>()?;
+        let config = AccountConfig::parse(params.get(1))?;
+        let mut env = self.environment();
+        let accounts: Vec<Value> = pubkeys.iter()
+            .map(|pubkey| load_account(&mut env, pubkey)
+                .map(|(account, executable)| config.encode(&account, executable))
+                .unwrap_or(Value::Null))
+            .collect();
+        Ok(with_context(&env, json!(accounts)))
+    }
+
+    fn get_balance(&self, params: &[Value]) -> Result<Value, RpcError> {
+        let pubkey = param_pubkey(params, 0)?;
+        let mut env = self.environment();
+        let lamports = load_account(&mut env, &pubkey).map(|(account, _)| account.lamports).unwrap_or(0);
+        Ok(with_context(&env, json!(lamports)))
+    }
+
+    fn get_program_accounts(&self, params: &[Value]) -> Result<Value, RpcError> {
+        let program_id = param_pubkey(params, 0)?;
+        let config = AccountConfig::parse(params.get(1))?;
+        let query = match params.get(1).and_then(|config| config.get("filters")) {
+            Some(filters) => parse_filters(AccountQuery::owned_by(&program_id), filters)?,
+            None => AccountQuery::owned_by(&program_id),
+        };
+        // Sliced by the encoding instead of the query, space reports the whole data
+        let env = self.environment();
+        let accounts: Vec<Value> = env.query(&query).iter()
+            .map(|account| json!({ "pubkey": account.pubkey.to_string(), "account": config.encode(account, false) }))
+            .collect();
+        match params.get(1).and_then(|config| config.get("withContext")).and_then(Value::as_bool) {
+            Some(true) => Ok(with_context(&env, json!(accounts))),
+            _ => Ok(json!(accounts)),
+        }
+    }
+
+    fn request_airdrop(&self, params: &[Value]) -> Result<Value, RpcError> {
+        let pubkey = param_pubkey(params, 0)?;
+        let lamports = param_u64(params, 1)?;
+        let mut env = self.environment();
+        match env.get_account_mut(&pubkey) {
+            Some(account) => account.lamports += lamports,
+            None => env.add_account(Account::new(pubkey, lamports, &solana_program::system_program::ID, Vec::new())),
+        }
+        // Recorded as a landed transaction, clients confirm airdrops by signature
+        let signature = bs58::encode((0..64).map(|_| rand::random::<u8>()).collect::<Vec<u8>>()).into_string();
+        self.transactions().insert(signature.clone(), ProcessedTransaction {
+            slot: env.settings().slot,
+            block_time: env.settings().unix_timestamp,
+            wire: Vec::new(),
+            message: VersionedMessage::Legacy(Default::default()),
+            loaded: (Vec::new(), Vec::new()),
+            err: None,
+            fee: 0,
+            pre_balances: Vec::new(),
+            post_balances: Vec::new(),
+            logs: Vec::new(),
+            return_data: None,
+        });
+        Ok(json!(signature))
+    }
+
+    // ++++++ Transactions +++++
+
+    fn get_latest_blockhash(&self) -> Result<Value, RpcError> {
+        let env = self.environment();
+        let value = json!({
+            "blockhash": env.settings().blockhash.to_string(),
+            "lastValidBlockHeight": env.settings().slot + MAX_PROCESSING_AGE,
+        });
+        Ok(with_context(&env, value))
+    }
+
+    // Preflight runs on a fork, a transaction that fails it never lands
+    fn send_transaction(&self, params: &[Value]) -> Result<Value, RpcError> {
+        let config = params.get(1).cloned().unwrap_or(Value::Null);
+        let wire = decode_transaction(params, &config)?;
+        let transaction = Transaction::from_wire(&wire).map_err(invalid_transaction)?;
+        let signature = transaction.signatures()[0].to_string();
+        if self.transactions().contains_key(&signature) {
+            return Err(RpcError::new(-32002, "Transaction simulation failed: This transaction has already been processed".to_string()));
+        }
+
+        let mut env = self.environment();
+        if !config.get("skipPreflight").and_then(Value::as_bool).unwrap_or(false) {
+            let receipt = env.fork().execute(&transaction);
+            if let Err(err) = receipt.transaction_result() {
+                return Err(RpcError::simulation_failed(&err, simulation_json(&receipt, Some(&err))));
+            }
+        }
+
+        let message = transaction.message().unwrap().clone();
+        let loaded = loaded_addresses(&env, &message);
+        let keys: Vec<Pubkey> = message.static_account_keys().iter()
+            .chain(loaded.0.iter())
+            .chain(loaded.1.iter())
+            .cloned()
+            .collect();
+        let balances = |env: &mut Environment| keys.iter()
+            .map(|key| load_account(env, key).map(|(account, _)| account.lamports).unwrap_or(0))
+            .collect::<Vec<u64>>();
+
+        let pre_balances = balances(&mut env);
+        let receipt = env.execute(&transaction);
+        let post_balances = balances(&mut env);
+        self.transactions().insert(signature.clone(), ProcessedTransaction {
+            slot: env.settings().slot,
+            block_time: env.settings().unix_timestamp,
+            wire,
+            message,
+            loaded,
+            err: receipt.transaction_result().err(),
+            fee: receipt.fee().map(|fee| fee.total()).unwrap_or(0),
+            pre_balances,
+            post_balances,
+            logs: cluster_logs(&receipt),
+            return_data: receipt.return_data.clone(),
+        });
+        Ok(json!(signature))
+    }
+
+    fn simulate_transaction(&self, params: &[Value]) -> Result<Value, RpcError> {
+        let config = params.get(1).cloned().unwrap_or(Value::Null);
+        let wire = decode_transaction(params, &config)?;
+        let mut transaction = match config.get("sigVerify").and_then(Value::as_bool).unwrap_or(false) {
+            true => Transaction::from_wire(&wire),
+            false => Transaction::from_wire_unverified(&wire),
+        }.map_err(invalid_transaction)?;
+
+        let mut env = self.environment();
+        if config.get("replaceRecentBlockhash").and_then(Value::as_bool).unwrap_or(false) {
+            let blockhash = env.settings().blockhash;
+            if let Some(message) = transaction.message.as_mut() {
+                message.set_recent_blockhash(blockhash);
+            }
+        }
+        let mut fork = env.fork();
+        let receipt = fork.execute(&transaction);
+        let mut value = simulation_json(&receipt, receipt.transaction_result().err().as_ref());
+
+        // Account states after the simulation, when asked for
+        if let Some(accounts) = config.get("accounts") {
+            let account_config = AccountConfig::parse(Some(accounts))?;
+            let addresses = accounts.get("addresses")
+                .and_then(Value::as_array)
+                .ok_or_else(|| RpcError::invalid_params("Expected accounts.addresses".to_string()))?
+                .iter()
+                .map(parse_pubkey)
+                .collect::<Result<Vec<Pubkey>, RpcError>>()?;
+            value["accounts"] = Value::Array(addresses.iter()
+                .map(|pubkey| load_account(&mut fork, pubkey)
+                    .map(|(account, executable)| account_config.encode(&account, executable))
+                    .unwrap_or(Value::Null))
+                .collect());
+        }
+        Ok(with_context(&env, value))
+    }
+
+    fn get_signature_statuses(&self, params: &[Value]) -> Result<Value, RpcError> {
+        let signatures = params.first()
+            .and_then(Value::as_array)
+            .ok_or_else(|| RpcError::invalid_params("Expected an array of signatures".to_string()))?;
+        let transactions = self.transactions();
+        let statuses: Vec<Value> = signatures.iter()
+            .map(|signature| match signature.as_str().and_then(|signature| transactions.get(signature)) {
+                Some(processed) => json!({
+                    "slot": processed.slot,
+                    "confirmations": Value::Null,
+                    "err": error_json(&processed.err),
+                    "status": status_json(&processed.err),
+                    "confirmationStatus": "finalized",
+                }),
+                None => Value::Null,
+            })
+            .collect();
+        drop(transactions);
+        Ok(with_context(&self.environment(), json!(statuses)))
+    }
+
+    fn get_transaction(&self, params: &[Value]) -> Result<Value, RpcError> {
+        let signature = params.first()
+            .and_then(Value::as_str)
+            .ok_or_else(|| RpcError::invalid_params("Expected a signature".to_string()))?;
+        let encoding = params.get(1)
+            .and_then(|config| config.get("encoding"))
+            .and_then(Value::as_str)
+            .unwrap_or("json");
+        let transactions = self.transactions();
+        let processed = match transactions.get(signature) {
+            Some(processed) if !processed.wire.is_empty() => processed,
+            _ => return Ok(Value::Null),
+        };
+
+        let transaction = match encoding {
+            "base64" => json!([BASE64_STANDARD.encode(&processed.wire), "base64"]),
+            "base58" => json!([bs58::encode(&processed.wire).into_string(), "base58"]),
+            _ => transaction_json(&processed.wire, &processed.message),
+        };
+        let mut meta = json!({
+            "err": error_json(&processed.err),
+            "status": status_json(&processed.err),
+            "fee": processed.fee,
+            "preBalances": processed.pre_balances,
+            "postBalances": processed.post_balances,
+            "innerInstructions": [],
+            "logMessages": processed.logs,
+            "preTokenBalances": [],
+            "postTokenBalances": [],
+            "rewards": [],
+            "loadedAddresses": {
+                "writable": processed.loaded.0.iter().map(|key| key.to_string()).collect::<Vec<String>>(),
+                "readonly": processed.loaded.1.iter().map(|key| key.to_string()).collect::<Vec<String>>(),
+            },
+            "computeUnitsConsumed": 0,
+        });
+        if let Some((program_id, data)) = &processed.return_data {
+            meta["returnData"] = json!({ "programId": program_id.to_string(), "data": [BASE64_STANDARD.encode(data), "base64"] });
+        }
+        let version = match processed.message {
+            VersionedMessage::Legacy(_) => json!("legacy"),
+            VersionedMessage::V0(_) => json!(0),
+        };
+        Ok(json!({
+            "slot": processed.slot,
+            "blockTime": processed.block_time,
+            "version": version,
+            "transaction": transaction,
+            "meta": meta,
+        }))
+    }
+}
+
+
+// ++++++ Accounts +++++
+
+// Encoding and data slice asked for, without an encoding data is a bare base58 string
+struct AccountConfig {
+    encoding: Option<String>,
+    slice: Option<(usize, usize)>,
+}
+
+impl AccountConfig {
+    fn parse(config: Option<&Value>) -> Result<Self, RpcError> {
+        let encoding = config.and_then(|config| config.get("encoding")).and_then(Value::as_str).map(str::to_string);
+        let slice = match config.and_then(|config| config.get("dataSlice")) {
+            None | Some(Value::Null) => None,
+            Some(slice) => Some((
+                slice.get("offset").and_then(Value::as_u64).ok_or_else(|| RpcError::invalid_params("Invalid dataSlice".to_string()))? as usize,
+                slice.get("length").and_then(Value::as_u64).ok_or_else(|| RpcError::invalid_params("Invalid dataSlice".to_string()))? as usize,
+            )),
+        };
+        Ok(Self { encoding, slice })
+    }
+
+    fn encode(&self, account: &Account, executable: bool) -> Value {
+        let data = match self.slice {
+            Some((offset, length)) => {
+                let start = offset.min(account.data.len());
+                &account.data[start..start.saturating_add(length).min(account.data.len())]
+            }
+            None => account.data.as_slice(),
+        };
+        let data = match self.encoding.as_deref() {
+            Some("base58") => json!([bs58::encode(data).into_string(), "base58"]),
+            Some(_) => json!([BASE64_STANDARD.encode(data), "base64"]),
+            None => json!(bs58::encode(data).into_string()),
+        };
+        json!({
+            "lamports": account.lamports,
+            "owner": account.owner.to_string(),
+            "data": data,
+            "executable": executable,
+            "rentEpoch": u64::MAX,
+            "space": account.data.len(),
+        })
+    }
+}
+
+fn parse_filters(query: AccountQuery, filters: &Value) -> Result<AccountQuery, RpcError> {
+    let invalid = |filter: &Value| RpcError::invalid_params(format!("Invalid filter {}", filter));
+    filters.as_array()
+        .ok_or_else(|| invalid(filters))?
+        .iter()
+        .try_fold(query, |query, filter| {
+            if let Some(size) = filter.get("dataSize").and_then(Value::as_u64) {
+                return Ok(query.with_data_size(size as usize));
+            }
+            let memcmp = filter.get("memcmp").ok_or_else(|| invalid(filter))?;
+            let offset = memcmp.get("offset").and_then(Value::as_u64).ok_or_else(|| invalid(filter))? as usize;
+            let encoded = memcmp.get("bytes").and_then(Value::as_str).ok_or_else(|| invalid(filter))?;
+            let bytes = match memcmp.get("encoding").and_then(Value::as_str).unwrap_or("base58") {
+                "base58" => bs58::decode(encoded).into_vec().map_err(|_| invalid(filter))?,
+                "base64" => BASE64_STANDARD.decode(encoded).map_err(|_| invalid(filter))?,
+                _ => return Err(invalid(filter)),
+            };
+            Ok(query.with_memcmp(offset, &bytes))
+        })
+}
+
+// Programs are read as executable accounts, they aren't in the account store
+fn load_account(env: &mut Environment, pubkey: &Pubkey) -> Option<(Account, bool)> {
+    match env.is_program(pubkey) {
+        true => env.program_account(pubkey).map(|account| (account, true)),
+        false => env.get_account(pubkey).map(|account| (account.clone(), false)),
+    }
+}
+
+fn with_context(env: &Environment, value: Value) -> Value {
+    json!({ "context": { "slot": env.settings().slot }, "value": value })
+}
+
+
+// ++++++ Transactions +++++
+
+fn decode_transaction(params: &[Value], config: &Value) -> Result<Vec<u8>, RpcError> {
+    let encoded = params.first()
+        .and_then(Value::as_str)
+        .ok_or_else(|| RpcError::invalid_params("Expected an encoded transaction".to_string()))?;
+    match config.get("encoding").and_then(Value::as_str).unwrap_or("base58") {
+        "base58" => bs58::decode(encoded).into_vec().map_err(|err| RpcError::invalid_params(format!("invalid base58 encoding: {}", err))),
+        "base64" => BASE64_STANDARD.decode(encoded).map_err(|err| RpcError::invalid_params(format!("invalid base64 encoding: {}", err))),
+        encoding => Err(RpcError::invalid_params(format!("unsupported encoding: {}", encoding))),
+    }
+}
+
+fn invalid_transaction(err: TransactionError) -> RpcError {
+    match err {
+        TransactionError::SignatureFailure => RpcError::new(-32003, "Transaction signature verification failure".to_string()),
+        err => RpcError::invalid_params(format!("invalid transaction: {:?}", err)),
+    }
+}
+
+// Writable addresses of every table come first, then the readonly ones
+fn loaded_addresses(env: &Environment, message: &VersionedMessage) -> (Vec<Pubkey>, Vec<Pubkey>) {
+    let (mut writable, mut readonly) = (Vec::new(), Vec::new());
+    for lookup in message.address_table_lookups().unwrap_or_default() {
+        let table = match env.get_account(&lookup.account_key) {
+            Some(account) if account.owner == address_lookup_table::ID => account,
+            _ => continue,
+        };
+        if let Ok(table) = AddressLookupTable::deserialize(&table.data) {
+            let address = |index: &u8| table.addresses.get(*index as usize).cloned();
+            writable.extend(lookup.writable_indexes.iter().filter_map(address));
+            readonly.extend(lookup.readonly_indexes.iter().filter_map(address));
+        }
+    }
+    (writable, readonly)
+}
+
+fn simulation_json(receipt: &Receipt, err: Option<&TransactionError>) -> Value {
+    let return_data = receipt.return_data.as_ref()
+        .map(|(program_id, data)| json!({ "programId": program_id.to_string(), "data": [BASE64_STANDARD.encode(data), "base64"] }))
+        .unwrap_or(Value::Null);
+    json!({
+        "err": err.map(transaction_error_json).unwrap_or(Value::Null),
+        "logs": cluster_logs(receipt),
+        "accounts": Value::Null,
+        "unitsConsumed": 0,
+        "returnData": return_data,
+    })
+}
+
+// The runtime's log lines as the cluster prints them, client log parsers expect that shape
+fn cluster_logs(receipt: &Receipt) -> Vec<String> {
+    let mut stack: Vec<String> = Vec::new();
+    receipt.log_messages.iter()
+        .filter_map(|message| {
+            let program = stack.last().cloned().unwrap_or_default();
+            let line = if let Some(program_id) = message.strip_prefix("Program invoked: ") {
+                stack.push(program_id.to_string());
+                format!("Program {} invoke [{}]", program_id, stack.len())
+            } else if let Some(text) = message.strip_prefix("Program logged: ") {
+                format!("Program log: {}", text.trim_matches('"'))
+            } else if let Some(units) = message.strip_prefix("Program consumed: ") {
+                // Dropped unless it reads exactly as the cluster's line
+                let (consumed, limit) = compute_units(units).filter(|_| !program.is_empty())?;
+                format!("Program {} consumed {} of {} compute units", program, consumed, limit)
+            } else if message == "Program returned success" {
+                stack.pop();
+                format!("Program {} success", program)
+            } else if let Some(err) = message.strip_prefix("Program returned error: ") {
+                stack.pop();
+                let err = err.trim_matches('"');
+                match logged_error(err) {
+                    Some(err) => format!("Program {} failed: {}", program, InstructionError::from(u64::from(err))),
+                    None => format!("Program {} failed: {}", program, err),
+                }
+            } else {
+                message.clone()
+            };
+            Some(line)
+        })
+        .collect()
+}
+
+// The error as the receipt logs it, its Debug form
+fn logged_error(text: &str) -> Option<ProgramError> {
+    if let Some(code) = text.strip_prefix("Custom(").and_then(|rest| rest.strip_suffix(')')) {
+        return code.parse().ok().map(ProgramError::Custom);
+    }
+    if let Some(message) = text.strip_prefix("BorshIoError(").and_then(|rest| rest.strip_suffix(')')) {
+        return Some(ProgramError::BorshIoError(message.trim_matches('"').to_string()));
+    }
+    // Builtin errors are numbered from 1 in the upper 32 bits
+    (1..64u64)
+        .map(|index| ProgramError::from(index << 32))
+        .find(|err| format!("{:?}", err) == text)
+}
+
+// "N of M compute units"
+fn compute_units(text: &str) -> Option<(u64, u64)> {
+    let (consumed, rest) = text.split_once(" of ")?;
+    let limit = rest.strip_suffix(" compute units")?;
+    Some((consumed.parse().ok()?, limit.parse().ok()?))
+}
+
+fn transaction_json(wire: &[u8], message: &VersionedMessage) -> Value {
+    let signatures: Vec<String> = Transaction::from_wire_unverified(wire)
+        .map(|transaction| transaction.signatures().iter().map(|signature| signature.to_string()).collect())
+        .unwrap_or_default();
+    let header = message.header();
+    let instructions: Vec<Value> = message.instructions().iter()
+        .map(|instruction| json!({
+            "programIdIndex": instruction.program_id_index,
+            "accounts": instruction.accounts,
+            "data": bs58::encode(&instruction.data).into_string(),
+            "stackHeight": Value::Null,
+        }))
+        .collect();
+    let mut message_json = json!({
+        "header": {
+            "numRequiredSignatures": header.num_required_signatures,
+            "numReadonlySignedAccounts": header.num_readonly_signed_accounts,
+            "numReadonlyUnsignedAccounts": header.num_readonly_unsigned_accounts,
+        },
+        "accountKeys": message.static_account_keys().iter().map(|key| key.to_string()).collect::<Vec<String>>(),
+        "recentBlockhash": message.recent_blockhash().to_string(),
+        "instructions": instructions,
+    });
+    if let Some(lookups) = message.address_table_lookups() {
+        message_json["addressTableLookups"] = Value::Array(lookups.iter()
+            .map(|lookup| json!({
+                "accountKey": lookup.account_key.to_string(),
+                "writableIndexes": lookup.writable_indexes,
+                "readonlyIndexes": lookup.readonly_indexes,
+            }))
+            .collect());
+    }
+    json!({ "signatures": signatures, "message": message_json })
+}
+
+// Serialized as the sdk's TransactionError is
+fn transaction_error_json(err: &TransactionError) -> Value {
+    match err {
+        TransactionError::InstructionError(index, err) => json!({ "InstructionError": [index, instruction_error_json(err)] }),
+        TransactionError::DuplicateInstruction(index) => json!({ "DuplicateInstruction": index }),
+        TransactionError::InsufficientFundsForRent { account_index } => json!({ "InsufficientFundsForRent": { "account_index": account_index } }),
+        err => json!(format!("{:?}", err)),
+    }
+}
+
+fn instruction_error_json(err: &ProgramError) -> Value {
+    serde_json::to_value(InstructionError::from(u64::from(err.clone()))).unwrap()
+}
+
+fn error_json(err: &Option<TransactionError>) -> Value {
+    err.as_ref().map(transaction_error_json).unwrap_or(Value::Null)
+}
+
+fn status_json(err: &Option<TransactionError>) -> Value {
+    match err {
+        Some(err) => json!({ "Err": transaction_error_json(err) }),
+        None => json!({ "Ok": Value::Null }),
+    }
+}
+
+
+// ++++++ Params +++++
+
+fn param_pubkey(params: &[Value], index: usize) -> Result<Pubkey, RpcError> {
+    params.get(index)
+        .ok_or_else(|| RpcError::invalid_params(format!("Missing parameter {}", index)))
+        .and_then(parse_pubkey)
+}
+
+fn param_u64(params: &[Value], index: usize) -> Result<u64, RpcError> {
+    params.get(index)
+        .and_then(Value::as_u64)
+        .ok_or_else(|| RpcError::invalid_params(format!("Expected an integer as parameter {}", index)))
+}
+
+fn parse_pubkey(value: &Value) -> Result<Pubkey, RpcError> {
+    value.as_str()
+        .and_then(|text| Pubkey::from_str(text).ok())
+        .ok_or_else(|| RpcError::invalid_params(format!("Invalid param: {}", value)))
+}
+
+fn error_response(id: Value, err: RpcError) -> Value {
+    json!({ "jsonrpc": "2.0", "error": err.to_json(), "id": id })
+}
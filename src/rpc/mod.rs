@@ -0,0 +1,4 @@
+mod methods;
+mod server;
+
+pub use server::RpcServer;
@@ -0,0 +1,135 @@
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use crate::rpc::methods::RpcState;
+use crate::runtime::{BanksClient, Environment};
+
+
+// Stands in for a validator's JSON-RPC port, requests run against the environment it serves
+pub struct RpcServer {
+    address: SocketAddr,
+    state: Arc<RpcState>,
+    running: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl RpcServer {
+    // On a free localhost port
+    pub fn start(env: Environment) -> io::Result<Self> {
+        Self::bind(env, "127.0.0.1:0")
+    }
+
+    pub fn bind<A: ToSocketAddrs>(env: Environment, address: A) -> io::Result<Self> {
+        let listener = TcpListener::bind(address)?;
+        let address = listener.local_addr()?;
+        let state = Arc::new(RpcState::new(Arc::new(Mutex::new(env))));
+        let running = Arc::new(AtomicBool::new(true));
+        let thread = {
+            let (state, running) = (state.clone(), running.clone());
+            thread::spawn(move || accept(listener, state, running))
+        };
+        Ok(Self { address, state, running, thread: Some(thread) })
+    }
+
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    pub fn url(&self) -> String {
+        format!("http://{}", self.address)
+    }
+
+    pub fn environment(&self) -> MutexGuard<'_, Environment> {
+        self.state.environment()
+    }
+
+    // Shares the served environment
+    pub fn banks_client(&self) -> BanksClient {
+        BanksClient::from_shared(self.state.env.clone())
+    }
+
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        if let Some(thread) = self.thread.take() {
+            self.running.store(false, Ordering::SeqCst);
+            let _ = TcpStream::connect(self.address); // Wakes the blocked accept
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for RpcServer {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+fn accept(listener: TcpListener, state: Arc<RpcState>, running: Arc<AtomicBool>) {
+    for stream in listener.incoming() {
+        if !running.load(Ordering::SeqCst) {
+            break;
+        }
+        if let Ok(stream) = stream {
+            let state = state.clone();
+            thread::spawn(move || serve(stream, &state));
+        }
+    }
+}
+
+// HTTP/1.1 with keep-alive, only JSON-RPC posts and CORS preflights are answered
+fn serve(stream: TcpStream, state: &RpcState) -> io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut writer = stream;
+    loop {
+        let mut request_line = String::new();
+        if reader.read_line(&mut request_line)? == 0 {
+            return Ok(());
+        }
+        let method = request_line.split_whitespace().next().unwrap_or_default().to_string();
+
+        let mut content_length = 0;
+        let mut close = request_line.trim_end().ends_with("HTTP/1.0");
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line)? == 0 {
+                return Ok(());
+            }
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(':') {
+                match name.trim().to_ascii_lowercase().as_str() {
+                    "content-length" => content_length = value.trim().parse().unwrap_or(0),
+                    "connection" => close = value.trim().eq_ignore_ascii_case("close"),
+                    _ => {}
+                }
+            }
+        }
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body)?;
+
+        let (status, body) = match method.as_str() {
+            "POST" => ("200 OK", state.handle(&body)),
+            "OPTIONS" => ("204 No Content", String::new()),
+            _ => ("405 Method Not Allowed", String::new()),
+        };
+        write!(
+            writer,
+            "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nAccess-Control-Allow-Origin: *\r\nAccess-Control-Allow-Methods: POST, OPTIONS\r\nAccess-Control-Allow-Headers: content-type\r\n{}\r\n{}",
+            status,
+            body.len(),
+            if close { "Connection: close\r\n" } else { "" },
+            body,
+        )?;
+        writer.flush()?;
+        if close {
+            return Ok(());
+        }
+    }
+}
@@ -1,73 +1,93 @@
-use std::ptr;
-use std::sync::atomic::{AtomicPtr};
 use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::pubkey::Pubkey;
-use crate::runtime::compare_arrays;
-use crate::runtime::meta::Meta;
+use crate::runtime::{compare_arrays, Labels};
+use crate::runtime::Meta;
 use crate::suit::rent_exempt_for_size;
 
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Account {
     pub pubkey: Pubkey,
-    pub lamports: Box<u64>,
-    pub owner: Box<Pubkey>,
-    pub data: Box<Vec<u8>>,
+    pub lamports: u64,
+    pub owner: Pubkey,
+    pub data: Vec<u8>,
+    pub(crate) label: Option<String>,
 }
 
 impl Account {
     pub fn new(pubkey: Pubkey, lamports: u64, owner: &Pubkey, data: Vec<u8>) -> Self {
         Self {
             pubkey,
-            lamports: Box::new(lamports),
-            owner: Box::new(owner.clone()),
-            data: Box::new(data)
+            lamports,
+            owner: *owner,
+            data,
+            label: None,
         }
     }
 
-    pub fn meta(&mut self, is_signer: bool, is_writable: bool) -> (Pubkey, Meta) {
-        (self.pubkey.clone(),
+    pub fn meta(&mut self, is_signer: bool, is_writable: bool) -> (Pubkey, Meta<'_>) {
+        (self.pubkey,
          Meta {
              is_signer,
              is_writable,
              executable: false,
-             lamports: AtomicPtr::new(self.lamports.as_mut() as *mut u64),
-             owner: AtomicPtr::new(self.owner.as_mut() as *mut Pubkey),
-             data: AtomicPtr::new(self.data.as_mut() as *mut Vec<u8>),
-             package: AtomicPtr::new(ptr::null_mut()),
+             lamports: &mut self.lamports,
+             owner: &mut self.owner,
+             data: &mut self.data,
+             package: None,
+             label: self.label.clone(),
          })
     }
 
     pub fn pubkey(&self) -> &Pubkey {
         &self.pubkey
     }
+
+    // Label rendered instead of the key in logs, diffs and failed expectations
+    pub fn with_label(mut self, label: &str) -> Self {
+        self.label = Some(label.to_string());
+        self
+    }
+
+    pub fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    pub(crate) fn labels(&self) -> Labels {
+        let mut labels = Labels::new();
+        if let Some(label) = &self.label {
+            labels.set(&self.pubkey, label);
+        }
+        labels
+    }
 }
 
 // ++++++++ Suit Methods +++++++
 impl Account {
     pub fn expect_balance(&self, balance: u64) {
-        if *self.lamports.as_ref() != balance {
-            println!("{} balance not matches {} != {}", self.pubkey.to_string(), *self.lamports.as_ref(), balance);
-            assert_eq!(*self.lamports.as_ref(), balance);
+        if self.lamports != balance {
+            println!("{} balance not matches {} != {}", self.labels().display_key(&self.pubkey), self.lamports, balance);
+            assert_eq!(self.lamports, balance);
         }
     }
 
     pub fn expect_owner(&self, owner: &Pubkey) {
-        if !owner.eq(self.owner.as_ref()) {
-            println!("{} owner not matches {} != {}", self.pubkey.to_string(), self.owner.to_string(), owner.to_string());
-            assert!(owner.eq(self.owner.as_ref()));
+        if !owner.eq(&self.owner) {
+            let labels = self.labels();
+            println!("{} owner not matches {} != {}", labels.display_key(&self.pubkey), labels.display_key(&self.owner), labels.display_key(owner));
+            assert!(owner.eq(&self.owner));
         }
     }
 
     pub fn expect_data<T: BorshSerialize>(&self, data: &T) {
         let mut bytes: Vec<u8> = Vec::new();
-        data.serialize(&mut bytes);
+        data.serialize(&mut bytes).expect("serialize");
         self.expect_bytes(&bytes);
     }
 
     pub fn expect_bytes(&self, bytes: &[u8]) {
         if !compare_arrays(self.data.as_slice(), bytes) {
-            println!("Data not match {:?} != {:?}", self.data.as_slice(), bytes);
+            println!("{} data not match {:?} != {:?}", self.labels().display_key(&self.pubkey), self.data.as_slice(), bytes);
             assert_eq!(self.data.as_slice(), bytes);
         }
     }
@@ -79,7 +99,7 @@ impl Account {
     }
 
     pub fn borsh_serialize<T: BorshSerialize>(&mut self, data: &T) {
-        data.serialize(self.data.as_mut())
+        data.serialize(&mut self.data)
             .expect("Failed to serialze data");
     }
 
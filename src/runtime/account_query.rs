@@ -0,0 +1,76 @@
+use solana_program::pubkey::Pubkey;
+use crate::runtime::Account;
+
+
+// The filters of getProgramAccounts
+#[derive(Clone, Debug, PartialEq)]
+pub enum AccountFilter {
+    DataSize(usize),
+    Memcmp { offset: usize, bytes: Vec<u8> },
+}
+
+impl AccountFilter {
+    pub fn matches(&self, data: &[u8]) -> bool {
+        match self {
+            AccountFilter::DataSize(size) => data.len() == *size,
+            AccountFilter::Memcmp { offset, bytes } => {
+                data.get(*offset..offset.saturating_add(bytes.len())) == Some(bytes.as_slice())
+            }
+        }
+    }
+}
+
+// Accounts to select from an AccountStore, all filters have to match
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AccountQuery {
+    pub(crate) owner: Option<Pubkey>,
+    pub(crate) filters: Vec<AccountFilter>,
+    pub(crate) data_slice: Option<(usize, usize)>,
+}
+
+impl AccountQuery {
+    // Every account
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn owned_by(owner: &Pubkey) -> Self {
+        Self { owner: Some(*owner), ..Self::default() }
+    }
+
+    pub fn with_data_size(self, size: usize) -> Self {
+        self.with_filter(AccountFilter::DataSize(size))
+    }
+
+    pub fn with_memcmp(self, offset: usize, bytes: &[u8]) -> Self {
+        self.with_filter(AccountFilter::Memcmp { offset, bytes: bytes.to_vec() })
+    }
+
+    pub fn with_filter(mut self, filter: AccountFilter) -> Self {
+        self.filters.push(filter);
+        self
+    }
+
+    // Results keep only these bytes, filters still see the whole data
+    pub fn with_data_slice(mut self, offset: usize, length: usize) -> Self {
+        self.data_slice = Some((offset, length));
+        self
+    }
+
+    pub fn matches(&self, account: &Account) -> bool {
+        self.owner.is_none_or(|owner| account.owner == owner)
+            && self.filters.iter().all(|filter| filter.matches(&account.data))
+    }
+
+    // Copy of a matched account, cut to the data slice
+    pub(crate) fn select(&self, account: &Account) -> Account {
+        let mut account = account.clone();
+        if let Some((offset, length)) = self.data_slice {
+            let start = offset.min(account.data.len());
+            let end = start.saturating_add(length).min(account.data.len());
+            account.data.truncate(end);
+            account.data.drain(..start);
+        }
+        account
+    }
+}
@@ -0,0 +1,98 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use solana_program::pubkey::Pubkey;
+use crate::runtime::{Account, AccountQuery};
+
+
+// Accounts shared between forks until written, a fork copies only what was touched since the last one
+#[derive(Clone, Default)]
+pub struct AccountStore {
+    base: Arc<BTreeMap<Pubkey, Arc<Account>>>,
+    changes: BTreeMap<Pubkey, Option<Arc<Account>>>, // None removes the base account
+}
+
+impl AccountStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, pubkey: &Pubkey) -> Option<&Account> {
+        match self.changes.get(pubkey) {
+            Some(change) => change.as_deref(),
+            None => self.base.get(pubkey).map(|account| account.as_ref()),
+        }
+    }
+
+    // Copies the account the first time it's written after a fork, data included however little of it changes
+    pub fn get_mut(&mut self, pubkey: &Pubkey) -> Option<&mut Account> {
+        if !self.changes.contains_key(pubkey) {
+            if Arc::strong_count(&self.base) == 1 {
+                return Arc::get_mut(&mut self.base).unwrap().get_mut(pubkey).map(Arc::make_mut);
+            }
+            let account = self.base.get(pubkey)?.clone();
+            self.changes.insert(*pubkey, Some(account));
+        }
+        self.changes.get_mut(pubkey)?.as_mut().map(Arc::make_mut)
+    }
+
+    pub fn contains(&self, pubkey: &Pubkey) -> bool {
+        self.get(pubkey).is_some()
+    }
+
+    pub fn insert(&mut self, account: Account) {
+        self.changes.insert(account.pubkey, Some(Arc::new(account)));
+    }
+
+    pub fn remove(&mut self, pubkey: &Pubkey) -> Option<Account> {
+        let removed = self.get(pubkey).cloned();
+        if self.base.contains_key(pubkey) {
+            self.changes.insert(*pubkey, None);
+        } else {
+            self.changes.remove(pubkey);
+        }
+        removed
+    }
+
+    // In key order
+    pub fn iter(&self) -> impl Iterator<Item = &Account> {
+        let mut accounts: BTreeMap<&Pubkey, &Account> = self.base.iter()
+            .filter(|(key, _)| !self.changes.contains_key(key))
+            .map(|(key, account)| (key, account.as_ref()))
+            .collect();
+        accounts.extend(self.changes.iter().filter_map(|(key, change)| change.as_deref().map(|account| (key, account))));
+        accounts.into_values()
+    }
+
+    // Matching accounts in key order, sliced as the query asks
+    pub fn query<'a>(&'a self, query: &'a AccountQuery) -> impl Iterator<Item = Account> + 'a {
+        self.iter()
+            .filter(|account| query.matches(account))
+            .map(|account| query.select(account))
+    }
+
+    pub fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // Accounts written since the last fork
+    pub fn touched(&self) -> usize {
+        self.changes.len()
+    }
+
+    // Folds the changes into the base when no other fork shares it, otherwise the fork takes them along
+    pub fn fork(&mut self) -> Self {
+        if let Some(base) = Arc::get_mut(&mut self.base) {
+            for (key, change) in std::mem::take(&mut self.changes) {
+                match change {
+                    Some(account) => base.insert(key, account),
+                    None => base.remove(&key),
+                };
+            }
+        }
+        self.clone()
+    }
+}
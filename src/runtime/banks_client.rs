@@ -0,0 +1,129 @@
+use std::fmt;
+use std::future::Future;
+use std::pin::pin;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::task::{Context, Poll, Wake};
+use std::thread::{self, Thread};
+use solana_program::{hash::Hash, pubkey::Pubkey, rent::Rent};
+use crate::runtime::{Environment, Transaction, TransactionError};
+
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum BanksClientError {
+    TransactionError(TransactionError),
+}
+
+impl BanksClientError {
+    // As `solana-program-test` tests unwrap it
+    pub fn unwrap(self) -> TransactionError {
+        match self {
+            BanksClientError::TransactionError(err) => err,
+        }
+    }
+}
+
+impl fmt::Display for BanksClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BanksClientError::TransactionError(err) => write!(f, "transaction error: {:?}", err),
+        }
+    }
+}
+
+impl std::error::Error for BanksClientError {}
+
+
+// Shaped as the sdk Account that `BanksClient::get_account` returns
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BanksAccount {
+    pub lamports: u64,
+    pub data: Vec<u8>,
+    pub owner: Pubkey,
+    pub executable: bool,
+    pub rent_epoch: u64,
+}
+
+// Same method shapes as `solana-program-test`'s BanksClient, futures complete on their first poll
+#[derive(Clone)]
+pub struct BanksClient {
+    env: Arc<Mutex<Environment>>,
+}
+
+impl BanksClient {
+    pub fn new(env: Environment) -> Self {
+        Self::from_shared(Arc::new(Mutex::new(env)))
+    }
+
+    pub(crate) fn from_shared(env: Arc<Mutex<Environment>>) -> Self {
+        Self { env }
+    }
+
+    // Setup between calls: accounts, programs, warps
+    pub fn environment(&self) -> MutexGuard<'_, Environment> {
+        self.env.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    pub async fn process_transaction(&mut self, transaction: Transaction) -> Result<(), BanksClientError> {
+        self.environment().execute(&transaction)
+            .transaction_result()
+            .map_err(BanksClientError::TransactionError)
+    }
+
+    // Stops at the first failure, as the sdk does
+    pub async fn process_transactions(&mut self, transactions: Vec<Transaction>) -> Result<(), BanksClientError> {
+        for transaction in transactions {
+            self.process_transaction(transaction).await?;
+        }
+        Ok(())
+    }
+
+    pub async fn get_account(&mut self, address: Pubkey) -> Result<Option<BanksAccount>, BanksClientError> {
+        let env = self.environment();
+        let executable = env.is_program(&address);
+        let account = match executable {
+            true => env.program_account(&address),
+            false => env.get_account(&address).cloned(),
+        };
+        Ok(account.map(|account| BanksAccount {
+            lamports: account.lamports,
+            data: account.data.to_vec(),
+            owner: account.owner,
+            executable,
+            rent_epoch: u64::MAX,
+        }))
+    }
+
+    pub async fn get_balance(&mut self, address: Pubkey) -> Result<u64, BanksClientError> {
+        Ok(self.get_account(address).await?.map(|account| account.lamports).unwrap_or(0))
+    }
+
+    pub async fn get_rent(&mut self) -> Result<Rent, BanksClientError> {
+        Ok(self.environment().settings().as_rent())
+    }
+
+    pub async fn get_latest_blockhash(&mut self) -> Result<Hash, BanksClientError> {
+        Ok(self.environment().settings().blockhash)
+    }
+}
+
+
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+// In-process executor for the client futures, any other executor runs them as well
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let waker = Arc::new(ThreadWaker(thread::current())).into();
+    let mut context = Context::from_waker(&waker);
+    loop {
+        match future.as_mut().poll(&mut context) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
@@ -1,35 +1,51 @@
 use solana_program::{
     clock::Clock,
-    rent::Rent
+    hash::{hashv, Hash},
+    rent::{Rent, DEFAULT_BURN_PERCENT, DEFAULT_EXEMPTION_THRESHOLD, DEFAULT_LAMPORTS_PER_BYTE_YEAR},
 };
 use chrono;
+use serde::{Deserialize, Serialize};
+use crate::runtime::MAX_TX_ACCOUNT_LOCKS;
 
 
+pub const DEFAULT_TARGET_LAMPORTS_PER_SIGNATURE: u64 = 5000;
+
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ClusterSettings {
     // ++++++ Rent +++++
     pub lamports_per_byte_year: u64,
     pub exemption_threshold: f64,
-    pub burn_percent: u8,
+    pub burn_percent: u8, // Also the share of transaction fees burnt
+    // ++++++ Fees +++++
+    pub lamports_per_signature: u64,
+    // ++++++ Ledger +++++
+    pub blockhash: Hash, // The only one wire transactions may reference
     // ++++++ Clock +++++
     pub slot: u64,
     pub epoch_start_timestamp: i64,
     pub epoch: u64,
     pub leader_schedule_epoch: u64,
     pub unix_timestamp: i64,
+    // ++++++ Limits +++++
+    pub transaction_account_lock_limit: usize, // Also caps the account infos passed to a CPI
 }
 
 impl ClusterSettings {
     pub fn new() -> Self {
         let time = chrono::Utc::now().timestamp();
         ClusterSettings {
-            lamports_per_byte_year: 1_000_000_000 / 100 * 365 / (1024 * 1024),
-            exemption_threshold: 2.0,
-            burn_percent: 50,
+            lamports_per_byte_year: DEFAULT_LAMPORTS_PER_BYTE_YEAR,
+            exemption_threshold: DEFAULT_EXEMPTION_THRESHOLD,
+            burn_percent: DEFAULT_BURN_PERCENT,
+            lamports_per_signature: DEFAULT_TARGET_LAMPORTS_PER_SIGNATURE,
+            blockhash: hashv(&[b"fulgurite"]),
             slot: 1,
             epoch_start_timestamp: time,
             epoch: 1,
             leader_schedule_epoch: 0,
             unix_timestamp: time,
+            transaction_account_lock_limit: MAX_TX_ACCOUNT_LOCKS,
         }
     }
 
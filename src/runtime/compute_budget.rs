@@ -0,0 +1,103 @@
+use solana_program::{
+    compute_budget::{self, ComputeBudgetInstruction},
+    instruction::Instruction,
+    program_error::ProgramError,
+};
+use crate::runtime::{TransactionError, HEAP_LENGTH};
+
+
+pub const DEFAULT_INSTRUCTION_COMPUTE_UNIT_LIMIT: u32 = 200_000;
+pub const MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+pub const MIN_HEAP_FRAME_BYTES: u32 = HEAP_LENGTH as u32;
+pub const MAX_HEAP_FRAME_BYTES: u32 = 256 * 1024;
+pub const MAX_LOADED_ACCOUNTS_DATA_SIZE_BYTES: u32 = 64 * 1024 * 1024;
+pub const MICRO_LAMPORTS_PER_LAMPORT: u64 = 1_000_000;
+
+// Transaction-wide limits, set by the Compute Budget instructions of the transaction
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ComputeBudget {
+    pub compute_unit_limit: u32,
+    pub compute_unit_price: u64, // Micro-lamports per compute unit
+    pub heap_size: u32,
+    pub loaded_accounts_data_size_limit: u32,
+}
+
+impl ComputeBudget {
+    pub fn new() -> Self {
+        Self {
+            compute_unit_limit: DEFAULT_INSTRUCTION_COMPUTE_UNIT_LIMIT,
+            compute_unit_price: 0,
+            heap_size: MIN_HEAP_FRAME_BYTES,
+            loaded_accounts_data_size_limit: MAX_LOADED_ACCOUNTS_DATA_SIZE_BYTES,
+        }
+    }
+
+    // Same checks and defaults as the runtime applies before executing a transaction
+    pub fn from_instructions(instructions: &[Instruction]) -> Result<Self, TransactionError> {
+        let mut unit_limit: Option<u32> = None;
+        let mut unit_price: Option<u64> = None;
+        let mut heap_size: Option<u32> = None;
+        let mut data_size_limit: Option<u32> = None;
+        let mut other_instructions: u32 = 0;
+
+        for (index, instruction) in instructions.iter().enumerate() {
+            let index = index as u8;
+            if !instruction.program_id.eq(&compute_budget::ID) {
+                other_instructions += 1;
+                continue;
+            }
+
+            let invalid = TransactionError::InstructionError(index, ProgramError::InvalidInstructionData);
+            let duplicate = TransactionError::DuplicateInstruction(index);
+            match ComputeBudgetInstruction::unpack(&instruction.data) {
+                Some(ComputeBudgetInstruction::RequestHeapFrame(bytes)) => {
+                    if heap_size.is_some() {
+                        return Err(duplicate);
+                    }
+                    if !(MIN_HEAP_FRAME_BYTES..=MAX_HEAP_FRAME_BYTES).contains(&bytes) || bytes % 1024 != 0 {
+                        return Err(invalid);
+                    }
+                    heap_size = Some(bytes);
+                }
+                Some(ComputeBudgetInstruction::SetComputeUnitLimit(units)) => {
+                    if unit_limit.replace(units).is_some() {
+                        return Err(duplicate);
+                    }
+                }
+                Some(ComputeBudgetInstruction::SetComputeUnitPrice(micro_lamports)) => {
+                    if unit_price.replace(micro_lamports).is_some() {
+                        return Err(duplicate);
+                    }
+                }
+                Some(ComputeBudgetInstruction::SetLoadedAccountsDataSizeLimit(bytes)) => {
+                    if data_size_limit.replace(bytes).is_some() {
+                        return Err(duplicate);
+                    }
+                }
+                Some(ComputeBudgetInstruction::Unused) | None => return Err(invalid),
+            }
+        }
+
+        if data_size_limit == Some(0) {
+            return Err(TransactionError::InvalidLoadedAccountsDataSizeLimit);
+        }
+
+        Ok(Self {
+            compute_unit_limit: unit_limit
+                .unwrap_or(other_instructions.saturating_mul(DEFAULT_INSTRUCTION_COMPUTE_UNIT_LIMIT))
+                .min(MAX_COMPUTE_UNIT_LIMIT),
+            compute_unit_price: unit_price.unwrap_or(0),
+            heap_size: heap_size.unwrap_or(MIN_HEAP_FRAME_BYTES),
+            loaded_accounts_data_size_limit: data_size_limit
+                .unwrap_or(MAX_LOADED_ACCOUNTS_DATA_SIZE_BYTES)
+                .min(MAX_LOADED_ACCOUNTS_DATA_SIZE_BYTES),
+        })
+    }
+
+    // Price times the requested limit, rounded up to whole lamports
+    pub fn priority_fee(&self) -> u64 {
+        let micro_lamports = self.compute_unit_price as u128 * self.compute_unit_limit as u128;
+        let lamports = micro_lamports.div_ceil(MICRO_LAMPORTS_PER_LAMPORT as u128);
+        lamports.min(u64::MAX as u128) as u64
+    }
+}
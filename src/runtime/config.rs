@@ -0,0 +1,67 @@
+use solana_program::pubkey::Pubkey;
+use crate::runtime::{ClusterSettings, FaultInjector, Labels, LayoutRegistry, Observer, Verbosity, HEAP_LENGTH};
+
+
+// Everything a single invocation runs with, Program::invoke_with_bytes uses the defaults
+pub struct RuntimeConfig {
+    pub settings: ClusterSettings,
+    pub faults: FaultInjector,
+    pub verbosity: Verbosity,
+    pub observers: Vec<Box<dyn Observer>>,
+    pub layouts: LayoutRegistry,
+    pub labels: Labels, // Those of the invoked accounts and programs are added
+    pub heap_size: Option<usize>, // Heap limit of each invocation, None leaves allocations untracked
+    pub fee_payer: Option<Pubkey>, // Charged before execution, None runs for free
+    pub transaction_limits: bool, // Holds direct invocations to the packet size and lock limits, transactions always are
+}
+
+impl RuntimeConfig {
+    pub fn new() -> Self {
+        Self {
+            settings: ClusterSettings::new(),
+            faults: FaultInjector::new(),
+            verbosity: Verbosity::from_env(),
+            observers: Vec::new(),
+            layouts: LayoutRegistry::new(),
+            labels: Labels::new(),
+            heap_size: None,
+            fee_payer: None,
+            transaction_limits: false,
+        }
+    }
+
+    pub fn with_verbosity(mut self, verbosity: Verbosity) -> Self {
+        self.verbosity = verbosity;
+        self
+    }
+
+    pub fn with_observer<O: Observer + 'static>(mut self, observer: O) -> Self {
+        self.observers.push(Box::new(observer));
+        self
+    }
+
+    pub fn with_label(mut self, pubkey: &Pubkey, label: &str) -> Self {
+        self.labels.set(pubkey, label);
+        self
+    }
+
+    // Requires TrackingAllocator as the global allocator of the test binary
+    pub fn with_heap_tracking(self) -> Self {
+        self.with_heap_size(HEAP_LENGTH)
+    }
+
+    pub fn with_heap_size(mut self, size: usize) -> Self {
+        self.heap_size = Some(size);
+        self
+    }
+
+    pub fn with_fee_payer(mut self, payer: &Pubkey) -> Self {
+        self.fee_payer = Some(*payer);
+        self
+    }
+
+    pub fn with_transaction_limits(mut self) -> Self {
+        self.transaction_limits = true;
+        self
+    }
+}
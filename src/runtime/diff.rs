@@ -0,0 +1,229 @@
+use std::fmt;
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use crate::runtime::{layout::split_fields, Labels, LayoutRegistry, Meta, RentState};
+
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DataRangeDiff {
+    pub offset: usize,
+    pub before: Vec<u8>,
+    pub after: Vec<u8>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FieldDiff {
+    pub name: String,
+    pub before: String,
+    pub after: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AccountDiff {
+    pub pubkey: Pubkey,
+    pub lamports: (u64, u64),
+    pub owner: (Pubkey, Pubkey),
+    pub data_len: (usize, usize),
+    pub data: Vec<DataRangeDiff>,
+    pub fields: Vec<FieldDiff>, // Filled when a layout is registered for the account
+}
+
+impl AccountDiff {
+    pub fn lamports_delta(&self) -> i128 {
+        self.lamports.1 as i128 - self.lamports.0 as i128
+    }
+
+    pub fn owner_changed(&self) -> bool {
+        self.owner.0 != self.owner.1
+    }
+
+    pub fn field(&self, name: &str) -> Option<&FieldDiff> {
+        self.fields.iter().find(|field| field.name.eq(name))
+    }
+}
+
+impl fmt::Display for AccountDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut text = String::new();
+        self.write_plain(&mut text)?;
+        write!(f, "{}", Labels::new().relabel(&text))
+    }
+}
+
+impl AccountDiff {
+    pub(crate) fn write_plain(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        writeln!(f, "Account {}", self.pubkey)?;
+        if self.lamports.0 != self.lamports.1 {
+            writeln!(f, "    lamports: {} -> {} ({:+})", self.lamports.0, self.lamports.1, self.lamports_delta())?;
+        }
+        if self.owner_changed() {
+            writeln!(f, "    owner: {} -> {}", self.owner.0, self.owner.1)?;
+        }
+        if self.data_len.0 != self.data_len.1 {
+            writeln!(f, "    data length: {} -> {}", self.data_len.0, self.data_len.1)?;
+        }
+        if self.fields.is_empty() {
+            for range in self.data.iter() {
+                writeln!(f, "    data[{}..{}]: {:?} -> {:?}",
+                    range.offset,
+                    range.offset + range.before.len().max(range.after.len()),
+                    range.before,
+                    range.after,
+                )?;
+            }
+        }
+        for field in self.fields.iter() {
+            writeln!(f, "    {}: {} -> {}", field.name, field.before, field.after)?;
+        }
+        Ok(())
+    }
+}
+
+
+struct AccountState {
+    pubkey: Pubkey,
+    is_writable: bool,
+    lamports: u64,
+    owner: Pubkey,
+    data: Vec<u8>,
+}
+
+// State of the invoked accounts taken before execution
+pub(crate) struct AccountSnapshot {
+    accounts: Vec<AccountState>,
+}
+
+impl AccountSnapshot {
+    pub(crate) fn capture(metas: &[(Pubkey, Meta)]) -> Self {
+        let mut accounts: Vec<AccountState> = Vec::with_capacity(metas.len());
+        for (pubkey, meta) in metas.iter() {
+            if meta.executable {
+                continue;
+            }
+            match accounts.iter_mut().find(|account| account.pubkey.eq(pubkey)) {
+                Some(account) => account.is_writable |= meta.is_writable,
+                None => accounts.push(AccountState {
+                    pubkey: *pubkey,
+                    is_writable: meta.is_writable,
+                    lamports: meta.get_lamports(),
+                    owner: *meta.get_owner(),
+                    data: meta.get_data().clone(),
+                }),
+            }
+        }
+        Self { accounts }
+    }
+
+    // First writable account left in a rent state it can't move to
+    pub(crate) fn rent_violation(&self, metas: &[(Pubkey, Meta)], rent: &Rent) -> Option<Pubkey> {
+        self.accounts.iter()
+            .filter(|account| account.is_writable)
+            .find(|account| {
+                let meta = match metas.iter().find(|(key, _)| key.eq(&account.pubkey)) {
+                    Some((_, meta)) => meta,
+                    None => return false,
+                };
+                let pre = RentState::from_balance(account.lamports, account.data.len(), rent);
+                let post = RentState::from_balance(meta.get_lamports(), meta.get_data().len(), rent);
+                !post.transition_allowed_from(&pre)
+            })
+            .map(|account| account.pubkey)
+    }
+
+    // Undoes every change made since the capture, as a failed transaction does
+    pub(crate) fn restore(&self, metas: &mut [(Pubkey, Meta)]) {
+        for account in self.accounts.iter() {
+            if let Some((_, meta)) = metas.iter_mut().find(|(key, _)| key.eq(&account.pubkey)) {
+                meta.set_lamports(account.lamports);
+                meta.set_owner(&account.owner);
+                meta.set_data(account.data.clone());
+            }
+        }
+    }
+
+    pub(crate) fn diff(&self, metas: &[(Pubkey, Meta)], layouts: &LayoutRegistry) -> Vec<AccountDiff> {
+        let mut diffs = Vec::new();
+        for AccountState { pubkey, lamports, owner, data, .. } in self.accounts.iter() {
+            let meta = match metas.iter().find(|(key, _)| key.eq(pubkey)) {
+                Some((_, meta)) => meta,
+                None => continue,
+            };
+            let new_data = meta.get_data();
+            let diff = AccountDiff {
+                pubkey: *pubkey,
+                lamports: (*lamports, meta.get_lamports()),
+                owner: (*owner, *meta.get_owner()),
+                data_len: (data.len(), new_data.len()),
+                data: diff_bytes(data, new_data),
+                fields: layouts.decoder(pubkey, meta.get_owner())
+                    .map(|decoder| diff_fields(decoder(data), decoder(new_data)))
+                    .unwrap_or_default(),
+            };
+
+            if diff.lamports.0 != diff.lamports.1 || diff.owner_changed() || !diff.data.is_empty() {
+                diffs.push(diff);
+            }
+        }
+        diffs
+    }
+}
+
+// Contiguous ranges of changed bytes, a length change is reported as a trailing range
+fn diff_bytes(before: &[u8], after: &[u8]) -> Vec<DataRangeDiff> {
+    let mut ranges: Vec<DataRangeDiff> = Vec::new();
+    let common = before.len().min(after.len());
+    let mut start: Option<usize> = None;
+
+    for i in 0..=common {
+        let changed = i < common && before[i] != after[i];
+        match (changed, start) {
+            (true, None) => start = Some(i),
+            (false, Some(offset)) => {
+                ranges.push(DataRangeDiff {
+                    offset,
+                    before: Vec::from(&before[offset..i]),
+                    after: Vec::from(&after[offset..i]),
+                });
+                start = None;
+            }
+            _ => {}
+        }
+    }
+
+    if before.len() != after.len() {
+        ranges.push(DataRangeDiff {
+            offset: common,
+            before: Vec::from(&before[common..]),
+            after: Vec::from(&after[common..]),
+        });
+    }
+    ranges
+}
+
+fn diff_fields(before: Option<String>, after: Option<String>) -> Vec<FieldDiff> {
+    let undecoded = || "<undecodable>".to_string();
+    let (before, after) = match (before, after) {
+        (Some(before), Some(after)) => (before, after),
+        (before, after) => {
+            return vec![FieldDiff {
+                name: "data".to_string(),
+                before: before.unwrap_or_else(undecoded),
+                after: after.unwrap_or_else(undecoded),
+            }];
+        }
+    };
+
+    let before_fields = split_fields(&before);
+    let after_fields = split_fields(&after);
+    if before_fields.is_empty() || before_fields.len() != after_fields.len() {
+        if before.eq(&after) {
+            return Vec::new();
+        }
+        return vec![FieldDiff { name: "data".to_string(), before, after }];
+    }
+
+    before_fields.into_iter().zip(after_fields)
+        .filter(|((_, before), (_, after))| before != after)
+        .map(|((name, before), (_, after))| FieldDiff { name, before, after })
+        .collect()
+}
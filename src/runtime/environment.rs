@@ -0,0 +1,427 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+use borsh::{BorshDeserialize, BorshSerialize};
+use serde::{Deserialize, Serialize};
+use solana_program::{
+    address_lookup_table::{self, state::AddressLookupTable},
+    bpf_loader_upgradeable::{self, get_program_data_address, UpgradeableLoaderState},
+    compute_budget,
+    message::VersionedMessage,
+    program_pack::Pack,
+    pubkey::Pubkey,
+};
+use crate::executor::{package_handle, Package, PackageRegistry};
+use crate::runtime::{Account, AccountQuery, AccountStore, ClusterSettings, Labels, Meta, Program, Receipt, RuntimeConfig, Transaction};
+use crate::suit::{KeyedAccount, StructWrapper, TypedAccount, ASSOCIATED_PROGRAM_ID, SPL_PROGRAM_ID, SYSTEM_PROGRAM_ID};
+use crate::suit::typed_account::borshed_wrapper::BorshedWrapper;
+use crate::suit::typed_account::packed_wrapper::PackedWrapper;
+
+
+#[derive(Debug)]
+pub enum EnvironmentError {
+    Io { path: PathBuf, message: String },
+    Parse { message: String },
+    MissingProgram(Pubkey),
+}
+
+impl fmt::Display for EnvironmentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EnvironmentError::Io { path, message } => write!(f, "Failed to access {}: {}", path.display(), message),
+            EnvironmentError::Parse { message } => write!(f, "Invalid environment state: {}", message),
+            EnvironmentError::MissingProgram(pubkey) => write!(f, "Program {} is not registered", pubkey),
+        }
+    }
+}
+
+impl std::error::Error for EnvironmentError {}
+
+
+// How a program is built for execution, upgradeable ones run whatever their program data holds
+#[derive(Clone)]
+enum Registration {
+    Package(Arc<dyn Package>),
+    Upgradeable,
+}
+
+// Saved state, programs are referenced by id and supplied again on restore
+#[derive(Serialize, Deserialize)]
+struct EnvironmentState {
+    settings: ClusterSettings,
+    accounts: Vec<KeyedAccount>,
+    programs: Vec<String>,
+    labels: BTreeMap<String, String>,
+}
+
+// Accounts, programs and cluster settings owned together, transactions run against them by key
+pub struct Environment {
+    pub(crate) accounts: AccountStore,
+    registrations: BTreeMap<Pubkey, Registration>,
+    pub(crate) packages: PackageRegistry,
+    pub(crate) labels: Labels,
+    pub(crate) settings: ClusterSettings,
+}
+
+impl Environment {
+    pub fn new() -> Self {
+        Self {
+            accounts: AccountStore::new(),
+            registrations: BTreeMap::new(),
+            packages: PackageRegistry::new(),
+            labels: Labels::new(),
+            settings: ClusterSettings::new(),
+        }
+    }
+
+    // ++++++ Accounts +++++
+
+    pub fn add_account(&mut self, account: Account) {
+        if let Some(label) = account.label() {
+            self.labels.set(&account.pubkey, label);
+        }
+        self.accounts.insert(account);
+    }
+
+    pub fn add_typed_account<T: StructWrapper>(&mut self, account: TypedAccount<T>) {
+        self.add_account(account.into_account());
+    }
+
+    pub fn remove_account(&mut self, pubkey: &Pubkey) -> Option<Account> {
+        self.accounts.remove(pubkey)
+    }
+
+    pub fn get_account(&self, pubkey: &Pubkey) -> Option<&Account> {
+        self.accounts.get(pubkey)
+    }
+
+    pub fn get_account_mut(&mut self, pubkey: &Pubkey) -> Option<&mut Account> {
+        self.accounts.get_mut(pubkey)
+    }
+
+    pub fn accounts(&self) -> impl Iterator<Item = &Account> {
+        self.accounts.iter()
+    }
+
+    // Copies, changes to them are not written back
+    pub fn get_borshed<B: BorshSerialize + BorshDeserialize>(&self, pubkey: &Pubkey) -> Option<TypedAccount<BorshedWrapper<B>>> {
+        TypedAccount::from_account_borshed(self.accounts.get(pubkey)?.clone())
+    }
+
+    pub fn get_packed<P: Pack>(&self, pubkey: &Pubkey) -> Option<TypedAccount<PackedWrapper<P>>> {
+        TypedAccount::from_account_packed(self.accounts.get(pubkey)?.clone())
+    }
+
+    // Copies of the matching accounts, as getProgramAccounts returns them
+    pub fn query(&self, query: &AccountQuery) -> Vec<Account> {
+        self.accounts.query(query).collect()
+    }
+
+    // Matches that don't deserialize are left out
+    pub fn query_borshed<B: BorshSerialize + BorshDeserialize>(&self, query: &AccountQuery) -> Vec<TypedAccount<BorshedWrapper<B>>> {
+        self.accounts.query(query).filter_map(TypedAccount::from_account_borshed).collect()
+    }
+
+    pub fn query_packed<P: Pack>(&self, query: &AccountQuery) -> Vec<TypedAccount<PackedWrapper<P>>> {
+        self.accounts.query(query).filter_map(TypedAccount::from_account_packed).collect()
+    }
+
+    // ++++++ Programs +++++
+
+    pub fn add_program(&mut self, program: Program) {
+        if let Some(label) = &program.label {
+            self.labels.set(&program.pubkey, label);
+        }
+        self.registrations.insert(program.pubkey, Registration::Package(program.package));
+    }
+
+    // Deploys the package behind a program data account, as `solana program deploy` does
+    pub fn add_upgradeable_program(&mut self, package: Box<dyn Package>, pubkey: Pubkey, upgrade_authority: Option<&Pubkey>) {
+        let handle = self.packages.register(Arc::from(package));
+        self.add_account(Account::new_program_data(&pubkey, handle, upgrade_authority));
+        self.registrations.insert(pubkey, Registration::Upgradeable);
+    }
+
+    // Buffer holding a package for the Upgrade instruction
+    pub fn add_program_buffer(&mut self, pubkey: Pubkey, package: Box<dyn Package>, authority: &Pubkey) {
+        let handle = self.packages.register(Arc::from(package));
+        self.add_account(Account::new_program_buffer(pubkey, handle, authority));
+    }
+
+    pub fn program_ids(&self) -> impl Iterator<Item = &Pubkey> {
+        self.registrations.keys()
+    }
+
+    pub fn is_program(&self, pubkey: &Pubkey) -> bool {
+        self.registrations.contains_key(pubkey)
+    }
+
+    // The program account as clients read it, not kept in the account store
+    pub(crate) fn program_account(&self, pubkey: &Pubkey) -> Option<Account> {
+        let program = self.build_program(*pubkey)?;
+        Some(Account::new(*pubkey, program.proxy_lamports, &program.owner, program.proxy_data.to_vec()))
+    }
+
+    // Built for each execution, upgradeable ones load the package their program data holds
+    fn build_program(&self, pubkey: Pubkey) -> Option<Program> {
+        match self.registrations.get(&pubkey)? {
+            Registration::Package(package) => Some(Program::with_package(Box::new(package.clone()), pubkey)),
+            Registration::Upgradeable => {
+                let programdata = self.accounts.get(&get_program_data_address(&pubkey))
+                    .unwrap_or_else(|| panic!("Program data account of {} is missing", pubkey));
+                Some(Program::upgradeable_from(pubkey, programdata, &self.packages))
+            }
+        }
+    }
+
+    // Packages of closed programs and consumed buffers are released
+    fn prune_packages(&mut self) {
+        let handles: Vec<u64> = self.accounts.iter()
+            .filter(|account| account.owner == bpf_loader_upgradeable::ID)
+            .filter_map(|account| UpgradeableLoaderState::program_bytes(&account.data).and_then(package_handle))
+            .collect();
+        self.packages.retain(&handles);
+    }
+
+    // ++++++ Labels +++++
+
+    // Rendered instead of the key in the receipts of this environment
+    pub fn set_label(&mut self, pubkey: &Pubkey, label: &str) {
+        self.labels.set(pubkey, label);
+    }
+
+    pub fn label_of(&self, pubkey: &Pubkey) -> Option<&str> {
+        self.labels.get(pubkey)
+    }
+
+    pub fn labels(&self) -> &Labels {
+        &self.labels
+    }
+
+    // ++++++ Cluster +++++
+
+    pub fn settings(&self) -> &ClusterSettings {
+        &self.settings
+    }
+
+    pub fn settings_mut(&mut self) -> &mut ClusterSettings {
+        &mut self.settings
+    }
+
+    pub fn warp_to_slot(&mut self, slot: u64) {
+        self.settings.slot = slot;
+    }
+
+    // ++++++ Execution +++++
+
+    pub fn execute(&mut self, transaction: &Transaction) -> Receipt {
+        self.execute_with_config(transaction, RuntimeConfig::new())
+    }
+
+    // Accounts get the privileges the transaction asks for, unknown ones load empty as on chain
+    pub fn execute_with_config(&mut self, transaction: &Transaction, mut config: RuntimeConfig) -> Receipt {
+        config.settings = self.settings.clone();
+        let mut labels = self.labels.clone();
+        labels.extend(&config.labels);
+        config.labels = labels;
+        let keys = self.requested_keys(transaction, transaction.payer.or(config.fee_payer));
+
+        let created: Vec<Pubkey> = keys.iter()
+            .map(|(key, _, _)| *key)
+            .filter(|key| !self.accounts.contains(key) && !self.registrations.contains_key(key))
+            .collect();
+        for key in created.iter() {
+            self.accounts.insert(Account::new(*key, 0, &SYSTEM_PROGRAM_ID, Vec::new()));
+        }
+
+        // Runs on copies of the accounts, the store takes back only the ones that changed
+        let mut programs: Vec<(Program, bool)> = keys.iter()
+            .filter_map(|(key, _, is_writable)| Some((self.build_program(*key)?, *is_writable)))
+            .collect();
+        let mut accounts: Vec<(Account, bool, bool)> = keys.iter()
+            .filter(|(key, _, _)| !self.registrations.contains_key(key))
+            .map(|(key, is_signer, is_writable)| (self.accounts.get(key).unwrap().clone(), *is_signer, *is_writable))
+            .collect();
+        let mut metas: Vec<(Pubkey, Meta)> = programs.iter_mut()
+            .map(|(program, is_writable)| program.meta_with_writable(*is_writable))
+            .collect();
+        metas.extend(accounts.iter_mut().map(|(account, is_signer, is_writable)| account.meta(*is_signer, *is_writable)));
+        let receipt = transaction.execute_with_config(metas, config);
+        drop(programs);
+
+        for (account, _, _) in accounts.into_iter() {
+            let pubkey = account.pubkey;
+            let stored = self.accounts.get(&pubkey).unwrap();
+            if stored.lamports != account.lamports || stored.owner != account.owner || stored.data != account.data {
+                *self.accounts.get_mut(&pubkey).unwrap() = account;
+            }
+        }
+
+        for key in created.iter() {
+            let account = self.accounts.get(key).unwrap();
+            if account.lamports == 0 && account.data.is_empty() && account.owner == SYSTEM_PROGRAM_ID {
+                self.accounts.remove(key);
+            }
+        }
+        if keys.iter().any(|(key, _, _)| *key == bpf_loader_upgradeable::ID) {
+            self.prune_packages();
+        }
+        receipt
+    }
+
+    fn requested_keys(&self, transaction: &Transaction, payer: Option<Pubkey>) -> Vec<(Pubkey, bool, bool)> {
+        let mut keys: Vec<(Pubkey, bool, bool)> = Vec::new();
+        let mut add = |key: Pubkey, is_signer: bool, is_writable: bool| {
+            match keys.iter_mut().find(|(other, _, _)| other.eq(&key)) {
+                Some(entry) => {
+                    entry.1 |= is_signer;
+                    entry.2 |= is_writable;
+                }
+                None => keys.push((key, is_signer, is_writable)),
+            }
+        };
+        if let Some(payer) = payer {
+            add(payer, true, true);
+        }
+        match &transaction.message {
+            None => {
+                for instruction in transaction.instructions.iter() {
+                    add(instruction.program_id, false, false);
+                    for meta in instruction.accounts.iter() {
+                        add(meta.pubkey, meta.is_signer, meta.is_writable);
+                    }
+                }
+            }
+            // Privileges come from the message itself, only the keys matter
+            Some(message) => {
+                for key in message.static_account_keys() {
+                    add(*key, false, false);
+                }
+                if let VersionedMessage::V0(message) = message {
+                    for lookup in message.address_table_lookups.iter() {
+                        add(lookup.account_key, false, false);
+                        let table = match self.accounts.get(&lookup.account_key) {
+                            Some(account) if account.owner == address_lookup_table::ID => account,
+                            _ => continue,
+                        };
+                        if let Ok(table) = AddressLookupTable::deserialize(&table.data) {
+                            for index in lookup.writable_indexes.iter().chain(lookup.readonly_indexes.iter()) {
+                                if let Some(address) = table.addresses.get(*index as usize) {
+                                    add(*address, false, false);
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        keys
+    }
+
+    // ++++++ Fork & persistence +++++
+
+    // Accounts are shared until written, programs share their packages
+    pub fn fork(&mut self) -> Self {
+        Self {
+            accounts: self.accounts.fork(),
+            registrations: self.registrations.clone(),
+            packages: self.packages.clone(),
+            labels: self.labels.clone(),
+            settings: self.settings.clone(),
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        let labels = self.accounts.iter().map(|account| &account.pubkey)
+            .chain(self.registrations.keys())
+            .filter_map(|key| self.labels.get(key).map(|label| (key.to_string(), label.to_string())))
+            .collect();
+        let state = EnvironmentState {
+            settings: self.settings.clone(),
+            accounts: self.accounts.iter().map(KeyedAccount::from_account).collect(),
+            programs: self.registrations.keys().map(|key| key.to_string()).collect(),
+            labels,
+        };
+        serde_json::to_string_pretty(&state).unwrap()
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), EnvironmentError> {
+        let path = path.as_ref();
+        fs::write(path, self.to_json()).map_err(|err| io_error(path, err))
+    }
+
+    // Built-in programs come back on their own, the others must be supplied,
+    // those of upgradeable programs are bound to the handle their program data holds
+    pub fn from_json(text: &str, programs: Vec<Program>) -> Result<Self, EnvironmentError> {
+        let state: EnvironmentState = serde_json::from_str(text).map_err(|err| parse_error(err.to_string()))?;
+        let mut env = Self::new();
+        env.settings = state.settings;
+        for keyed in state.accounts.iter() {
+            env.add_account(keyed.to_account().map_err(|err| parse_error(err.to_string()))?);
+        }
+        for program in programs {
+            match env.deployed_handle(&program.pubkey) {
+                Some(handle) => {
+                    env.packages.bind(handle, program.package);
+                    env.registrations.insert(program.pubkey, Registration::Upgradeable);
+                }
+                None => env.add_program(program),
+            }
+        }
+        for program_id in state.programs.iter() {
+            let pubkey = parse_pubkey(program_id)?;
+            if env.registrations.contains_key(&pubkey) {
+                continue;
+            }
+            match built_in_program(&pubkey) {
+                Some(program) => env.add_program(program),
+                None => return Err(EnvironmentError::MissingProgram(pubkey)),
+            }
+        }
+        for (key, label) in state.labels.iter() {
+            env.labels.set(&parse_pubkey(key)?, label);
+        }
+        Ok(env)
+    }
+
+    pub fn restore<P: AsRef<Path>>(path: P, programs: Vec<Program>) -> Result<Self, EnvironmentError> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|err| io_error(path, err))?;
+        Self::from_json(&text, programs)
+    }
+
+    fn deployed_handle(&self, pubkey: &Pubkey) -> Option<u64> {
+        let programdata = self.accounts.get(&get_program_data_address(pubkey))?;
+        if programdata.owner != bpf_loader_upgradeable::ID {
+            return None;
+        }
+        UpgradeableLoaderState::program_bytes(&programdata.data).and_then(package_handle)
+    }
+}
+
+fn built_in_program(pubkey: &Pubkey) -> Option<Program> {
+    match *pubkey {
+        key if key == SYSTEM_PROGRAM_ID => Some(Program::system_program()),
+        key if key == compute_budget::ID => Some(Program::compute_budget_program()),
+        key if key == address_lookup_table::ID => Some(Program::address_lookup_table_program()),
+        key if key == SPL_PROGRAM_ID => Some(Program::token_program()),
+        key if key == ASSOCIATED_PROGRAM_ID => Some(Program::associated_token_program()),
+        key if key == bpf_loader_upgradeable::ID => Some(Program::upgradeable_loader()),
+        _ => None,
+    }
+}
+
+fn parse_pubkey(text: &str) -> Result<Pubkey, EnvironmentError> {
+    Pubkey::from_str(text).map_err(|_| parse_error(format!("Invalid pubkey {}", text)))
+}
+
+fn parse_error(message: String) -> EnvironmentError {
+    EnvironmentError::Parse { message }
+}
+
+fn io_error(path: &Path, err: std::io::Error) -> EnvironmentError {
+    EnvironmentError::Io { path: path.to_path_buf(), message: err.to_string() }
+}
@@ -0,0 +1,79 @@
+use std::fmt::Debug;
+use borsh::BorshDeserialize;
+use solana_program::{hash::hashv, pubkey::Pubkey};
+use crate::runtime::Receipt;
+
+
+pub const ANCHOR_DISCRIMINATOR_LEN: usize = 8;
+
+// Discriminator Anchor prefixes to events declared with #[event]
+pub fn anchor_event_discriminator(name: &str) -> [u8; ANCHOR_DISCRIMINATOR_LEN] {
+    let hash = hashv(&[format!("event:{}", name).as_bytes()]);
+    hash.to_bytes()[..ANCHOR_DISCRIMINATOR_LEN].try_into().unwrap()
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Event<T> {
+    pub program_id: Pubkey,
+    pub index: usize,           // Position among all "Program data" entries of the receipt
+    pub call_path: Vec<usize>,  // Invocation in the CPI tree, empty for the invoked program
+    pub value: T,
+}
+
+impl<T> Event<T> {
+    pub fn stack_height(&self) -> usize {
+        self.call_path.len() + 1
+    }
+}
+
+impl Receipt {
+    // Entries of the program whose concatenated slices decode as T with no bytes left
+    pub fn events<T: BorshDeserialize>(&self, program_id: &Pubkey) -> Vec<Event<T>> {
+        self.decode_events(program_id, |bytes| T::try_from_slice(bytes).ok())
+    }
+
+    // Entries of the program starting with the discriminator, the rest decoded as T
+    pub fn discriminated_events<T: BorshDeserialize>(&self, program_id: &Pubkey, discriminator: &[u8]) -> Vec<Event<T>> {
+        self.decode_events(program_id, |bytes| {
+            bytes.strip_prefix(discriminator).and_then(|body| T::try_from_slice(body).ok())
+        })
+    }
+
+    pub fn anchor_events<T: BorshDeserialize>(&self, program_id: &Pubkey, name: &str) -> Vec<Event<T>> {
+        self.discriminated_events(program_id, &anchor_event_discriminator(name))
+    }
+
+    pub fn expect_event<T: BorshDeserialize + PartialEq + Debug>(&self, program_id: &Pubkey, value: &T) {
+        let values: Vec<T> = self.events(program_id).into_iter().map(|event| event.value).collect();
+        if !values.contains(value) {
+            println!("{}", self);
+            println!("Event {:?} not emitted by {}, decoded {:?}", value, self.labels.display_key(program_id), values);
+            assert!(values.contains(value));
+        }
+    }
+
+    pub fn expect_anchor_event<T: BorshDeserialize + PartialEq + Debug>(&self, program_id: &Pubkey, name: &str, value: &T) {
+        let values: Vec<T> = self.anchor_events(program_id, name).into_iter().map(|event| event.value).collect();
+        if !values.contains(value) {
+            println!("{}", self);
+            println!("Event {} {:?} not emitted by {}, decoded {:?}", name, value, self.labels.display_key(program_id), values);
+            assert!(values.contains(value));
+        }
+    }
+
+    fn decode_events<T, F: Fn(&[u8]) -> Option<T>>(&self, program_id: &Pubkey, decode: F) -> Vec<Event<T>> {
+        self.log_datas.iter()
+            .zip(self.log_data_paths.iter())
+            .enumerate()
+            .filter(|(_, ((source, _), _))| source.eq(program_id))
+            .filter_map(|(index, ((source, data), call_path))| {
+                decode(&data.concat()).map(|value| Event {
+                    program_id: *source,
+                    index,
+                    call_path: call_path.clone(),
+                    value,
+                })
+            })
+            .collect()
+    }
+}
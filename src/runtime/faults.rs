@@ -0,0 +1,125 @@
+use std::collections::HashMap;
+use solana_program::{
+    instruction::Instruction,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+
+pub type FaultPredicate = dyn Fn(&[u8]) -> bool + Send + Sync;
+
+pub enum FaultTrigger {
+    Nth(usize), // 1-based count of CPIs to the program
+    Every,
+    Matching(Box<FaultPredicate>),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FaultMode {
+    FailBeforeExecution, // Callee is not run
+    FailAfterExecution,  // Callee runs, its result is replaced
+}
+
+pub struct Fault {
+    pub program_id: Pubkey,
+    pub trigger: FaultTrigger,
+    pub error: ProgramError,
+    pub mode: FaultMode,
+}
+
+impl Fault {
+    pub fn nth(program_id: &Pubkey, n: usize, error: ProgramError) -> Self {
+        Self {
+            program_id: *program_id,
+            trigger: FaultTrigger::Nth(n),
+            error,
+            mode: FaultMode::FailBeforeExecution,
+        }
+    }
+
+    pub fn every(program_id: &Pubkey, error: ProgramError) -> Self {
+        Self {
+            program_id: *program_id,
+            trigger: FaultTrigger::Every,
+            error,
+            mode: FaultMode::FailBeforeExecution,
+        }
+    }
+
+    pub fn matching<F>(program_id: &Pubkey, predicate: F, error: ProgramError) -> Self
+        where F: Fn(&[u8]) -> bool + Send + Sync + 'static
+    {
+        Self {
+            program_id: *program_id,
+            trigger: FaultTrigger::Matching(Box::new(predicate)),
+            error,
+            mode: FaultMode::FailBeforeExecution,
+        }
+    }
+
+    pub fn after_execution(mut self) -> Self {
+        self.mode = FaultMode::FailAfterExecution;
+        self
+    }
+
+    fn triggers(&self, instruction: &Instruction, call_index: usize) -> bool {
+        if !self.program_id.eq(&instruction.program_id) {
+            return false;
+        }
+
+        match &self.trigger {
+            FaultTrigger::Nth(n) => *n == call_index,
+            FaultTrigger::Every => true,
+            FaultTrigger::Matching(predicate) => predicate(instruction.data.as_slice()),
+        }
+    }
+}
+
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct InjectedFault {
+    pub program_id: Pubkey,
+    pub call_index: usize,
+    pub stack_height: usize,
+    pub error: ProgramError,
+    pub mode: FaultMode,
+}
+
+
+#[derive(Default)]
+pub struct FaultInjector {
+    faults: Vec<Fault>,
+    calls: HashMap<Pubkey, usize>,
+}
+
+impl FaultInjector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn inject(&mut self, fault: Fault) -> &mut Self {
+        self.faults.push(fault);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.faults.is_empty()
+    }
+
+    // Counts the CPI and returns the first fault it triggers
+    pub(crate) fn on_invoke(&mut self, instruction: &Instruction, stack_height: usize) -> Option<InjectedFault> {
+        let call_index = self.calls.entry(instruction.program_id).or_insert(0);
+        *call_index += 1;
+        let call_index = *call_index;
+
+        self.faults.iter()
+            .find(|fault| fault.triggers(instruction, call_index))
+            .map(|fault| InjectedFault {
+                program_id: instruction.program_id,
+                call_index,
+                stack_height,
+                error: fault.error.clone(),
+                mode: fault.mode,
+            })
+    }
+}
@@ -0,0 +1,61 @@
+use solana_program::{pubkey::Pubkey, system_program};
+use crate::runtime::{ClusterSettings, ComputeBudget, Meta, RentState, TransactionError};
+
+
+// Split as the cluster splits it, but only the payer's debit is applied: no account is credited
+// with the collected share, the whole fee leaves the supply
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FeeDetails {
+    pub payer: Pubkey,
+    pub signatures: u64,
+    pub signature_fee: u64,
+    pub priority_fee: u64,
+    pub burned: u64,
+    pub collected: u64, // The leader's share, reported only
+}
+
+impl FeeDetails {
+    pub fn total(&self) -> u64 {
+        self.signature_fee + self.priority_fee
+    }
+}
+
+// Debits the payer before execution, failed instructions don't refund it
+pub(crate) fn charge_fee(payer: &Pubkey, accounts: &mut [(Pubkey, Meta)], settings: &ClusterSettings, budget: &ComputeBudget) -> Result<FeeDetails, TransactionError> {
+    let account_index = accounts.iter()
+        .position(|(key, _)| key.eq(payer))
+        .ok_or(TransactionError::AccountNotFound)?;
+    let meta = &accounts[account_index].1;
+
+    if !meta.get_owner().eq(&system_program::ID) || !meta.get_data().is_empty() {
+        return Err(TransactionError::InvalidAccountForFee);
+    }
+
+    // The payer signs even when its meta doesn't say so
+    let signatures = 1 + accounts.iter().filter(|(key, meta)| meta.is_signer && !key.eq(payer)).count() as u64;
+    let signature_fee = signatures * settings.lamports_per_signature;
+    let priority_fee = budget.priority_fee();
+    let total = signature_fee.saturating_add(priority_fee);
+
+    let lamports = meta.get_lamports();
+    if lamports < total {
+        return Err(TransactionError::InsufficientFundsForFee);
+    }
+    let rent = settings.as_rent();
+    let pre = RentState::from_balance(lamports, 0, &rent);
+    let post = RentState::from_balance(lamports - total, 0, &rent);
+    if !post.transition_allowed_from(&pre) {
+        return Err(TransactionError::InsufficientFundsForRent { account_index: account_index as u8 });
+    }
+
+    accounts[account_index].1.set_lamports(lamports - total);
+    let burned = total * settings.burn_percent as u64 / 100;
+    Ok(FeeDetails {
+        payer: *payer,
+        signatures,
+        signature_fee,
+        priority_fee,
+        burned,
+        collected: total - burned,
+    })
+}
@@ -0,0 +1,138 @@
+use std::{mem, slice};
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::{ProgramResult, MAX_PERMITTED_DATA_INCREASE},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+use crate::runtime::Meta;
+
+
+// Account as a program is handed it, programs change copies and the caller takes the changes back
+#[derive(Clone, Debug)]
+pub(crate) struct FrameAccount {
+    pub(crate) pubkey: Pubkey,
+    pub(crate) lamports: u64,
+    pub(crate) owner: Pubkey,
+    pub(crate) data: Vec<u8>,
+    pub(crate) executable: bool,
+}
+
+impl FrameAccount {
+    pub(crate) fn from_meta(pubkey: &Pubkey, meta: &Meta) -> Self {
+        Self {
+            pubkey: *pubkey,
+            lamports: *meta.lamports,
+            owner: *meta.owner,
+            data: meta.data.clone(),
+            executable: meta.executable,
+        }
+    }
+
+    // Fails as on chain when the caller holds the account mutably borrowed
+    pub(crate) fn from_info(info: &AccountInfo) -> Result<Self, ProgramError> {
+        Ok(Self {
+            pubkey: *info.key,
+            lamports: info.try_lamports()?,
+            owner: info.owner.get(),
+            data: info.try_borrow_data()?.to_vec(),
+            executable: info.executable,
+        })
+    }
+
+    pub(crate) fn store(&self, meta: &mut Meta) {
+        *meta.lamports = self.lamports;
+        *meta.owner = self.owner;
+        meta.data.clone_from(&self.data);
+    }
+}
+
+// Accounts of one invocation, each info points at an account and carries its own privileges
+#[derive(Default)]
+pub(crate) struct Frame {
+    pub(crate) accounts: Vec<FrameAccount>,
+    pub(crate) privileges: Vec<(usize, bool, bool)>, // (account index, is_signer, is_writable)
+}
+
+impl Frame {
+    // An account passed twice is copied once, its infos share it as duplicates do on chain
+    pub(crate) fn add(&mut self, account: FrameAccount, is_signer: bool, is_writable: bool) {
+        let index = match self.accounts.iter().position(|other| other.pubkey.eq(&account.pubkey)) {
+            Some(index) => index,
+            None => {
+                self.accounts.push(account);
+                self.accounts.len() - 1
+            }
+        };
+        self.privileges.push((index, is_signer, is_writable));
+    }
+
+    pub(crate) fn store(&self, metas: &mut [(Pubkey, Meta)]) {
+        for account in self.accounts.iter() {
+            for (_, meta) in metas.iter_mut().filter(|(key, _)| key.eq(&account.pubkey)) {
+                account.store(meta);
+            }
+        }
+    }
+}
+
+
+// Data of an account while its frame runs. The infos get the one slice cut from the caller's bytes,
+// room is reserved past the data so it can grow in place as it does on chain
+pub(crate) struct DataBuffer {
+    grown: Option<Vec<u8>>, // Allocated once the data outgrows the reserved room
+    ptr: *mut u8,
+    capacity: usize,
+    original_len: usize,
+    len: usize, // Kept by realloc as the runtime keeps it, programs may move their slice
+}
+
+impl DataBuffer {
+    // The slice holds the bytes borrowed, they can't be touched or dropped while the infos live
+    pub(crate) fn new(bytes: &mut Vec<u8>) -> (Self, &mut [u8]) {
+        let original_len = bytes.len();
+        bytes.resize(original_len + MAX_PERMITTED_DATA_INCREASE, 0);
+        let ptr = bytes.as_mut_ptr();
+        let buffer = Self { grown: None, ptr, capacity: bytes.len(), original_len, len: original_len };
+        // Cut from the pointer kept for realloc, so growing in place doesn't invalidate it
+        let data = unsafe { slice::from_raw_parts_mut(ptr, original_len) };
+        (buffer, data)
+    }
+
+    pub(crate) fn holds(&self, data: &[u8]) -> bool {
+        std::ptr::eq(data.as_ptr(), self.ptr)
+    }
+
+    // Takes the slice handed out by this buffer and replaces it, grown bytes are zeroed
+    pub(crate) fn resize(&mut self, data: &mut &mut [u8], new_len: usize, max_increase: usize) -> ProgramResult {
+        if new_len > self.original_len.saturating_add(max_increase) {
+            return Err(ProgramError::InvalidRealloc);
+        }
+        let old_len = data.len();
+        self.len = new_len;
+        if new_len <= old_len {
+            let data_slice = mem::take(data);
+            *data = &mut data_slice[..new_len];
+            return Ok(());
+        }
+
+        if new_len > self.capacity {
+            let mut bytes = vec![0; new_len + MAX_PERMITTED_DATA_INCREASE];
+            bytes[..old_len].copy_from_slice(data);
+            self.ptr = bytes.as_mut_ptr();
+            self.capacity = bytes.len();
+            *data = &mut [];
+            self.grown = Some(bytes);
+        }
+        *data = unsafe { slice::from_raw_parts_mut(self.ptr, new_len) };
+        data[old_len..].fill(0);
+        Ok(())
+    }
+
+    // The data as the program left it, in the bytes it was cut from unless it outgrew them
+    pub(crate) fn into_data(self, bytes: Vec<u8>) -> Vec<u8> {
+        let mut bytes = self.grown.unwrap_or(bytes);
+        bytes.truncate(self.len);
+        bytes
+    }
+}
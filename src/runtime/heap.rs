@@ -0,0 +1,133 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+pub use solana_program::entrypoint::HEAP_LENGTH;
+
+
+// Nested invocations accounted at once, deeper frames run untracked
+const MAX_FRAMES: usize = 8;
+
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    static DEPTH: Cell<usize> = const { Cell::new(0) };
+    static USED: Cell<[usize; MAX_FRAMES]> = const { Cell::new([0; MAX_FRAMES]) };
+    static PAUSED: Cell<[bool; MAX_FRAMES]> = const { Cell::new([false; MAX_FRAMES]) };
+}
+
+// Counts what programs allocate while they run, install it in the test binary with
+// #[global_allocator] static ALLOCATOR: TrackingAllocator = TrackingAllocator;
+// Only packages running in this process are seen, external libraries link their own allocator
+pub struct TrackingAllocator;
+
+impl TrackingAllocator {
+    pub fn is_installed() -> bool {
+        INSTALLED.load(Ordering::Relaxed)
+    }
+}
+
+unsafe impl GlobalAlloc for TrackingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        record(layout.size(), layout.align());
+        System.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        record(layout.size(), layout.align());
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    // The bump allocator never grows in place, every realloc takes a fresh block
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        record(new_size, layout.align());
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+// Frees give nothing back, as with the on-chain bump allocator
+fn record(size: usize, align: usize) {
+    INSTALLED.store(true, Ordering::Relaxed);
+    let _ = DEPTH.try_with(|depth| {
+        let depth = depth.get();
+        if depth == 0 || depth > MAX_FRAMES || PAUSED.with(|paused| paused.get()[depth - 1]) {
+            return;
+        }
+        USED.with(|used| {
+            let mut frames = used.get();
+            frames[depth - 1] = (frames[depth - 1] + size + align - 1) & !(align - 1);
+            used.set(frames);
+        });
+    });
+}
+
+
+// Heap of one program invocation, opened right before the package executes
+pub(crate) struct HeapFrame {
+    depth: usize,
+}
+
+impl HeapFrame {
+    pub(crate) fn enter() -> Self {
+        if !TrackingAllocator::is_installed() {
+            panic!("Heap tracking requires TrackingAllocator installed as the global allocator: \
+                #[global_allocator] static ALLOCATOR: TrackingAllocator = TrackingAllocator;");
+        }
+        let depth = DEPTH.with(|depth| {
+            depth.set(depth.get() + 1);
+            depth.get()
+        });
+        if depth <= MAX_FRAMES {
+            USED.with(|used| {
+                let mut frames = used.get();
+                frames[depth - 1] = 0;
+                used.set(frames);
+            });
+            set_paused(depth, false);
+        }
+        Self { depth }
+    }
+
+    // Bytes the invocation allocated, callees not included
+    pub(crate) fn exit(self) -> usize {
+        DEPTH.with(|depth| depth.set(self.depth - 1));
+        if self.depth > MAX_FRAMES {
+            return 0;
+        }
+        USED.with(|used| used.get()[self.depth - 1])
+    }
+}
+
+// Runtime bookkeeping done on behalf of the program isn't charged to its heap
+pub(crate) struct Untracked {
+    depth: usize,
+    paused: bool,
+}
+
+pub(crate) fn untracked() -> Untracked {
+    let depth = DEPTH.with(|depth| depth.get());
+    let paused = depth > 0 && depth <= MAX_FRAMES && PAUSED.with(|paused| paused.get()[depth - 1]);
+    set_paused(depth, true);
+    Untracked { depth, paused }
+}
+
+impl Drop for Untracked {
+    fn drop(&mut self) {
+        set_paused(self.depth, self.paused);
+    }
+}
+
+fn set_paused(depth: usize, value: bool) {
+    if depth == 0 || depth > MAX_FRAMES {
+        return;
+    }
+    PAUSED.with(|paused| {
+        let mut frames = paused.get();
+        frames[depth - 1] = value;
+        paused.set(frames);
+    });
+}
@@ -0,0 +1,74 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+use lazy_static::lazy_static;
+use regex::Regex;
+use solana_program::{address_lookup_table, bpf_loader_upgradeable, compute_budget, pubkey::Pubkey};
+use crate::suit::{ASSOCIATED_PROGRAM_ID, SPL_PROGRAM_ID, SYSTEM_PROGRAM_ID};
+
+
+lazy_static! {
+    static ref KEY_TOKEN: Regex = Regex::new(r"\b[1-9A-HJ-NP-Za-km-z]{32,44}\b").unwrap();
+}
+
+// Names rendered instead of keys, built-in programs are always labeled
+#[derive(Clone, Debug, PartialEq)]
+pub struct Labels {
+    labels: HashMap<Pubkey, String>,
+}
+
+impl Labels {
+    pub fn new() -> Self {
+        Self {
+            labels: HashMap::from([
+                (SYSTEM_PROGRAM_ID, "system_program".to_string()),
+                (SPL_PROGRAM_ID, "token_program".to_string()),
+                (ASSOCIATED_PROGRAM_ID, "associated_token_program".to_string()),
+                (bpf_loader_upgradeable::ID, "upgradeable_loader".to_string()),
+                (compute_budget::ID, "compute_budget_program".to_string()),
+                (address_lookup_table::ID, "address_lookup_table_program".to_string()),
+            ]),
+        }
+    }
+
+    pub fn set(&mut self, pubkey: &Pubkey, label: &str) {
+        self.labels.insert(*pubkey, label.to_string());
+    }
+
+    pub fn get(&self, pubkey: &Pubkey) -> Option<&str> {
+        self.labels.get(pubkey).map(String::as_str)
+    }
+
+    // Labels of the other set win
+    pub fn extend(&mut self, other: &Labels) {
+        self.labels.extend(other.labels.iter().map(|(pubkey, label)| (*pubkey, label.clone())));
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Pubkey, &str)> {
+        self.labels.iter().map(|(pubkey, label)| (pubkey, label.as_str()))
+    }
+
+    // "alice (7xK...)" for labeled keys, plain base58 otherwise
+    pub fn display_key(&self, pubkey: &Pubkey) -> String {
+        match self.get(pubkey) {
+            Some(label) => format!("{} ({})", label, pubkey),
+            None => pubkey.to_string(),
+        }
+    }
+
+    // Substitutes labels for the labeled keys found in the text, whole tokens only
+    pub fn relabel(&self, text: &str) -> String {
+        KEY_TOKEN.replace_all(text, |captures: &regex::Captures| {
+            let token = &captures[0];
+            Pubkey::from_str(token).ok()
+                .and_then(|pubkey| self.get(&pubkey))
+                .unwrap_or(token)
+                .to_string()
+        }).to_string()
+    }
+}
+
+impl Default for Labels {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,79 @@
+use std::collections::HashMap;
+use std::fmt::Debug;
+use borsh::BorshDeserialize;
+use solana_program::{
+    program_pack::Pack,
+    pubkey::Pubkey,
+};
+
+
+// Decodes account bytes into the pretty Debug form of the registered type
+pub type LayoutDecoder = dyn Fn(&[u8]) -> Option<String> + Send + Sync;
+
+// Account layouts used to decode diffs, looked up by account first, then by owner
+#[derive(Default)]
+pub struct LayoutRegistry {
+    accounts: HashMap<Pubkey, Box<LayoutDecoder>>,
+    owners: HashMap<Pubkey, Box<LayoutDecoder>>,
+}
+
+impl LayoutRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn borsh<T: BorshDeserialize + Debug + 'static>(&mut self, pubkey: &Pubkey) -> &mut Self {
+        self.accounts.insert(*pubkey, Box::new(decode_borsh::<T>));
+        self
+    }
+
+    pub fn pack<T: Pack + Debug + 'static>(&mut self, pubkey: &Pubkey) -> &mut Self {
+        self.accounts.insert(*pubkey, Box::new(decode_pack::<T>));
+        self
+    }
+
+    pub fn borsh_for_owner<T: BorshDeserialize + Debug + 'static>(&mut self, owner: &Pubkey) -> &mut Self {
+        self.owners.insert(*owner, Box::new(decode_borsh::<T>));
+        self
+    }
+
+    pub fn pack_for_owner<T: Pack + Debug + 'static>(&mut self, owner: &Pubkey) -> &mut Self {
+        self.owners.insert(*owner, Box::new(decode_pack::<T>));
+        self
+    }
+
+    pub(crate) fn decoder(&self, pubkey: &Pubkey, owner: &Pubkey) -> Option<&LayoutDecoder> {
+        self.accounts.get(pubkey)
+            .or_else(|| self.owners.get(owner))
+            .map(|decoder| decoder.as_ref())
+    }
+}
+
+fn decode_borsh<T: BorshDeserialize + Debug>(bytes: &[u8]) -> Option<String> {
+    T::deserialize(&mut &bytes[..]).ok().map(|value| format!("{:#?}", value))
+}
+
+fn decode_pack<T: Pack + Debug>(bytes: &[u8]) -> Option<String> {
+    T::unpack_unchecked(bytes).ok().map(|value| format!("{:#?}", value))
+}
+
+// Splits a pretty printed struct into its top-level fields
+pub(crate) fn split_fields(pretty: &str) -> Vec<(String, String)> {
+    let mut fields: Vec<(String, String)> = Vec::new();
+    for line in pretty.lines().skip(1) {
+        if !line.starts_with("    ") {
+            continue;
+        }
+        let is_field = !line.starts_with("     ");
+        match line.trim_end_matches(',').strip_prefix("    ").and_then(|field| field.split_once(": ")) {
+            Some((name, value)) if is_field => fields.push((name.to_string(), value.to_string())),
+            _ => {
+                if let Some((_, value)) = fields.last_mut() {
+                    value.push('\n');
+                    value.push_str(line.trim_end_matches(','));
+                }
+            }
+        }
+    }
+    fields
+}
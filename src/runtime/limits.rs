@@ -0,0 +1,36 @@
+use std::collections::HashSet;
+use solana_program::{message::VersionedMessage, pubkey::Pubkey, short_vec::ShortU16};
+use crate::runtime::{ClusterSettings, Receipt, TransactionError, SIGNATURE_BYTES};
+
+
+// An IPv6 MTU minus the IPv6 and fragment headers, no transaction is larger
+pub const PACKET_DATA_SIZE: usize = 1280 - 40 - 8;
+pub const MAX_TX_ACCOUNT_LOCKS: usize = 64;
+pub const INCREASED_MAX_TX_ACCOUNT_LOCKS: usize = 128;
+
+// Bytes of the signed transaction carrying the message, as sent to the cluster
+pub fn transaction_size(message: &VersionedMessage) -> usize {
+    let signatures = message.header().num_required_signatures as usize;
+    bincode::serialized_size(&ShortU16(signatures as u16)).unwrap() as usize
+        + signatures * SIGNATURE_BYTES
+        + message.serialize().len()
+}
+
+// Checked before the blockhash and the fee, keys are every account the transaction locks
+pub(crate) fn check_transaction_limits(receipt: &mut Receipt, message: &VersionedMessage, keys: &[Pubkey], settings: &ClusterSettings) -> Result<(), TransactionError> {
+    let size = transaction_size(message);
+    if size > PACKET_DATA_SIZE {
+        receipt.push_msg(format!("Transaction too large: {} bytes (max: {} bytes)", size, PACKET_DATA_SIZE));
+        return Err(TransactionError::SanitizeFailure);
+    }
+
+    let mut unique = HashSet::with_capacity(keys.len());
+    if !keys.iter().all(|key| unique.insert(key)) {
+        return Err(TransactionError::AccountLoadedTwice);
+    }
+    if keys.len() > settings.transaction_account_lock_limit {
+        receipt.push_msg(format!("Transaction locks {} accounts (max: {})", keys.len(), settings.transaction_account_lock_limit));
+        return Err(TransactionError::TooManyAccountLocks);
+    }
+    Ok(())
+}
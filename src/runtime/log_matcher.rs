@@ -0,0 +1,166 @@
+use std::fmt;
+use borsh::BorshDeserialize;
+use regex::Regex;
+use solana_program::pubkey::Pubkey;
+use crate::runtime::Receipt;
+
+
+// Matches the text of "Program logged" lines
+#[derive(Clone, Debug)]
+pub enum LogMatcher {
+    Exact(String),
+    Contains(String),
+    Regex(Regex),
+}
+
+impl LogMatcher {
+    pub fn exact(text: &str) -> Self {
+        LogMatcher::Exact(text.to_string())
+    }
+
+    pub fn contains(text: &str) -> Self {
+        LogMatcher::Contains(text.to_string())
+    }
+
+    pub fn regex(pattern: &str) -> Self {
+        LogMatcher::Regex(Regex::new(pattern).unwrap_or_else(|err| panic!("Invalid log pattern {}: {}", pattern, err)))
+    }
+
+    pub fn matches(&self, log: &str) -> bool {
+        match self {
+            LogMatcher::Exact(text) => log.eq(text),
+            LogMatcher::Contains(text) => log.contains(text.as_str()),
+            LogMatcher::Regex(regex) => regex.is_match(log),
+        }
+    }
+}
+
+impl From<&str> for LogMatcher {
+    fn from(text: &str) -> Self {
+        LogMatcher::exact(text)
+    }
+}
+
+impl fmt::Display for LogMatcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LogMatcher::Exact(text) => write!(f, "\"{}\"", text),
+            LogMatcher::Contains(text) => write!(f, "containing \"{}\"", text),
+            LogMatcher::Regex(regex) => write!(f, "matching /{}/", regex),
+        }
+    }
+}
+
+
+impl Receipt {
+    // Texts of "Program logged" lines, optionally only those emitted by the program
+    pub fn program_logs(&self, program_id: Option<&Pubkey>) -> Vec<&str> {
+        self.log_messages.iter()
+            .zip(self.log_programs.iter())
+            .filter(|(_, source)| program_id.map(|id| source.as_ref() == Some(id)).unwrap_or(true))
+            .filter_map(|(msg, _)| msg.strip_prefix("Program logged: \"").and_then(|msg| msg.strip_suffix('"')))
+            .collect()
+    }
+
+    pub fn count_logs<M: Into<LogMatcher>>(&self, matcher: M) -> usize {
+        let matcher = matcher.into();
+        self.program_logs(None).iter().filter(|log| matcher.matches(log)).count()
+    }
+
+    // Values passed to sol_log_64, in order
+    pub fn log_u64s(&self, program_id: Option<&Pubkey>) -> Vec<[u64; 5]> {
+        self.program_logs(program_id).iter()
+            .filter_map(|log| parse_log_64(log))
+            .collect()
+    }
+
+    // "Program data" entries of the program, the slices of each entry decoded as one value
+    pub fn data_as<T: BorshDeserialize>(&self, program_id: &Pubkey) -> Vec<T> {
+        self.events(program_id).into_iter().map(|event| event.value).collect()
+    }
+
+    pub fn expect_log_matching<M: Into<LogMatcher>>(&self, matcher: M) {
+        let matcher = matcher.into();
+        if self.count_logs(matcher.clone()) == 0 {
+            println!("{}", self);
+            println!("Log {} not found", matcher);
+            assert_ne!(self.count_logs(matcher), 0);
+        }
+    }
+
+    pub fn expect_log_contains(&self, text: &str) {
+        self.expect_log_matching(LogMatcher::contains(text));
+    }
+
+    pub fn expect_log_regex(&self, pattern: &str) {
+        self.expect_log_matching(LogMatcher::regex(pattern));
+    }
+
+    pub fn expect_no_log<M: Into<LogMatcher>>(&self, matcher: M) {
+        let matcher = matcher.into();
+        if self.count_logs(matcher.clone()) != 0 {
+            println!("{}", self);
+            println!("Log {} found when must not", matcher);
+            assert_eq!(self.count_logs(matcher), 0);
+        }
+    }
+
+    pub fn expect_log_count<M: Into<LogMatcher>>(&self, matcher: M, count: usize) {
+        let matcher = matcher.into();
+        let got = self.count_logs(matcher.clone());
+        if got != count {
+            println!("{}", self);
+            println!("Log {} found {} times, expected {}", matcher, got, count);
+            assert_eq!(got, count);
+        }
+    }
+
+    // Matchers must match logs in this order, other logs may come in between
+    pub fn expect_logs_in_order(&self, matchers: &[LogMatcher]) {
+        let logs = self.program_logs(None);
+        let mut position = 0;
+        for matcher in matchers.iter() {
+            match logs[position..].iter().position(|log| matcher.matches(log)) {
+                Some(found) => position += found + 1,
+                None => {
+                    println!("{}", self);
+                    println!("Log {} not found after position {}", matcher, position);
+                    panic!("Logs out of order");
+                }
+            }
+        }
+    }
+
+    pub fn expect_program_log<M: Into<LogMatcher>>(&self, program_id: &Pubkey, matcher: M) {
+        let matcher = matcher.into();
+        if !self.program_logs(Some(program_id)).iter().any(|log| matcher.matches(log)) {
+            println!("{}", self);
+            println!("Log {} not found for {}", matcher, self.labels.display_key(program_id));
+            assert!(self.program_logs(Some(program_id)).iter().any(|log| matcher.matches(log)));
+        }
+    }
+
+    pub fn expect_log_64(&self, values: [u64; 5]) {
+        if !self.log_u64s(None).contains(&values) {
+            println!("{}", self);
+            println!("Log {:?} not found", values);
+            assert!(self.log_u64s(None).contains(&values));
+        }
+    }
+
+    pub fn expect_data_as<T: BorshDeserialize + PartialEq + fmt::Debug>(&self, program_id: &Pubkey, value: &T) {
+        let values: Vec<T> = self.data_as(program_id);
+        if !values.contains(value) {
+            println!("{}", self);
+            println!("Data {:?} not found for {}, decoded {:?}", value, self.labels.display_key(program_id), values);
+            assert!(values.contains(value));
+        }
+    }
+}
+
+fn parse_log_64(log: &str) -> Option<[u64; 5]> {
+    let values: Vec<u64> = log.split(", ")
+        .map(|value| value.strip_prefix("0x").and_then(|hex| u64::from_str_radix(hex, 16).ok()))
+        .collect::<Option<Vec<u64>>>()?;
+    values.try_into().ok()
+}
@@ -1,107 +1,64 @@
-use std::cell::{RefCell, RefMut};
-use std::mem::forget;
-use std::rc::Rc;
-use std::sync::atomic::{AtomicPtr, Ordering};
-use std::sync::atomic::Ordering::Relaxed;
+use std::fmt;
+use std::sync::Arc;
 use solana_program::pubkey::Pubkey;
-use solana_program::account_info::AccountInfo;
 use crate::executor::Package;
 
 
-#[derive(Debug)]
-pub struct Meta {
+// Account or program lent to one execution, changes are written back through it once instructions return
+pub struct Meta<'a> {
     pub(crate) is_signer: bool,
     pub(crate) is_writable: bool,
     pub(crate) executable: bool,
-    pub(crate) lamports: AtomicPtr<u64>, // Owned by Account or Program
-    pub(crate) owner: AtomicPtr<Pubkey>, // Owned by Account or Program
-    pub(crate) data: AtomicPtr<Vec<u8>>, // Owned by Account or Program
-    pub(crate) package: AtomicPtr<Box<dyn Package>>, // Owned by Program or Null
+    pub(crate) lamports: &'a mut u64,
+    pub(crate) owner: &'a mut Pubkey,
+    pub(crate) data: &'a mut Vec<u8>,
+    pub(crate) package: Option<Arc<dyn Package>>, // Programs only
+    pub(crate) label: Option<String>,
 }
 
-impl Meta {
-    pub fn as_info<'a>(&self, pubkey: &'a Pubkey) -> AccountInfo<'a> {
-        self.as_info_with_meta(pubkey, self.is_signer, self.is_writable)
-    }
-
-    pub fn as_info_with_meta<'a>(&self, pubkey: &'a Pubkey, is_signer: bool, is_writable: bool) -> AccountInfo<'a> {
-        unsafe {
-            AccountInfo {
-                key: pubkey,
-                lamports: Rc::new(RefCell::new(self.lamports.load(Relaxed).as_mut().unwrap())),
-                data: Rc::new(RefCell::new(self.data.load(Relaxed).as_mut().unwrap())),
-                owner: self.owner.load(Relaxed).as_ref().unwrap(),
-                rent_epoch: 0,
-                is_signer,
-                is_writable,
-                executable: self.executable,
-            }
-        }
-    }
-
-    pub fn shallow_copy_info<'a>(&self, info: &'a AccountInfo<'a>, is_signer: bool, is_writable: bool) -> AccountInfo<'a> {
-        unsafe {
-            AccountInfo {
-                key: info.key,
-                lamports: Rc::clone(&info.lamports),
-                data: Rc::clone(&info.data),
-                owner: info.owner,
-                rent_epoch: 0,
-                is_signer,
-                is_writable,
-                executable: self.executable,
-            }
-        }
-    }
-
-    pub fn as_package<'a>(&self) -> &'a Box<dyn Package> {
-        unsafe {
-            self.package.load(Relaxed).as_mut().unwrap()
-        }
-    }
-
+impl Meta<'_> {
     pub fn set_owner(&mut self, owner: &Pubkey) {
-        let value = self.owner.load(Relaxed);
-        unsafe { *value = owner.clone() }
+        *self.owner = *owner;
     }
 
     pub fn set_lamports(&mut self, lamports: u64) {
-        let value = self.lamports.load(Relaxed);
-        unsafe { *value = lamports; }
+        *self.lamports = lamports;
     }
 
-    pub fn set_data(&mut self, info: &AccountInfo, data: Vec<u8>) {
-        unsafe {
-            let value = unsafe { self.data.load(Relaxed).as_mut().unwrap() };
-            *value = data;
-
-            *info.data.borrow_mut() = value;
-        }
+    pub fn set_data(&mut self, data: Vec<u8>) {
+        *self.data = data;
     }
 
     pub fn get_lamports(&self) -> u64 {
-        let value = self.lamports.load(Relaxed);
-        unsafe { *value }
+        *self.lamports
     }
 
     pub fn get_owner(&self) -> &Pubkey {
-        let value = self.owner.load(Relaxed);
-        unsafe {value.as_mut().unwrap()}
+        self.owner
     }
 
     pub fn get_data(&self) -> &Vec<u8> {
-        let value = self.data.load(Relaxed);
-        unsafe {value.as_mut().unwrap()}
+        self.data
     }
-    
-    pub fn get_data_ptr(&self) -> AtomicPtr<Vec<u8>> {
-        AtomicPtr::new(self.data.load(Ordering::Relaxed))
+}
+
+impl fmt::Debug for Meta<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Meta")
+            .field("is_signer", &self.is_signer)
+            .field("is_writable", &self.is_writable)
+            .field("executable", &self.executable)
+            .field("lamports", &self.lamports)
+            .field("owner", &self.owner)
+            .field("data", &self.data)
+            .field("label", &self.label)
+            .finish_non_exhaustive()
     }
 }
 
-impl ToString for Meta {
-    fn to_string(&self) -> String {
-        format!("is_signer: {}, is_writable: {}, executable: {}, owner: {:?}, lamports: {}, data: {:?}",
+impl fmt::Display for Meta<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "is_signer: {}, is_writable: {}, executable: {}, owner: {:?}, lamports: {}, data: {:?}",
             self.is_signer,
             self.is_writable,
             self.executable,
@@ -111,17 +68,3 @@ impl ToString for Meta {
         )
     }
 }
-
-impl Clone for Meta {
-    fn clone(&self) -> Self {
-        Self {
-            is_signer: self.is_signer,
-            is_writable: self.is_writable,
-            executable: self.executable,
-            lamports: AtomicPtr::new(self.lamports.load(Ordering::Relaxed)),
-            owner: AtomicPtr::new(self.owner.load(Ordering::Relaxed)),
-            data: AtomicPtr::new(self.data.load(Ordering::Relaxed)),
-            package: AtomicPtr::new(self.package.load(Ordering::Relaxed))
-        }
-    }
-}
\ No newline at end of file
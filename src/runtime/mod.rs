@@ -3,8 +3,30 @@ mod receipt;
 mod account;
 mod program;
 mod meta;
+mod frame;
 mod scope;
 mod utils;
+mod upgradeable_program;
+mod config;
+mod faults;
+mod observer;
+mod layout;
+mod diff;
+mod labels;
+mod snapshot;
+mod log_matcher;
+mod events;
+mod rent_state;
+mod heap;
+mod compute_budget;
+mod transaction;
+mod fees;
+mod wire;
+mod limits;
+mod account_store;
+mod account_query;
+mod environment;
+mod banks_client;
 
 pub use scope::*;
 pub use program::*;
@@ -13,4 +35,27 @@ pub use cluster_settings::*;
 pub use account::*;
 pub use scope::*;
 pub use meta::*;
+pub(crate) use frame::{DataBuffer, Frame, FrameAccount};
 pub use utils::*;
+pub use config::*;
+pub use faults::*;
+pub use observer::*;
+pub use layout::LayoutRegistry;
+pub use diff::*;
+pub use labels::*;
+pub use log_matcher::LogMatcher;
+pub use events::*;
+pub use rent_state::RentState;
+pub use heap::{TrackingAllocator, HEAP_LENGTH};
+pub(crate) use heap::{untracked, HeapFrame};
+pub use compute_budget::*;
+pub use transaction::*;
+pub use account_store::AccountStore;
+pub use account_query::*;
+pub use environment::*;
+pub use banks_client::*;
+pub use fees::FeeDetails;
+pub use wire::{Signature, SIGNATURE_BYTES};
+pub use limits::{transaction_size, INCREASED_MAX_TX_ACCOUNT_LOCKS, MAX_TX_ACCOUNT_LOCKS, PACKET_DATA_SIZE};
+pub(crate) use limits::check_transaction_limits;
+pub use snapshot::{SNAPSHOT_DIR_ENV, UPDATE_SNAPSHOTS_ENV};
@@ -0,0 +1,124 @@
+use std::fmt;
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    pubkey::Pubkey,
+};
+use crate::runtime::{AccountDiff, InjectedFault, Labels};
+
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Verbosity {
+    Quiet, // Receipt is printed by failed expectations only
+    Logs,  // Program log lines as they are emitted
+    Trace, // Everything, including accounts around each CPI
+}
+
+impl Verbosity {
+    // FULGURITE_VERBOSITY=quiet|logs|trace, quiet when unset
+    pub fn from_env() -> Self {
+        match std::env::var("FULGURITE_VERBOSITY").unwrap_or_default().to_lowercase().as_str() {
+            "logs" => Verbosity::Logs,
+            "trace" => Verbosity::Trace,
+            _ => Verbosity::Quiet,
+        }
+    }
+}
+
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InvokeStage {
+    Before,
+    After,
+}
+
+#[derive(Debug)]
+pub enum RuntimeEvent<'a> {
+    InvokeStarted { program_id: &'a Pubkey, stack_height: usize },
+    InvokeFinished { program_id: &'a Pubkey, stack_height: usize, result: &'a ProgramResult },
+    Accounts { stage: InvokeStage, program_id: &'a Pubkey, accounts: &'a [AccountInfo<'a>] },
+    Log { message: &'a str },
+    Data { program_id: &'a Pubkey, data: &'a [Vec<u8>] },
+    ReturnData { program_id: &'a Pubkey, data: &'a [u8] },
+    FaultInjected { fault: &'a InjectedFault },
+    AccountChanged { diff: &'a AccountDiff },
+}
+
+impl RuntimeEvent<'_> {
+    pub fn verbosity(&self) -> Verbosity {
+        match self {
+            RuntimeEvent::Log { .. } => Verbosity::Logs,
+            _ => Verbosity::Trace,
+        }
+    }
+}
+
+impl fmt::Display for RuntimeEvent<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RuntimeEvent::InvokeStarted { program_id, stack_height } =>
+                write!(f, "[{}] Invoke {}", stack_height, program_id),
+            RuntimeEvent::InvokeFinished { program_id, stack_height, result } =>
+                write!(f, "[{}] Finished {}: {:?}", stack_height, program_id, result),
+            RuntimeEvent::Accounts { stage, program_id, accounts } => {
+                write!(f, "{:?} {}", stage, program_id)?;
+                for account in accounts.iter() {
+                    write!(f, "\n    {:?}", account)?;
+                }
+                Ok(())
+            }
+            RuntimeEvent::Log { message } => write!(f, "{}", message),
+            RuntimeEvent::Data { program_id, data } => write!(f, "Data {}: {:?}", program_id, data),
+            RuntimeEvent::ReturnData { program_id, data } => write!(f, "Return {}: {:?}", program_id, data),
+            RuntimeEvent::FaultInjected { fault } => write!(f, "Fault {:?}", fault),
+            RuntimeEvent::AccountChanged { diff } => write!(f, "{}", diff),
+        }
+    }
+}
+
+
+// Configs go along with the environments they run on, which are shared between threads
+pub trait Observer: Send + Sync {
+    fn on_event(&mut self, event: &RuntimeEvent);
+}
+
+impl<F: FnMut(&RuntimeEvent) + Send + Sync> Observer for F {
+    fn on_event(&mut self, event: &RuntimeEvent) {
+        self(event)
+    }
+}
+
+
+// Prints events up to the verbosity and forwards all of them to the observers
+pub struct Tracer {
+    pub(crate) verbosity: Verbosity,
+    pub(crate) labels: Labels,
+    observers: Vec<Box<dyn Observer>>,
+}
+
+impl Tracer {
+    pub fn new(verbosity: Verbosity, observers: Vec<Box<dyn Observer>>) -> Self {
+        Self { verbosity, labels: Labels::new(), observers }
+    }
+
+    pub(crate) fn emit(&mut self, event: RuntimeEvent) {
+        if self.verbosity != Verbosity::Quiet && self.verbosity >= event.verbosity() {
+            println!("{}", self.labels.relabel(&event.to_string()));
+        }
+        for observer in self.observers.iter_mut() {
+            observer.on_event(&event);
+        }
+    }
+}
+
+impl Default for Tracer {
+    fn default() -> Self {
+        Self::new(Verbosity::from_env(), Vec::new())
+    }
+}
+
+impl fmt::Debug for Tracer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Tracer({:?}, {} observers)", self.verbosity, self.observers.len())
+    }
+}
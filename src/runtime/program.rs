@@ -1,14 +1,24 @@
-use std::sync::atomic::AtomicPtr;
+use std::sync::Arc;
 use borsh::BorshSerialize;
 use solana_program::{
     bpf_loader,
+    instruction::{AccountMeta, Instruction},
+    message::{Message, VersionedMessage},
     pubkey::Pubkey,
 };
 use solana_program::account_info::AccountInfo;
 use solana_program::entrypoint::ProcessInstruction;
-use crate::executor::{ExternalPackage, Package, InlinePackage};
+use crate::executor::{ExternalPackage, FnPackage, InlinePackage, MockPackage, Package, PackageError, PackageLocator};
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program_error::ProgramError;
 use crate::runtime::{
+    check_transaction_limits,
+    fees::charge_fee,
+    AccountSnapshot,
+    Frame,
+    FrameAccount,
     Receipt,
+    RuntimeConfig,
     Scope,
     Meta,
 };
@@ -17,48 +27,84 @@ pub use built_in::*;
 
 pub struct Program {
     pub pubkey: Pubkey,
-    pub(crate) package: Box<dyn Package>,
-    owner: Box<Pubkey>,
-    proxy_lamports: Box<u64>,
-    proxy_data: Box<Vec<u8>>,
+    pub(crate) package: Arc<dyn Package>,
+    pub(crate) owner: Pubkey,
+    pub(crate) proxy_lamports: u64,
+    pub(crate) proxy_data: Vec<u8>,
+    pub(crate) label: Option<String>,
 }
 
 impl Program {
     pub fn new(name: &str, pubkey: Pubkey) -> Self {
-        Self {
-            pubkey,
-            package: Box::new(ExternalPackage::new(name)),
-            owner: Box::new(bpf_loader::ID.clone()),
-            proxy_lamports: Box::new(0x1337),
-            proxy_data: Box::new(Vec::new()),
-        }
+        Self::with_package(Box::new(ExternalPackage::new(name)), pubkey)
+    }
+
+    pub fn try_new(name: &str, pubkey: Pubkey) -> Result<Self, PackageError> {
+        Self::from_locator(&PackageLocator::new(name), pubkey)
+    }
+
+    pub fn from_locator(locator: &PackageLocator, pubkey: Pubkey) -> Result<Self, PackageError> {
+        Ok(Self::with_package(Box::new(ExternalPackage::from_locator(locator)?), pubkey))
     }
 
     pub fn inline(entrypoint: ProcessInstruction, pubkey: Pubkey) -> Self {
+        Self::with_package(Box::new(InlinePackage::new(entrypoint)), pubkey)
+    }
+
+    pub fn with_package(package: Box<dyn Package>, pubkey: Pubkey) -> Self {
         Self {
             pubkey,
-            package: Box::new(InlinePackage::new(entrypoint)),
-            owner: Box::new(bpf_loader::ID.clone()),
-            proxy_lamports: Box::new(0x1337),
-            proxy_data: Box::new(Vec::new()),
+            package: Arc::from(package),
+            owner: bpf_loader::ID,
+            proxy_lamports: 0x1337,
+            proxy_data: Vec::new(),
+            label: None,
         }
     }
 
+    pub fn from_fn<F>(function: F, pubkey: Pubkey) -> Self
+        where F: Fn(&Pubkey, &[AccountInfo], &[u8]) -> ProgramResult + Send + Sync + 'static
+    {
+        Self::with_package(Box::new(FnPackage::new(function)), pubkey)
+    }
+
+    // Recording stand-in, keep the returned handle to script responses and inspect calls
+    pub fn mock(pubkey: Pubkey) -> (Self, MockPackage) {
+        let mock = MockPackage::new();
+        (Self::with_package(Box::new(mock.clone()), pubkey), mock)
+    }
+
+    // Swaps the code in place, the next execution runs it
+    pub fn reload(&mut self, package: Box<dyn Package>) {
+        self.package = Arc::from(package);
+    }
+
     pub fn pubkey(&self) -> &Pubkey {
         &self.pubkey
     }
 
-    pub fn meta(&mut self) -> (Pubkey, Meta) {
+    pub fn with_label(mut self, label: &str) -> Self {
+        self.label = Some(label.to_string());
+        self
+    }
+
+    pub fn meta(&mut self) -> (Pubkey, Meta<'_>) {
+        self.meta_with_writable(false)
+    }
+
+    // Loader instructions (Upgrade, Close) require the program account to be writable
+    pub fn meta_with_writable(&mut self, is_writable: bool) -> (Pubkey, Meta<'_>) {
         (
-            self.pubkey.clone(),
+            self.pubkey,
             Meta {
                 is_signer: false,
-                is_writable: false,
+                is_writable,
                 executable: true,
-                lamports: AtomicPtr::new(self.proxy_lamports.as_mut() as *mut u64),
-                owner: AtomicPtr::new(self.owner.as_mut() as *mut Pubkey),
-                data: AtomicPtr::new(self.proxy_data.as_mut() as *mut Vec<u8>),
-                package: AtomicPtr::new((&mut self.package) as *mut Box<dyn Package>),
+                lamports: &mut self.proxy_lamports,
+                owner: &mut self.owner,
+                data: &mut self.proxy_data,
+                package: Some(self.package.clone()),
+                label: self.label.clone(),
             }
         )
     }
@@ -69,26 +115,59 @@ impl Program {
         self.invoke_with_bytes(bytes.as_slice(), accounts)
     }
 
-    pub fn invoke_with_bytes<'e>(&self, instruction_data: &[u8], accounts: Vec<(Pubkey, Meta)>) -> Receipt {
-        let mut scope = Box::new(Scope::new(&accounts));
-        let infos: Vec<AccountInfo> = accounts.iter().map(|(key, meta)| meta.as_info(&key)).collect();
+    pub fn invoke_with_bytes(&self, instruction_data: &[u8], accounts: Vec<(Pubkey, Meta)>) -> Receipt {
+        self.invoke_with_config(instruction_data, accounts, RuntimeConfig::new())
+    }
 
-        scope.receipt.call_stack.push(self.pubkey.clone());
-        scope.receipt.log_program_invoked(&self.pubkey);
-        scope.receipt.return_data = None;
+    pub fn invoke_with_config(&self, instruction_data: &[u8], mut accounts: Vec<(Pubkey, Meta)>, mut config: RuntimeConfig) -> Receipt {
+        if let Some(label) = &self.label {
+            config.labels.set(&self.pubkey, label);
+        }
+        let payer = config.fee_payer;
+        let transaction_limits = config.transaction_limits;
+        let mut scope = Box::new(Scope::with_config(&accounts, config));
+        let snapshot = AccountSnapshot::capture(&accounts);
+
+        // Opted in, the invocation has to fit in a transaction of its own
+        if transaction_limits {
+            let instruction = Instruction {
+                program_id: self.pubkey,
+                accounts: accounts.iter().map(|(key, meta)| AccountMeta { pubkey: *key, is_signer: meta.is_signer, is_writable: meta.is_writable }).collect(),
+                data: instruction_data.to_vec(),
+            };
+            let message = Message::new(&[instruction], payer.as_ref());
+            let keys = message.account_keys.clone();
+            if let Err(err) = check_transaction_limits(&mut scope.receipt, &VersionedMessage::Legacy(message), &keys, &scope.settings) {
+                scope.receipt.fail_transaction(err);
+                return scope.receipt;
+            }
+        }
 
-        scope.receipt.result = self.package.execute(
-            infos.as_slice(),
-            instruction_data,
-            &self.pubkey,
-            scope.clone(),
-        );
+        if let Some(payer) = payer {
+            match charge_fee(&payer, &mut accounts, &scope.settings, &scope.receipt.compute_budget) {
+                Ok(fee) => scope.receipt.record_fee(fee),
+                Err(err) => {
+                    scope.receipt.fail_transaction(err);
+                    return scope.receipt;
+                }
+            }
+        }
 
-        match &scope.receipt.result {
-            Ok(_) => { scope.receipt.log_program_succeed(); }
-            Err(err) => { scope.receipt.log_program_failed(err.clone()); }
+        let mut frame = Frame::default();
+        for (key, meta) in accounts.iter() {
+            frame.add(FrameAccount::from_meta(key, meta), meta.is_signer, meta.is_writable);
         }
-        scope.receipt.call_stack.pop();
+        scope.receipt.result = scope.process_instruction(&self.pubkey, self.package.as_ref(), instruction_data, &mut frame);
+        frame.store(&mut accounts);
+
+        if scope.receipt.result.is_ok() {
+            if let Some(pubkey) = snapshot.rent_violation(&accounts, &scope.settings.as_rent()) {
+                scope.receipt.push_msg(format!("Account {} has insufficient funds for rent", pubkey));
+                scope.receipt.result = Err(ProgramError::AccountNotRentExempt);
+            }
+        }
+        let diffs = snapshot.diff(&accounts, &scope.layouts);
+        scope.receipt.record_diffs(diffs);
 
         scope.receipt
     }
@@ -96,8 +175,8 @@ impl Program {
 
 
 mod built_in {
-    use solana_program::bpf_loader;
-    use crate::executor::SystemProgramPackage;
+    use solana_program::{address_lookup_table, compute_budget};
+    use crate::executor::{AddressLookupTablePackage, ComputeBudgetPackage, SystemProgramPackage};
     use crate::runtime::Program;
     use crate::suit::{ASSOCIATED_PROGRAM_ID, SPL_PROGRAM_ID, SYSTEM_PROGRAM_ID, SYSVAR_PROGRAM_ID};
 
@@ -105,31 +184,27 @@ mod built_in {
     // Built-in Programs
     impl Program {
         pub fn system_program() -> Self {
-            Self {
-                pubkey: SYSTEM_PROGRAM_ID.clone(),
-                package: Box::new(SystemProgramPackage {}),
-                owner: Box::new(bpf_loader::ID.clone()),
-                proxy_lamports: Box::new(0x1337),
-                proxy_data: Box::new(Vec::new()),
-            }
+            Self::with_package(Box::new(SystemProgramPackage {}), SYSTEM_PROGRAM_ID)
+        }
+
+        pub fn compute_budget_program() -> Self {
+            Self::with_package(Box::new(ComputeBudgetPackage {}), compute_budget::ID)
+        }
+
+        pub fn address_lookup_table_program() -> Self {
+            Self::with_package(Box::new(AddressLookupTablePackage {}), address_lookup_table::ID)
         }
 
         pub fn token_program() -> Self {
-            Self::inline(spl_token::entrypoint::entrypoint, SPL_PROGRAM_ID.clone())
+            Self::inline(spl_token::entrypoint::entrypoint, SPL_PROGRAM_ID)
         }
 
         pub fn associated_token_program() -> Self {
-            Self::inline(spl_associated_token_account::entrypoint::entrypoint, ASSOCIATED_PROGRAM_ID.clone())
+            Self::inline(spl_associated_token_account::entrypoint::entrypoint, ASSOCIATED_PROGRAM_ID)
         }
 
         pub fn sysvar_program() -> Self {
-            Self { //TODO research methods
-                pubkey: SYSVAR_PROGRAM_ID.clone(),
-                package: Box::new(SystemProgramPackage {}),
-                owner: Box::new(bpf_loader::ID.clone()),
-                proxy_lamports: Box::new(0x1337),
-                proxy_data: Box::new(Vec::new()),
-            }
+            Self::with_package(Box::new(SystemProgramPackage {}), SYSVAR_PROGRAM_ID) //TODO research methods
         }
     }
 }
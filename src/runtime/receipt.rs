@@ -3,31 +3,106 @@ use solana_program::{
     program_error::ProgramError,
     pubkey::Pubkey
 };
-use crate::runtime::utils;
+use std::fmt;
+use borsh::BorshDeserialize;
+use crate::runtime::{utils, AccountDiff, ComputeBudget, FeeDetails, InjectedFault, Labels, RuntimeEvent, Tracer, TransactionError};
 
 #[derive(Debug)]
 pub struct Receipt {
-    pub result: ProgramResult,
+    pub result: ProgramResult, // Of the instruction that ran last, see transaction_error for the rest
+    pub(crate) transaction_error: Option<TransactionError>,
+    pub(crate) compute_budget: ComputeBudget,
+    pub(crate) fee: Option<FeeDetails>,
     pub(crate) log_messages: Vec<String>,
+    pub(crate) log_programs: Vec<Option<Pubkey>>, // Program active when each message was logged
     pub(crate) log_datas: Vec<(Pubkey, Vec<Vec<u8>>)>,
+    pub(crate) log_data_paths: Vec<Vec<usize>>, // Call path of the invocation that logged each data
     pub(crate) call_stack: Vec<Pubkey>,
+    pub(crate) call_path: Vec<usize>, // Indexes of the running invocation among its siblings, empty at the top
+    child_counts: Vec<usize>,
     pub(crate) return_data: Option<(Pubkey, Vec<u8>)>,
+    abort: Option<ProgramError>, // Syscall failure of the running program, reported once it returns
+    pub(crate) injected_faults: Vec<InjectedFault>,
+    pub(crate) account_diffs: Vec<AccountDiff>,
+    pub(crate) heap_peak: Option<usize>, // Largest heap of a single invocation, when tracked
+    pub(crate) timestamps: Vec<i64>, // Clock values, normalized in snapshots
+    pub(crate) labels: Labels,
+    pub(crate) tracer: Tracer,
 }
 
 impl Receipt {
     pub(crate) fn new() -> Self {
+        Self::with_tracer(Tracer::default())
+    }
+
+    pub(crate) fn with_tracer(tracer: Tracer) -> Self {
         Self {
             result: Ok(()),
+            transaction_error: None,
+            compute_budget: ComputeBudget::new(),
+            fee: None,
             log_messages: Vec::new(),
+            log_programs: Vec::new(),
             log_datas: Vec::new(),
+            log_data_paths: Vec::new(),
             call_stack: Vec::new(),
+            call_path: Vec::new(),
+            child_counts: Vec::new(),
             return_data: None,
+            abort: None,
+            injected_faults: Vec::new(),
+            account_diffs: Vec::new(),
+            heap_peak: None,
+            timestamps: Vec::new(),
+            labels: tracer.labels.clone(),
+            tracer,
         }
     }
 
     pub(crate) fn push_msg(&mut self, msg: String) {
-        println!("{}", &msg);
+        self.tracer.emit(RuntimeEvent::Log { message: &msg });
         self.log_messages.push(msg);
+        self.log_programs.push(self.call_stack.last().cloned());
+    }
+
+    pub(crate) fn push_data(&mut self, data: Vec<Vec<u8>>) {
+        let program_id = self.active_program();
+        self.tracer.emit(RuntimeEvent::Data { program_id: &program_id, data: &data });
+        self.log_datas.push((program_id, data));
+        self.log_data_paths.push(self.call_path.clone());
+    }
+
+    pub(crate) fn set_return_data(&mut self, data: &[u8]) {
+        let program_id = self.active_program();
+        self.tracer.emit(RuntimeEvent::ReturnData { program_id: &program_id, data });
+        self.return_data = Some((program_id, Vec::from(data)));
+    }
+
+    // The VM stops the program on a failed syscall, native code keeps running until it returns
+    pub(crate) fn abort(&mut self, reason: String, err: ProgramError) {
+        self.push_msg(format!("Program {} failed: {}", self.active_program(), reason));
+        if self.abort.is_none() {
+            self.abort = Some(err);
+        }
+    }
+
+    pub(crate) fn record_heap_usage(&mut self, used: usize) {
+        self.heap_peak = Some(self.heap_peak.unwrap_or(0).max(used));
+    }
+
+    pub(crate) fn record_fee(&mut self, fee: FeeDetails) {
+        self.fee = Some(fee);
+    }
+
+    pub(crate) fn fail_transaction(&mut self, err: TransactionError) {
+        if let TransactionError::InstructionError(_, program_err) = &err {
+            self.result = Err(program_err.clone());
+        }
+        self.transaction_error = Some(err);
+    }
+
+    pub(crate) fn take_abort(&mut self) -> Option<ProgramError> {
+        self.abort.take()
     }
 
     pub(crate) fn active_program(&self) -> Pubkey {
@@ -35,23 +110,98 @@ impl Receipt {
     }
 
     pub(crate) fn log_program_invoked(&mut self, program: &Pubkey) {
-        self.push_msg(format!("Program invoked: {}", program.to_string()));
+        if let Some(count) = self.child_counts.last_mut() {
+            self.call_path.push(*count);
+            *count += 1;
+        }
+        self.child_counts.push(0);
+        self.tracer.emit(RuntimeEvent::InvokeStarted { program_id: program, stack_height: self.call_stack.len() });
+        self.push_msg(format!("Program invoked: {}", program));
     }
 
     pub(crate) fn log_program_succeed(&mut self) {
-        self.push_msg("Program consumed: 0 of 200000 compute units".to_string());
+        self.push_msg(format!("Program consumed: 0 of {} compute units", self.compute_budget.compute_unit_limit));
         self.push_msg("Program returned success".to_string());
+        self.trace_finished(&Ok(()));
+    }
+
+    pub(crate) fn log_fault_injected(&mut self, fault: InjectedFault) {
+        self.push_msg(format!("Program fault injected: \"{:?}\"", fault.error));
+        self.tracer.emit(RuntimeEvent::FaultInjected { fault: &fault });
+        self.injected_faults.push(fault);
     }
 
     pub(crate) fn log_program_failed(&mut self, err: ProgramError) {
         self.push_msg(format!("Program returned error: \"{:?}\"", err));
+        self.trace_finished(&Err(err));
+    }
+
+    pub(crate) fn record_diffs(&mut self, diffs: Vec<AccountDiff>) {
+        for diff in diffs.iter() {
+            self.tracer.emit(RuntimeEvent::AccountChanged { diff });
+        }
+        self.account_diffs = diffs;
+    }
+
+    fn trace_finished(&mut self, result: &ProgramResult) {
+        let program_id = self.active_program();
+        self.tracer.emit(RuntimeEvent::InvokeFinished {
+            program_id: &program_id,
+            stack_height: self.call_stack.len(),
+            result,
+        });
+        self.child_counts.pop();
+        self.call_path.pop();
+    }
+}
+
+impl fmt::Display for Receipt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut text = String::new();
+        self.write_plain(&mut text)?;
+        write!(f, "{}", self.labels.relabel(&text))
+    }
+}
+
+impl Receipt {
+    // Receipt with raw keys, Display substitutes labels
+    pub(crate) fn write_plain(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        match &self.transaction_error {
+            Some(err) => writeln!(f, "Receipt: Err({:?})", err)?,
+            None => writeln!(f, "Receipt: {:?}", self.result)?,
+        }
+        for msg in self.log_messages.iter() {
+            writeln!(f, "  {}", msg)?;
+        }
+        if let Some((program_id, data)) = &self.return_data() {
+            writeln!(f, "  Return data {}: {:?}", program_id, data)?;
+        }
+        if let Some(fee) = &self.fee {
+            writeln!(f, "  Fee: {} lamports paid by {}", fee.total(), fee.payer)?;
+        }
+        if let Some(peak) = self.heap_peak {
+            writeln!(f, "  Heap peak: {} bytes", peak)?;
+        }
+        for fault in self.injected_faults.iter() {
+            writeln!(f, "  Injected {:?}", fault)?;
+        }
+        for diff in self.account_diffs.iter() {
+            diff.write_plain(f)?;
+        }
+        Ok(())
     }
 }
 
 // +++++++++ Suit Methods +++++++++
 impl Receipt {
     pub fn expect_ok(&self) {
+        if let Some(err) = &self.transaction_error {
+            println!("{}", self);
+            println!("Transaction failed with {:?}", err);
+            assert_eq!(self.transaction_result(), Ok(()));
+        }
         if self.result.is_err() {
+            println!("{}", self);
             println!("Invoke failed with {:?}", self.result.clone().err().unwrap());
             assert_eq!(self.result, Ok(()));
         }
@@ -59,26 +209,151 @@ impl Receipt {
 
     pub fn expect_err(&self, err: ProgramError) {
         if self.result.is_ok() {
+            println!("{}", self);
             println!("Invoke succeed when must not");
             assert_ne!(self.result, Ok(()));
         }
 
         let got_err = self.result.clone().err().unwrap();
         if !got_err.eq(&err) {
+            println!("{}", self);
             println!("Invoke failed with different error {:?} != {:?}", got_err, err);
             assert_eq!(got_err, err);
         }
     }
 
     pub fn expect_any_err(&self) {
-        if self.result.is_ok() {
+        if self.result.is_ok() && self.transaction_error.is_none() {
+            println!("{}", self);
             println!("Invoke succeed when must not");
             assert_ne!(self.result, Ok(()));
         }
     }
 
+    pub fn transaction_result(&self) -> Result<(), TransactionError> {
+        match &self.transaction_error {
+            Some(err) => Err(err.clone()),
+            None => Ok(()),
+        }
+    }
+
+    pub fn expect_transaction_err(&self, err: TransactionError) {
+        if self.transaction_result() != Err(err.clone()) {
+            println!("{}", self);
+            println!("Transaction result {:?} != {:?}", self.transaction_result(), err);
+            assert_eq!(self.transaction_result(), Err(err));
+        }
+    }
+
+    pub fn compute_budget(&self) -> &ComputeBudget {
+        &self.compute_budget
+    }
+
+    pub fn fee(&self) -> Option<&FeeDetails> {
+        self.fee.as_ref()
+    }
+
+    pub fn expect_fee(&self, lamports: u64) {
+        let charged = self.fee.as_ref().map(|fee| fee.total()).unwrap_or(0);
+        if charged != lamports {
+            println!("{}", self);
+            println!("Fee charged {} lamports, expected {}", charged, lamports);
+            assert_eq!(charged, lamports);
+        }
+    }
+
+    pub fn injected_faults(&self) -> &[InjectedFault] {
+        self.injected_faults.as_slice()
+    }
+
+    pub fn expect_fault_injected(&self, program_id: &Pubkey) {
+        if !self.injected_faults.iter().any(|fault| fault.program_id.eq(program_id)) {
+            println!("{}", self);
+            println!("No fault injected into {}: {:?}", self.labels.display_key(program_id), self.injected_faults);
+            assert!(false);
+        }
+    }
+
+    pub fn heap_peak(&self) -> Option<usize> {
+        self.heap_peak
+    }
+
+    pub fn expect_heap_peak_below(&self, bytes: usize) {
+        let peak = self.heap_peak.expect("Heap is not tracked, enable it with RuntimeConfig::with_heap_tracking");
+        if peak >= bytes {
+            println!("{}", self);
+            println!("Heap peak {} bytes, expected below {}", peak, bytes);
+            assert!(peak < bytes);
+        }
+    }
+
+    pub fn account_diffs(&self) -> &[AccountDiff] {
+        self.account_diffs.as_slice()
+    }
+
+    pub fn diff(&self, pubkey: &Pubkey) -> Option<&AccountDiff> {
+        self.account_diffs.iter().find(|diff| diff.pubkey.eq(pubkey))
+    }
+
+    pub fn expect_unchanged(&self, pubkey: &Pubkey) {
+        if self.diff(pubkey).is_some() {
+            println!("{}", self);
+            println!("Account {} changed when must not", self.labels.display_key(pubkey));
+            assert!(false);
+        }
+    }
+
+    pub fn expect_lamports_delta(&self, pubkey: &Pubkey, delta: i128) {
+        let got = self.diff(pubkey).map(|diff| diff.lamports_delta()).unwrap_or(0);
+        if got != delta {
+            println!("{}", self);
+            println!("Account {} lamports changed by {} != {}", self.labels.display_key(pubkey), got, delta);
+            assert_eq!(got, delta);
+        }
+    }
+
+    // Return data as reported by the cluster, trailing zeros trimmed
+    pub fn return_data(&self) -> Option<(Pubkey, Vec<u8>)> {
+        let (program_id, data) = self.return_data.as_ref()?;
+        let end = data.iter().rposition(|byte| *byte != 0).map_or(0, |i| i + 1);
+        if end == 0 {
+            return None;
+        }
+        Some((*program_id, Vec::from(&data[..end])))
+    }
+
+    // Untrimmed bytes, as the caller of a CPI sees them
+    pub fn raw_return_data(&self) -> Option<(Pubkey, Vec<u8>)> {
+        self.return_data.clone()
+    }
+
+    pub fn return_data_as<T: BorshDeserialize>(&self) -> Option<T> {
+        self.return_data.as_ref().and_then(|(_, data)| T::try_from_slice(data).ok())
+    }
+
+    pub fn expect_return_data(&self, program_id: &Pubkey, data: &[u8]) {
+        let end = data.iter().rposition(|byte| *byte != 0).map_or(0, |i| i + 1);
+        let expected = Some((*program_id, Vec::from(&data[..end]))).filter(|_| end > 0);
+        let got = self.return_data();
+        if got != expected {
+            println!("{}", self);
+            println!("Return data not matches {:?} != {:?}", got, expected);
+            assert_eq!(got, expected);
+        }
+    }
+
+    pub fn expect_return_data_as<T: BorshDeserialize + PartialEq + fmt::Debug>(&self, value: &T) {
+        let got: Option<T> = self.return_data_as();
+        if got.as_ref() != Some(value) {
+            println!("{}", self);
+            println!("Return data not matches {:?} != {:?}", got, value);
+            assert_eq!(got.as_ref(), Some(value));
+        }
+    }
+
     pub fn expect_log(&self, msg: &str) {
         if !self.contains_program_log(msg) {
+            println!("{}", self);
             println!("Log not found {}", msg);
             assert!(false);
         }
@@ -86,7 +361,8 @@ impl Receipt {
 
     pub fn expect_data(&self, program_id: &Pubkey, data: &[&[u8]]) {
         if !self.contains_data(program_id, data) {
-            println!("Data log not found {}: {:?}", program_id.to_string(), data);
+            println!("{}", self);
+            println!("Data log not found {}: {:?}", self.labels.display_key(program_id), data);
             assert!(false);
         }
     }
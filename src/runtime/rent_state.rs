@@ -0,0 +1,34 @@
+use solana_program::rent::Rent;
+
+
+// Rent class of an account, as the runtime checks it once an instruction completes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RentState {
+    Uninitialized,
+    RentPaying { data_size: usize, lamports: u64 },
+    RentExempt,
+}
+
+impl RentState {
+    pub fn from_balance(lamports: u64, data_size: usize, rent: &Rent) -> Self {
+        if lamports == 0 {
+            RentState::Uninitialized
+        } else if rent.is_exempt(lamports, data_size) {
+            RentState::RentExempt
+        } else {
+            RentState::RentPaying { data_size, lamports }
+        }
+    }
+
+    // Accounts may only stay rent paying, without growing or receiving lamports
+    pub fn transition_allowed_from(&self, pre: &RentState) -> bool {
+        match (self, pre) {
+            (RentState::Uninitialized | RentState::RentExempt, _) => true,
+            (
+                RentState::RentPaying { data_size: post_size, lamports: post_lamports },
+                RentState::RentPaying { data_size: pre_size, lamports: pre_lamports },
+            ) => post_size == pre_size && post_lamports <= pre_lamports,
+            (RentState::RentPaying { .. }, _) => false,
+        }
+    }
+}
@@ -1,70 +1,166 @@
+use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
-use std::sync::atomic::Ordering::Relaxed;
+use std::mem;
+use std::rc::Rc;
+use std::sync::Arc;
 use solana_program::{
+    entrypoint::ProgramResult,
     pubkey::Pubkey,
-    instruction::AccountMeta,
     account_info::AccountInfo
 };
+use solana_program::program_error::ProgramError;
 use crate::executor::Package;
 use crate::runtime::{
     ClusterSettings,
+    DataBuffer,
+    FaultInjector,
+    Frame,
+    HeapFrame,
+    LayoutRegistry,
     meta::Meta,
-    Receipt
+    Receipt,
+    RuntimeConfig,
+    Tracer,
 };
 
 
 pub struct Scope {
-    pub(crate) metas: HashMap<Pubkey, Meta>,
+    pub(crate) packages: HashMap<Pubkey, Arc<dyn Package>>,
+    pub(crate) frames: Vec<Vec<DataBuffer>>, // Data of the accounts of each running invocation
     pub(crate) receipt: Receipt,
     pub(crate) settings: ClusterSettings,
+    pub(crate) faults: FaultInjector,
+    pub(crate) layouts: LayoutRegistry,
+    pub(crate) heap_size: Option<usize>,
 }
 
 impl Scope {
     pub fn new(metas: &Vec<(Pubkey, Meta)>) -> Self {
+        Self::with_config(metas, RuntimeConfig::new())
+    }
+
+    pub fn with_config(metas: &Vec<(Pubkey, Meta)>, config: RuntimeConfig) -> Self {
+        let mut tracer = Tracer::new(config.verbosity, config.observers);
+        tracer.labels = config.labels;
+        for (pubkey, meta) in metas.iter() {
+            if let Some(label) = &meta.label {
+                tracer.labels.set(pubkey, label);
+            }
+        }
+        let mut receipt = Receipt::with_tracer(tracer);
+        receipt.timestamps = vec![config.settings.unix_timestamp, config.settings.epoch_start_timestamp];
+
         Self {
-            metas: metas.iter().map(|(key, meta)| (key.clone(), meta.clone())).collect(),
-            receipt: Receipt::new(),
-            settings: ClusterSettings::new(),
+            packages: metas.iter()
+                .filter_map(|(key, meta)| Some((*key, meta.package.clone()?)))
+                .collect(),
+            frames: Vec::new(),
+            receipt,
+            settings: config.settings,
+            faults: config.faults,
+            layouts: config.layouts,
+            heap_size: config.heap_size,
         }
     }
 
-    pub fn clone(&self) -> Box<Self> {
-        unsafe {
-            Box::from_raw(self as *const Scope as *mut Scope)
+    pub fn get_package(&self, pubkey: &Pubkey) -> Arc<dyn Package> {
+        match self.packages.get(pubkey) {
+            Some(package) => package.clone(),
+            None => panic!("Trying to call non-executable {}", pubkey),
         }
     }
 
-    pub fn replicate_info<'a>(&self, account_info: &'a AccountInfo<'a>, account_meta: &'a AccountMeta, copy: bool) -> AccountInfo<'a> {
-        let meta = self.metas.get(&account_meta.pubkey)
-            .expect(&format!("Undefined Account {}", account_meta.pubkey.to_string()));
+    // Runs one top-level instruction, as the first frame of the call stack
+    pub(crate) fn process_instruction(&mut self, program_id: &Pubkey, package: &dyn Package, instruction_data: &[u8], frame: &mut Frame) -> ProgramResult {
+        self.receipt.call_stack.push(*program_id);
+        self.receipt.log_program_invoked(program_id);
+        self.receipt.return_data = None;
 
-        if copy {
-            meta.as_info_with_meta(account_info.key, account_meta.is_signer, account_meta.is_writable)
-        }
-        else {
-            meta.shallow_copy_info(account_info, account_meta.is_signer, account_meta.is_writable)
+        let result = self.run_frame(frame, program_id, instruction_data, |scope, infos, program_id, instruction_data| {
+            let heap = scope.enter_heap_frame();
+            let result = package.execute(infos, instruction_data, program_id, scope);
+            scope.exit_heap_frame(heap);
+            result
+        });
+        let result = match self.receipt.take_abort() {
+            Some(err) => Err(err),
+            None => result,
+        };
+
+        match &result {
+            Ok(_) => { self.receipt.log_program_succeed(); }
+            Err(err) => { self.receipt.log_program_failed(err.clone()); }
         }
+        self.receipt.call_stack.pop();
+        result
     }
 
-    pub fn get_info<'a>(&self, pubkey: &'a Pubkey) -> AccountInfo<'a> {
-        let meta = self.metas.get(pubkey)
-            .expect(&format!("Undefined Account {}", pubkey.to_string()));
+    // Hands the frame accounts to the closure as infos, what it leaves in them is copied back whatever the result.
+    // The program id and instruction data are passed along to share the lifetime of the infos
+    pub(crate) fn run_frame<F>(&mut self, frame: &mut Frame, program_id: &Pubkey, instruction_data: &[u8], run: F) -> ProgramResult
+        where F: for<'i> FnOnce(&mut Scope, &'i [AccountInfo<'i>], &'i Pubkey, &'i [u8]) -> ProgramResult
+    {
+        let mut bytes: Vec<Vec<u8>> = frame.accounts.iter_mut().map(|account| mem::take(&mut account.data)).collect();
+        let mut lamports: Vec<u64> = frame.accounts.iter().map(|account| account.lamports).collect();
+        let owners: Vec<Rc<Cell<Pubkey>>> = frame.accounts.iter().map(|account| Rc::new(Cell::new(account.owner))).collect();
+
+        let result = {
+            let (buffers, data): (Vec<DataBuffer>, Vec<&mut [u8]>) = bytes.iter_mut().map(DataBuffer::new).unzip();
+            self.frames.push(buffers);
+            let data: Vec<Rc<RefCell<&mut [u8]>>> = data.into_iter()
+                .map(|data| Rc::new(RefCell::new(data)))
+                .collect();
+            let lamports: Vec<Rc<RefCell<&mut u64>>> = lamports.iter_mut()
+                .map(|lamports| Rc::new(RefCell::new(lamports)))
+                .collect();
+            let infos: Vec<AccountInfo> = frame.privileges.iter()
+                .map(|(index, is_signer, is_writable)| AccountInfo {
+                    key: &frame.accounts[*index].pubkey,
+                    lamports: lamports[*index].clone(),
+                    data: data[*index].clone(),
+                    owner: owners[*index].clone(),
+                    rent_epoch: 0,
+                    is_signer: *is_signer,
+                    is_writable: *is_writable,
+                    executable: frame.accounts[*index].executable,
+                })
+                .collect();
+
+            run(self, &infos, program_id, instruction_data)
+        };
+
+        let buffers = self.frames.pop().unwrap();
+        for (((account, buffer), bytes), index) in frame.accounts.iter_mut().zip(buffers).zip(bytes).zip(0..) {
+            account.lamports = lamports[index];
+            account.owner = owners[index].get();
+            account.data = buffer.into_data(bytes);
+        }
+        result
+    }
 
-        meta.as_info(pubkey)
+    pub(crate) fn enter_heap_frame(&self) -> Option<HeapFrame> {
+        self.heap_size.map(|_| HeapFrame::enter())
     }
 
-    pub fn get_package<'a>(&self, pubkey: &Pubkey) -> &'a Box<dyn Package> {
-        let meta = self.metas.get(pubkey)
-            .expect(&format!("Undefined Account {}", pubkey.to_string()));
-        if !meta.executable {
-            panic!("Trying to call non-executable {}", pubkey.to_string());
+    // Allocations past the limit are served natively, the invocation fails once it returns
+    pub(crate) fn exit_heap_frame(&mut self, frame: Option<HeapFrame>) {
+        let (frame, limit) = match (frame, self.heap_size) {
+            (Some(frame), Some(limit)) => (frame, limit),
+            _ => return,
+        };
+        let used = frame.exit();
+        self.receipt.record_heap_usage(used);
+        if used > limit {
+            self.receipt.abort(
+                format!("memory allocation failed, out of memory ({} > {} bytes)", used, limit),
+                ProgramError::InvalidArgument,
+            );
         }
-        meta.as_package()
     }
 }
 
-impl Into<Receipt> for Scope {
-    fn into(self) -> Receipt {
-        self.receipt
+impl From<Scope> for Receipt {
+    fn from(scope: Scope) -> Self {
+        scope.receipt
     }
 }
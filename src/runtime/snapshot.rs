@@ -0,0 +1,166 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use regex::Regex;
+use serde_json::{json, Value};
+use solana_program::pubkey::Pubkey;
+use crate::runtime::Receipt;
+
+
+// FULGURITE_UPDATE_SNAPSHOTS=1 rewrites snapshot files instead of comparing them
+pub const UPDATE_SNAPSHOTS_ENV: &str = "FULGURITE_UPDATE_SNAPSHOTS";
+pub const SNAPSHOT_DIR_ENV: &str = "FULGURITE_SNAPSHOT_DIR";
+
+impl Receipt {
+    // Stable JSON form, labels substituted, unlabeled keys and timestamps normalized
+    pub fn to_snapshot(&self) -> String {
+        let snapshot = json!({
+            "result": format!("{:?}", self.result),
+            "call_tree": self.call_tree(),
+            "logs": self.log_messages,
+            "data": self.log_datas.iter()
+                .map(|(program_id, data)| json!({"program": program_id.to_string(), "data": data}))
+                .collect::<Vec<Value>>(),
+            "return_data": self.return_data().as_ref()
+                .map(|(program_id, data)| json!({"program": program_id.to_string(), "data": data})),
+            "injected_faults": self.injected_faults.iter()
+                .map(|fault| json!({
+                    "program": fault.program_id.to_string(),
+                    "call_index": fault.call_index,
+                    "stack_height": fault.stack_height,
+                    "error": format!("{:?}", fault.error),
+                    "mode": format!("{:?}", fault.mode),
+                }))
+                .collect::<Vec<Value>>(),
+            "account_diffs": self.account_diffs.iter()
+                .map(|diff| json!({
+                    "account": diff.pubkey.to_string(),
+                    "lamports": [diff.lamports.0, diff.lamports.1],
+                    "owner": [diff.owner.0.to_string(), diff.owner.1.to_string()],
+                    "data_len": [diff.data_len.0, diff.data_len.1],
+                    "data": diff.data.iter()
+                        .map(|range| json!({"offset": range.offset, "before": range.before, "after": range.after}))
+                        .collect::<Vec<Value>>(),
+                    "fields": diff.fields.iter()
+                        .map(|field| json!({"name": field.name, "before": field.before, "after": field.after}))
+                        .collect::<Vec<Value>>(),
+                }))
+                .collect::<Vec<Value>>(),
+        });
+
+        let text = serde_json::to_string_pretty(&snapshot).unwrap();
+        normalize_keys(&self.normalize_timestamps(&self.labels.relabel(&text)))
+    }
+
+    pub fn assert_snapshot(&self, name: &str) {
+        self.assert_snapshot_in(snapshot_dir(), name);
+    }
+
+    pub fn assert_snapshot_in<P: AsRef<Path>>(&self, dir: P, name: &str) {
+        let path = dir.as_ref().join(format!("{}.snap.json", name));
+        let actual = self.to_snapshot();
+
+        if std::env::var(UPDATE_SNAPSHOTS_ENV).map(|value| !value.is_empty() && value != "0").unwrap_or(false) {
+            std::fs::create_dir_all(dir.as_ref()).expect("Failed to create snapshot directory");
+            std::fs::write(&path, &actual).expect("Failed to write snapshot");
+            return;
+        }
+
+        let expected = match std::fs::read_to_string(&path) {
+            Ok(expected) => expected,
+            Err(_) => {
+                println!("{}", actual);
+                println!("Snapshot {} not found, run with {}=1 to create it", path.display(), UPDATE_SNAPSHOTS_ENV);
+                panic!("Missing snapshot {}", name);
+            }
+        };
+
+        if expected.trim_end() != actual.trim_end() {
+            print_line_diff(&expected, &actual);
+            println!("Snapshot {} not matches, run with {}=1 to update it", path.display(), UPDATE_SNAPSHOTS_ENV);
+            assert_eq!(expected.trim_end(), actual.trim_end());
+        }
+    }
+
+    // Nested invocations rebuilt from the invoke/return log lines
+    fn call_tree(&self) -> Vec<Value> {
+        let mut stack: Vec<(String, Vec<Value>)> = Vec::new();
+        let mut roots: Vec<Value> = Vec::new();
+
+        for msg in self.log_messages.iter() {
+            if let Some(program) = msg.strip_prefix("Program invoked: ") {
+                stack.push((program.to_string(), Vec::new()));
+                continue;
+            }
+
+            let result = if msg.eq("Program returned success") {
+                "success".to_string()
+            } else if let Some(err) = msg.strip_prefix("Program returned error: ") {
+                err.trim_matches('"').to_string()
+            } else {
+                continue;
+            };
+
+            if let Some((program, calls)) = stack.pop() {
+                let node = json!({"program": program, "result": result, "calls": calls});
+                match stack.last_mut() {
+                    Some((_, parent_calls)) => parent_calls.push(node),
+                    None => roots.push(node),
+                }
+            }
+        }
+        roots
+    }
+
+    fn normalize_timestamps(&self, text: &str) -> String {
+        let mut text = text.to_string();
+        for timestamp in self.timestamps.iter().filter(|timestamp| **timestamp != 0) {
+            let pattern = Regex::new(&format!(r"\b{}\b", timestamp)).unwrap();
+            text = pattern.replace_all(&text, "<timestamp>").to_string();
+        }
+        text
+    }
+}
+
+// Random keys are numbered by first appearance, so reruns produce the same text
+fn normalize_keys(text: &str) -> String {
+    let pattern = Regex::new(r"\b[1-9A-HJ-NP-Za-km-z]{32,44}\b").unwrap();
+    let mut numbers: HashMap<String, usize> = HashMap::new();
+
+    pattern.replace_all(text, |captures: &regex::Captures| {
+        let key = captures[0].to_string();
+        if Pubkey::from_str(&key).is_err() {
+            return key;
+        }
+        let next = numbers.len() + 1;
+        format!("<key:{}>", numbers.entry(key).or_insert(next))
+    }).to_string()
+}
+
+fn snapshot_dir() -> PathBuf {
+    if let Ok(dir) = std::env::var(SNAPSHOT_DIR_ENV) {
+        return PathBuf::from(dir);
+    }
+    std::env::var("CARGO_MANIFEST_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from("."))
+        .join("snapshots")
+}
+
+fn print_line_diff(expected: &str, actual: &str) {
+    let expected: Vec<&str> = expected.lines().collect();
+    let actual: Vec<&str> = actual.lines().collect();
+    for i in 0..expected.len().max(actual.len()) {
+        match (expected.get(i), actual.get(i)) {
+            (Some(a), Some(b)) if a == b => {}
+            (a, b) => {
+                if let Some(a) = a {
+                    println!("{:>4} - {}", i + 1, a);
+                }
+                if let Some(b) = b {
+                    println!("{:>4} + {}", i + 1, b);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,277 @@
+use std::borrow::Cow;
+use solana_program::{
+    address_lookup_table::{self, state::AddressLookupTable},
+    clock::Slot,
+    instruction::{Instruction, InstructionError},
+    message::{v0::{self, LoadedAddresses, LoadedMessage}, Message, VersionedMessage},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+use crate::runtime::{check_transaction_limits, fees::charge_fee, AccountSnapshot, Frame, FrameAccount, Signature, ComputeBudget, Meta, Receipt, RuntimeConfig, Scope};
+
+
+// Failures reported for the transaction as a whole, named as the runtime names them
+#[derive(Clone, Debug, PartialEq)]
+pub enum TransactionError {
+    AccountNotFound,
+    ProgramAccountNotFound,
+    SanitizeFailure,
+    SignatureFailure,
+    BlockhashNotFound,
+    InvalidProgramForExecution,
+    AccountLoadedTwice,
+    TooManyAccountLocks,
+    AddressLookupTableNotFound,
+    InvalidAddressLookupTableOwner,
+    InvalidAddressLookupTableData,
+    InvalidAddressLookupTableIndex,
+    InstructionError(u8, ProgramError),
+    DuplicateInstruction(u8),
+    InsufficientFundsForRent { account_index: u8 },
+    InsufficientFundsForFee,
+    InvalidAccountForFee,
+    InvalidLoadedAccountsDataSizeLimit,
+    MaxLoadedAccountsDataSizeExceeded,
+}
+
+// Instructions executed in order against the same accounts, all or nothing
+pub struct Transaction {
+    pub(crate) instructions: Vec<Instruction>,
+    pub(crate) payer: Option<Pubkey>,
+    pub(crate) message: Option<VersionedMessage>, // Set when decoded from the wire, privileges come from it
+    pub(crate) signatures: Vec<Signature>,
+}
+
+impl Transaction {
+    pub fn new(instructions: &[Instruction]) -> Self {
+        Self::new_with_payer(instructions, None)
+    }
+
+    // Without a payer, here or in the config, the transaction runs for free
+    pub fn new_with_payer(instructions: &[Instruction], payer: Option<&Pubkey>) -> Self {
+        Self {
+            instructions: instructions.to_vec(),
+            payer: payer.cloned(),
+            message: None,
+            signatures: Vec::new(),
+        }
+    }
+
+    // Empty for v0 messages until executed, their accounts come from lookup tables
+    pub fn instructions(&self) -> &[Instruction] {
+        self.instructions.as_slice()
+    }
+
+    pub fn execute(&self, accounts: Vec<(Pubkey, Meta)>) -> Receipt {
+        self.execute_with_config(accounts, RuntimeConfig::new())
+    }
+
+    // Signer and writable flags of the metas apply to every instruction, as message flags do
+    pub fn execute_with_config(&self, accounts: Vec<(Pubkey, Meta)>, config: RuntimeConfig) -> Receipt {
+        let payer = self.payer.or(config.fee_payer);
+        let blockhash = config.settings.blockhash;
+        let (instructions, message, locked_keys, mut accounts) = match &self.message {
+            None => {
+                // Compiled as the sdk would send it, to find out whether it fits
+                let message = Message::new_with_blockhash(&self.instructions, payer.as_ref(), &blockhash);
+                let keys = message.account_keys.clone();
+                (Cow::Borrowed(&self.instructions), Cow::Owned(VersionedMessage::Legacy(message)), keys, merge_accounts(accounts))
+            }
+            Some(message) => match resolve_message(message, &accounts, config.settings.slot) {
+                Ok((instructions, keys)) => {
+                    let locked_keys = keys.iter().map(|(key, _, _)| *key).collect();
+                    (Cow::Owned(instructions), Cow::Borrowed(message), locked_keys, message_accounts(&keys, accounts))
+                }
+                Err(err) => {
+                    let mut scope = Box::new(Scope::with_config(&Vec::new(), config));
+                    scope.receipt.fail_transaction(err);
+                    return scope.receipt;
+                }
+            },
+        };
+        let mut scope = Box::new(Scope::with_config(&accounts, config));
+
+        if let Err(err) = check_transaction_limits(&mut scope.receipt, &message, &locked_keys, &scope.settings) {
+            scope.receipt.fail_transaction(err);
+            return scope.receipt;
+        }
+
+        if self.message.as_ref().map(|message| message.recent_blockhash() != &blockhash).unwrap_or(false) {
+            scope.receipt.fail_transaction(TransactionError::BlockhashNotFound);
+            return scope.receipt;
+        }
+
+        let budget = match ComputeBudget::from_instructions(&instructions) {
+            Ok(budget) => budget,
+            Err(err) => {
+                scope.receipt.fail_transaction(err);
+                return scope.receipt;
+            }
+        };
+        if let Some(heap_size) = scope.heap_size {
+            scope.heap_size = Some(heap_size.max(budget.heap_size as usize));
+        }
+        scope.receipt.compute_budget = budget;
+
+        if let Err(err) = self.load(&instructions, &accounts, &scope.receipt.compute_budget) {
+            scope.receipt.fail_transaction(err);
+            return scope.receipt;
+        }
+
+        let snapshot = AccountSnapshot::capture(&accounts);
+        if let Some(payer) = payer {
+            match charge_fee(&payer, &mut accounts, &scope.settings, &scope.receipt.compute_budget) {
+                Ok(fee) => scope.receipt.record_fee(fee),
+                Err(err) => {
+                    scope.receipt.fail_transaction(err);
+                    return scope.receipt;
+                }
+            }
+        }
+
+        let rollback = AccountSnapshot::capture(&accounts);
+        for (index, instruction) in instructions.iter().enumerate() {
+            if let Err(err) = execute_instruction(&mut scope, instruction, &mut accounts) {
+                scope.receipt.fail_transaction(TransactionError::InstructionError(index as u8, err));
+                break;
+            }
+        }
+
+        if scope.receipt.transaction_error.is_none() {
+            if let Some(pubkey) = snapshot.rent_violation(&accounts, &scope.settings.as_rent()) {
+                let account_index = accounts.iter().position(|(key, _)| key.eq(&pubkey)).unwrap() as u8;
+                scope.receipt.push_msg(format!("Account {} has insufficient funds for rent", pubkey));
+                scope.receipt.fail_transaction(TransactionError::InsufficientFundsForRent { account_index });
+            }
+        }
+        if scope.receipt.transaction_error.is_some() {
+            rollback.restore(&mut accounts);
+        }
+        let diffs = snapshot.diff(&accounts, &scope.layouts);
+        scope.receipt.record_diffs(diffs);
+
+        scope.receipt
+    }
+
+    // Every referenced account must be provided before anything runs
+    fn load(&self, instructions: &[Instruction], accounts: &[(Pubkey, Meta)], budget: &ComputeBudget) -> Result<(), TransactionError> {
+        let find = |pubkey: &Pubkey| accounts.iter().find(|(key, _)| key.eq(pubkey)).map(|(_, meta)| meta);
+        if let Some(message) = &self.message {
+            if message.static_account_keys().iter().any(|pubkey| find(pubkey).is_none()) {
+                return Err(TransactionError::AccountNotFound);
+            }
+        }
+        for instruction in instructions.iter() {
+            match find(&instruction.program_id) {
+                None => return Err(TransactionError::ProgramAccountNotFound),
+                Some(meta) if !meta.executable => return Err(TransactionError::InvalidProgramForExecution),
+                Some(_) => {}
+            }
+            if instruction.accounts.iter().any(|account| find(&account.pubkey).is_none()) {
+                return Err(TransactionError::AccountNotFound);
+            }
+        }
+
+        let loaded_size: usize = accounts.iter().map(|(_, meta)| meta.get_data().len()).sum();
+        if loaded_size > budget.loaded_accounts_data_size_limit as usize {
+            return Err(TransactionError::MaxLoadedAccountsDataSizeExceeded);
+        }
+        Ok(())
+    }
+}
+
+fn execute_instruction(scope: &mut Scope, instruction: &Instruction, accounts: &mut [(Pubkey, Meta)]) -> Result<(), ProgramError> {
+    let mut frame = Frame::default();
+    for account_meta in instruction.accounts.iter() {
+        let (key, meta) = accounts.iter().find(|(key, _)| key.eq(&account_meta.pubkey)).unwrap();
+        if account_meta.is_signer && !meta.is_signer {
+            scope.receipt.push_msg(format!("Signer Privilege escalated for {}", key));
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if account_meta.is_writable && !meta.is_writable {
+            scope.receipt.push_msg(format!("Writable Privilege escalated for {}", key));
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        frame.add(FrameAccount::from_meta(key, meta), account_meta.is_signer, account_meta.is_writable);
+    }
+
+    let (_, program) = accounts.iter().find(|(key, _)| key.eq(&instruction.program_id)).unwrap();
+    let package = program.package.clone().unwrap();
+    let result = scope.process_instruction(&instruction.program_id, package.as_ref(), &instruction.data, &mut frame);
+    frame.store(accounts);
+    result
+}
+
+// Key of a message with its (signer, writable) flags
+type MessageKey = (Pubkey, bool, bool);
+
+// Instructions of the message and its keys, lookup table addresses included
+fn resolve_message(message: &VersionedMessage, accounts: &[(Pubkey, Meta)], slot: Slot) -> Result<(Vec<Instruction>, Vec<MessageKey>), TransactionError> {
+    match message {
+        VersionedMessage::Legacy(message) => {
+            let keys = message.account_keys.iter()
+                .enumerate()
+                .map(|(index, pubkey)| (*pubkey, message.is_signer(index), message.is_writable(index)))
+                .collect();
+            Ok((message.decompile_instructions(), keys))
+        }
+        VersionedMessage::V0(message) => {
+            let message = LoadedMessage::new(message.clone(), load_addresses(message, accounts, slot)?);
+            let keys = message.account_keys().into_iter()
+                .enumerate()
+                .map(|(index, pubkey)| (pubkey, message.is_signer(index), message.is_writable(index)))
+                .collect();
+            Ok((message.decompile_instructions(), keys))
+        }
+    }
+}
+
+// Lookup tables are read as of the slot of the cluster settings, they need not be among the message keys
+fn load_addresses(message: &v0::Message, accounts: &[(Pubkey, Meta)], slot: Slot) -> Result<LoadedAddresses, TransactionError> {
+    let mut loaded = LoadedAddresses::default();
+    for lookup in message.address_table_lookups.iter() {
+        let (_, meta) = accounts.iter()
+            .find(|(key, _)| key.eq(&lookup.account_key))
+            .ok_or(TransactionError::AddressLookupTableNotFound)?;
+        if !meta.get_owner().eq(&address_lookup_table::ID) {
+            return Err(TransactionError::InvalidAddressLookupTableOwner);
+        }
+        let table = AddressLookupTable::deserialize(meta.get_data())
+            .map_err(|_| TransactionError::InvalidAddressLookupTableData)?;
+        let lookup_error = |err: InstructionError| match err {
+            InstructionError::InvalidArgument => TransactionError::AddressLookupTableNotFound, // Deactivated
+            _ => TransactionError::InvalidAddressLookupTableIndex,
+        };
+        loaded.writable.extend(table.lookup(slot, &lookup.writable_indexes).map_err(lookup_error)?);
+        loaded.readonly.extend(table.lookup(slot, &lookup.readonly_indexes).map_err(lookup_error)?);
+    }
+    Ok(loaded)
+}
+
+// Accounts in message order, flagged as the message flags them, missing ones are caught by load
+fn message_accounts<'a>(keys: &[MessageKey], mut accounts: Vec<(Pubkey, Meta<'a>)>) -> Vec<(Pubkey, Meta<'a>)> {
+    keys.iter()
+        .filter_map(|(pubkey, is_signer, is_writable)| {
+            let index = accounts.iter().position(|(key, _)| key.eq(pubkey))?;
+            let (_, mut meta) = accounts.swap_remove(index);
+            meta.is_signer = *is_signer;
+            meta.is_writable = *is_writable;
+            Some((*pubkey, meta))
+        })
+        .collect()
+}
+
+// One entry per key, a key passed twice gets the union of its flags
+fn merge_accounts(accounts: Vec<(Pubkey, Meta)>) -> Vec<(Pubkey, Meta)> {
+    let mut merged: Vec<(Pubkey, Meta)> = Vec::with_capacity(accounts.len());
+    for (pubkey, meta) in accounts.into_iter() {
+        match merged.iter_mut().find(|(key, _)| key.eq(&pubkey)) {
+            Some((_, existing)) => {
+                existing.is_signer |= meta.is_signer;
+                existing.is_writable |= meta.is_writable;
+            }
+            None => merged.push((pubkey, meta)),
+        }
+    }
+    merged
+}
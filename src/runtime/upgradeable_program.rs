@@ -0,0 +1,51 @@
+use solana_program::{
+    bpf_loader_upgradeable::{self, get_program_data_address, UpgradeableLoaderState},
+    pubkey::Pubkey,
+};
+use crate::executor::{PackageRegistry, UpgradeableLoaderPackage, UpgradeablePackage};
+use crate::runtime::{Account, Program};
+use crate::suit::rent_exempt_for_size;
+
+
+impl Program {
+    pub fn upgradeable_loader() -> Self {
+        Self::with_package(Box::new(UpgradeableLoaderPackage {}), bpf_loader_upgradeable::ID)
+    }
+
+    // Program running the package its program data account holds when loaded
+    pub(crate) fn upgradeable_from(pubkey: Pubkey, programdata: &Account, packages: &PackageRegistry) -> Self {
+        let mut program = Self::with_package(
+            Box::new(UpgradeablePackage::new(&programdata.data, packages)),
+            pubkey,
+        );
+        program.owner = bpf_loader_upgradeable::ID;
+        program.proxy_data = bincode::serialize(&UpgradeableLoaderState::Program {
+            programdata_address: programdata.pubkey,
+        }).unwrap();
+        program
+    }
+}
+
+impl Account {
+    // Program data as `solana program deploy` leaves it, the handle stands for the program bytes
+    pub(crate) fn new_program_data(program_id: &Pubkey, handle: Vec<u8>, upgrade_authority: Option<&Pubkey>) -> Self {
+        let mut bytes = bincode::serialize(&UpgradeableLoaderState::ProgramData {
+            slot: 0,
+            upgrade_authority_address: upgrade_authority.cloned(),
+        }).unwrap();
+        bytes.resize(UpgradeableLoaderState::size_of_programdata_metadata(), 0); // Immutable programs too
+        bytes.extend(handle);
+
+        Account::new(get_program_data_address(program_id), rent_exempt_for_size(bytes.len()), &bpf_loader_upgradeable::ID, bytes)
+    }
+
+    // Buffer account ready to be passed to the Upgrade instruction
+    pub(crate) fn new_program_buffer(pubkey: Pubkey, handle: Vec<u8>, authority: &Pubkey) -> Self {
+        let mut bytes = bincode::serialize(&UpgradeableLoaderState::Buffer {
+            authority_address: Some(*authority),
+        }).unwrap();
+        bytes.extend(handle);
+
+        Account::new(pubkey, rent_exempt_for_size(bytes.len()), &bpf_loader_upgradeable::ID, bytes)
+    }
+}
@@ -0,0 +1,155 @@
+use std::fmt;
+use ed25519_dalek::{Keypair, PublicKey, Signer};
+use solana_program::{
+    address_lookup_table::AddressLookupTableAccount,
+    hash::Hash,
+    instruction::Instruction,
+    message::{v0, Message, VersionedMessage},
+    pubkey::Pubkey,
+    sanitize::Sanitize,
+    short_vec::{decode_shortu16_len, ShortU16},
+};
+use crate::runtime::{Transaction, TransactionError, PACKET_DATA_SIZE};
+
+
+pub const SIGNATURE_BYTES: usize = 64;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Signature(pub [u8; SIGNATURE_BYTES]);
+
+impl Signature {
+    pub fn as_bytes(&self) -> &[u8; SIGNATURE_BYTES] {
+        &self.0
+    }
+
+    fn verify(&self, pubkey: &Pubkey, message: &[u8]) -> bool {
+        let signature = match ed25519_dalek::Signature::from_bytes(&self.0) {
+            Ok(signature) => signature,
+            Err(_) => return false,
+        };
+        PublicKey::from_bytes(pubkey.as_ref())
+            .map(|key| key.verify_strict(message, &signature).is_ok())
+            .unwrap_or(false)
+    }
+}
+
+impl fmt::Display for Signature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", bs58::encode(self.0).into_string())
+    }
+}
+
+impl fmt::Debug for Signature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+
+impl Transaction {
+    // Bincode layout of the sdk Transaction: short_vec of signatures, then the message they sign
+    pub fn from_wire(bytes: &[u8]) -> Result<Self, TransactionError> {
+        Self::decode_wire(bytes, true)
+    }
+
+    // Signatures are kept but not checked, as simulations without sigVerify do
+    pub(crate) fn from_wire_unverified(bytes: &[u8]) -> Result<Self, TransactionError> {
+        Self::decode_wire(bytes, false)
+    }
+
+    fn decode_wire(bytes: &[u8], verify: bool) -> Result<Self, TransactionError> {
+        if bytes.len() > PACKET_DATA_SIZE {
+            return Err(TransactionError::SanitizeFailure);
+        }
+        let (count, offset) = decode_shortu16_len(bytes).map_err(|_| TransactionError::SanitizeFailure)?;
+        let message_start = offset + count * SIGNATURE_BYTES;
+        if bytes.len() < message_start {
+            return Err(TransactionError::SanitizeFailure);
+        }
+        let signatures: Vec<Signature> = bytes[offset..message_start]
+            .chunks(SIGNATURE_BYTES)
+            .map(|chunk| Signature(chunk.try_into().unwrap()))
+            .collect();
+
+        let message_bytes = &bytes[message_start..];
+        let message = VersionedMessage::deserialize(message_bytes).map_err(|_| TransactionError::SanitizeFailure)?;
+        if message.sanitize().is_err() {
+            return Err(TransactionError::SanitizeFailure);
+        }
+        if signatures.len() != message.header().num_required_signatures as usize {
+            return Err(TransactionError::SanitizeFailure);
+        }
+        for (signature, pubkey) in signatures.iter().zip(message.static_account_keys().iter()) {
+            if verify && !signature.verify(pubkey, message_bytes) {
+                return Err(TransactionError::SignatureFailure);
+            }
+        }
+
+        // Instructions of a v0 message are only known once its lookup tables are loaded
+        let instructions = match &message {
+            VersionedMessage::Legacy(message) => message.decompile_instructions(),
+            VersionedMessage::V0(_) => Vec::new(),
+        };
+        Ok(Self {
+            instructions,
+            payer: message.static_account_keys().first().cloned(),
+            message: Some(message),
+            signatures,
+        })
+    }
+
+    // Signed as the sdk does, then decoded as the cluster would receive it
+    pub fn new_signed_with_payer(instructions: &[Instruction], payer: Option<&Pubkey>, signers: &[&Keypair], recent_blockhash: Hash) -> Self {
+        let mut transaction = Self::new_with_payer(instructions, payer);
+        transaction.sign(signers, recent_blockhash);
+        transaction
+    }
+
+    // Executions check the signed blockhash against the cluster's
+    pub fn sign(&mut self, signers: &[&Keypair], recent_blockhash: Hash) {
+        let wire = self.to_wire(&recent_blockhash, signers);
+        *self = Self::from_wire(&wire).unwrap_or_else(|err| panic!("Failed to sign transaction: {:?}", err));
+    }
+
+    // Compiles a legacy message and signs it, each required signer must be among the keypairs
+    pub fn to_wire(&self, blockhash: &Hash, signers: &[&Keypair]) -> Vec<u8> {
+        let payer = self.fee_payer(signers);
+        let message = Message::new_with_blockhash(&self.instructions, payer.as_ref(), blockhash);
+        sign(&VersionedMessage::Legacy(message), signers)
+    }
+
+    // Compiles a v0 message, accounts found in the tables are loaded from them instead of listed
+    pub fn to_wire_v0(&self, blockhash: &Hash, signers: &[&Keypair], tables: &[AddressLookupTableAccount]) -> Vec<u8> {
+        let payer = self.fee_payer(signers).expect("A v0 message requires a payer");
+        let message = v0::Message::try_compile(&payer, &self.instructions, tables, *blockhash)
+            .unwrap_or_else(|err| panic!("Failed to compile v0 message: {:?}", err));
+        sign(&VersionedMessage::V0(message), signers)
+    }
+
+    pub fn signatures(&self) -> &[Signature] {
+        self.signatures.as_slice()
+    }
+
+    pub fn message(&self) -> Option<&VersionedMessage> {
+        self.message.as_ref()
+    }
+
+    fn fee_payer(&self, signers: &[&Keypair]) -> Option<Pubkey> {
+        self.payer.or_else(|| signers.first().map(|keypair| Pubkey::new_from_array(keypair.public.to_bytes())))
+    }
+}
+
+fn sign(message: &VersionedMessage, signers: &[&Keypair]) -> Vec<u8> {
+    let message_bytes = message.serialize();
+    let num_required_signatures = message.header().num_required_signatures as usize;
+
+    let mut bytes = bincode::serialize(&ShortU16(num_required_signatures as u16)).unwrap();
+    for pubkey in message.static_account_keys()[..num_required_signatures].iter() {
+        let keypair = signers.iter()
+            .find(|keypair| keypair.public.as_bytes() == pubkey.as_ref())
+            .unwrap_or_else(|| panic!("Missing signer {}", pubkey));
+        bytes.extend_from_slice(&keypair.sign(&message_bytes).to_bytes());
+    }
+    bytes.extend_from_slice(&message_bytes);
+    bytes
+}
@@ -0,0 +1,162 @@
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use base64::prelude::BASE64_STANDARD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use solana_program::pubkey::Pubkey;
+use crate::runtime::Account;
+
+
+// Rent-exempt accounts report it since rent collection stopped
+pub const RENT_EXEMPT_RENT_EPOCH: u64 = u64::MAX;
+
+#[derive(Debug)]
+pub enum FixtureError {
+    Io { path: PathBuf, message: String },
+    Parse { path: Option<PathBuf>, message: String },
+}
+
+impl FixtureError {
+    fn parse(message: String) -> Self {
+        FixtureError::Parse { path: None, message }
+    }
+
+    fn at(self, file: &Path) -> Self {
+        match self {
+            FixtureError::Parse { path: None, message } => FixtureError::Parse { path: Some(file.to_path_buf()), message },
+            err => err,
+        }
+    }
+}
+
+impl fmt::Display for FixtureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FixtureError::Io { path, message } => write!(f, "Failed to access {}: {}", path.display(), message),
+            FixtureError::Parse { path: Some(path), message } => write!(f, "Invalid account file {}: {}", path.display(), message),
+            FixtureError::Parse { path: None, message } => write!(f, "Invalid account json: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for FixtureError {}
+
+
+// Output of `solana account --output json`, also what `solana-test-validator --account` reads
+#[derive(Serialize, Deserialize)]
+pub(crate) struct KeyedAccount {
+    pubkey: String,
+    account: UiAccount,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UiAccount {
+    lamports: u64,
+    data: (String, String), // Encoded data and its encoding
+    owner: String,
+    executable: bool,
+    rent_epoch: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    space: Option<u64>,
+}
+
+// Executable flag and rent epoch aren't kept, exported accounts are never executable
+impl KeyedAccount {
+    pub(crate) fn from_account(account: &Account) -> Self {
+        Self {
+            pubkey: account.pubkey.to_string(),
+            account: UiAccount {
+                lamports: account.lamports,
+                data: (BASE64_STANDARD.encode(account.data.as_slice()), "base64".to_string()),
+                owner: account.owner.to_string(),
+                executable: false,
+                rent_epoch: RENT_EXEMPT_RENT_EPOCH,
+                space: Some(account.data.len() as u64),
+            },
+        }
+    }
+
+    pub(crate) fn to_account(&self) -> Result<Account, FixtureError> {
+        let pubkey = parse_pubkey(&self.pubkey)?;
+        let owner = parse_pubkey(&self.account.owner)?;
+        let (encoded, encoding) = &self.account.data;
+        let data = match encoding.as_str() {
+            "base64" => BASE64_STANDARD.decode(encoded).map_err(|err| FixtureError::parse(err.to_string()))?,
+            "base58" => bs58::decode(encoded).into_vec().map_err(|err| FixtureError::parse(err.to_string()))?,
+            encoding => return Err(FixtureError::parse(format!("Unsupported data encoding {}", encoding))),
+        };
+        if let Some(space) = self.account.space {
+            if space as usize != data.len() {
+                return Err(FixtureError::parse(format!("Data is {} bytes, space is {}", data.len(), space)));
+            }
+        }
+        Ok(Account::new(pubkey, self.account.lamports, &owner, data))
+    }
+}
+
+impl Account {
+    pub fn from_json(text: &str) -> Result<Self, FixtureError> {
+        let keyed: KeyedAccount = serde_json::from_str(text)
+            .map_err(|err| FixtureError::parse(err.to_string()))?;
+        keyed.to_account()
+    }
+
+    pub fn from_json_file<P: AsRef<Path>>(path: P) -> Result<Self, FixtureError> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|err| io_error(path, err))?;
+        Self::from_json(&text).map_err(|err| err.at(path))
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(&KeyedAccount::from_account(self)).unwrap()
+    }
+
+    pub fn write_json_file<P: AsRef<Path>>(&self, path: P) -> Result<(), FixtureError> {
+        let path = path.as_ref();
+        fs::write(path, self.to_json()).map_err(|err| io_error(path, err))
+    }
+}
+
+// Every *.json file of the directory, in file name order
+pub fn load_accounts_dir<P: AsRef<Path>>(dir: P) -> Result<Vec<Account>, FixtureError> {
+    let dir = dir.as_ref();
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)
+        .map_err(|err| io_error(dir, err))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file() && path.extension().map(|ext| ext == "json").unwrap_or(false))
+        .collect();
+    paths.sort();
+    paths.iter().map(Account::from_json_file).collect()
+}
+
+// One <pubkey>.json per account, the layout `solana-test-validator --account-dir` reads
+pub fn export_accounts_dir<P: AsRef<Path>>(accounts: &[&Account], dir: P) -> Result<Vec<PathBuf>, FixtureError> {
+    let dir = dir.as_ref();
+    fs::create_dir_all(dir).map_err(|err| io_error(dir, err))?;
+    accounts.iter()
+        .map(|account| {
+            let path = dir.join(format!("{}.json", account.pubkey));
+            account.write_json_file(&path).map(|_| path)
+        })
+        .collect()
+}
+
+// Exports the accounts and returns the `--account <ADDRESS> <FILE>` arguments loading them
+pub fn test_validator_account_args<P: AsRef<Path>>(accounts: &[&Account], dir: P) -> Result<Vec<String>, FixtureError> {
+    let paths = export_accounts_dir(accounts, dir)?;
+    Ok(accounts.iter()
+        .zip(paths.iter())
+        .flat_map(|(account, path)| ["--account".to_string(), account.pubkey.to_string(), path.display().to_string()])
+        .collect())
+}
+
+fn parse_pubkey(text: &str) -> Result<Pubkey, FixtureError> {
+    Pubkey::from_str(text).map_err(|_| FixtureError::parse(format!("Invalid pubkey {}", text)))
+}
+
+fn io_error(path: &Path, err: std::io::Error) -> FixtureError {
+    FixtureError::Io { path: path.to_path_buf(), message: err.to_string() }
+}
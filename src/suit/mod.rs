@@ -3,11 +3,13 @@ pub mod utils;
 pub mod spl;
 pub mod program_ids;
 pub mod typed_account;
+pub mod fixtures;
 
 pub use initializers::*;
 pub use utils::*;
 pub use spl::*;
 pub use program_ids::*;
 pub use typed_account::*;
+pub use fixtures::*;
 
 
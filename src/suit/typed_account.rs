@@ -1,6 +1,5 @@
-use std::io::Error;
+use std::cell::OnceCell;
 use std::ops::{Deref, DerefMut};
-use std::sync::atomic::{AtomicBool, AtomicPtr, Ordering};
 use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::program_pack::Pack;
 
@@ -13,9 +12,8 @@ use crate::suit::typed_account::packed_wrapper::PackedWrapper;
 
 pub struct TypedAccount<T: StructWrapper> {
     pub account: Account,
-    modified_bytes: AtomicBool,
-    modified_struct: AtomicBool,
-    data: AtomicPtr<T>,
+    modified_struct: bool,
+    data: OnceCell<T>, // Read from the bytes on first access, emptied when a writable meta is handed out
 }
 
 impl<B: BorshSerialize + BorshDeserialize> TypedAccount<BorshedWrapper<B>> {
@@ -28,9 +26,8 @@ impl<B: BorshSerialize + BorshDeserialize> TypedAccount<BorshedWrapper<B>> {
             Ok(data) => {
                 Some(Self {
                     account,
-                    modified_bytes: AtomicBool::new(false),
-                    modified_struct: AtomicBool::new(true),
-                    data: AtomicPtr::new(Box::leak(Box::new(BorshedWrapper::new(data))))
+                    modified_struct: true,
+                    data: OnceCell::from(BorshedWrapper::new(data)),
                 })
             }
             Err(_) => None
@@ -48,9 +45,8 @@ impl<P: Pack> TypedAccount<PackedWrapper<P>> {
             Ok(data) => {
                 Some(Self {
                     account,
-                    modified_bytes: AtomicBool::new(false),
-                    modified_struct: AtomicBool::new(true),
-                    data: AtomicPtr::new(Box::leak(Box::new(PackedWrapper::new(data))))
+                    modified_struct: true,
+                    data: OnceCell::from(PackedWrapper::new(data)),
                 })
             }
             Err(_) => None
@@ -65,9 +61,8 @@ impl<T: StructWrapper> TypedAccount<T> {
 
         Self {
             account: Account::new(pubkey, rent_exempt_for_size(bytes.len()), owner, bytes),
-            modified_bytes: AtomicBool::new(false),
-            modified_struct: AtomicBool::new(false),
-            data: AtomicPtr::new(Box::leak(Box::new(data))),
+            modified_struct: false,
+            data: OnceCell::from(data),
         }
     }
 
@@ -75,63 +70,43 @@ impl<T: StructWrapper> TypedAccount<T> {
         self.account.pubkey()
     }
 
+    pub fn with_label(mut self, label: &str) -> Self {
+        self.account.label = Some(label.to_string());
+        self
+    }
+
+    // Plain account with the struct written back to its bytes
+    pub fn into_account(mut self) -> Account {
+        if self.modified_struct {
+            self.copy_struct_to_bytes();
+        }
+        self.account
+    }
+
     pub fn lamports(&self) -> u64 {
-        *self.account.lamports.as_ref()
+        self.account.lamports
     }
 
-    pub fn meta(&mut self, is_signer: bool, is_writable: bool) -> (Pubkey, Meta) {
-        if self.modified_struct.load(Ordering::Relaxed) {
+    pub fn meta(&mut self, is_signer: bool, is_writable: bool) -> (Pubkey, Meta<'_>) {
+        if self.modified_struct {
             self.copy_struct_to_bytes();
-            self.modified_struct.store(false, Ordering::Relaxed);
+            self.modified_struct = false;
         }
         if is_writable {
-            self.modified_bytes.store(true, Ordering::Relaxed);
+            self.data.take();
         }
 
         self.account.meta(is_signer, is_writable)
     }
 
     fn get(&self) -> &T {
-        unsafe {
-            self.data.load(Ordering::Relaxed)
-                .as_ref()
-                .unwrap()
-        }
-    }
-
-    fn get_mut(&self) -> &mut T {
-        unsafe {
-            self.data.load(Ordering::Relaxed)
-                .as_mut()
-                .unwrap()
-        }
-    }
-
-    fn copy_bytes_to_struct(&self) {
-        // println!("copy_bytes_to_struct");
-        unsafe { // Replacing data mut as non-mut
-            let new_struct = Box::new(
-                T::_deserialize(
-                    &mut self.account.data.as_slice())
-            );
-
-            let _ = Box::from_raw(
-                self.data.swap(
-                    Box::leak(new_struct),
-                    Ordering::Relaxed,
-                )
-            );
-        }
+        self.data.get_or_init(|| T::_deserialize(self.account.data.as_slice()))
     }
 
     fn copy_struct_to_bytes(&mut self) {
-        // println!("copy_struct_to_bytes");
-        unsafe {
+        if let Some(data) = self.data.get() {
             self.account.data.clear();
-            self.data.load(Ordering::Relaxed)
-                .as_ref()
-                .unwrap()
-                ._serialize(self.account.data.as_mut());
+            data._serialize(&mut self.account.data);
         }
     }
 }
@@ -141,24 +116,15 @@ impl<T: StructWrapper> Deref for TypedAccount<T> {
     type Target = T;
 
     fn deref(&self) -> &Self::Target {
-        if self.modified_bytes.load(Ordering::Relaxed) {
-            self.copy_bytes_to_struct();
-            self.modified_bytes.store(false, Ordering::Relaxed);
-        }
-
         self.get()
     }
 }
 
 impl<T: StructWrapper> DerefMut for TypedAccount<T> {
     fn deref_mut(&mut self) -> &mut Self::Target {
-        if self.modified_bytes.load(Ordering::Relaxed) {
-            self.copy_bytes_to_struct();
-            self.modified_bytes.store(false, Ordering::Relaxed);
-        }
-        self.modified_struct.store(true, Ordering::Relaxed);
-
-        self.get_mut()
+        self.get();
+        self.modified_struct = true;
+        self.data.get_mut().unwrap()
     }
 }
 
@@ -1,12 +1,29 @@
-use solana_program::rent::{
-    DEFAULT_LAMPORTS_PER_BYTE_YEAR,
-    DEFAULT_EXEMPTION_THRESHOLD
-};
+use ed25519_dalek::Keypair;
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
 
 pub fn sol_to_lamports(sol: u64) -> u64 {
     sol * 1000000000
 }
 
 pub fn rent_exempt_for_size(size: usize) -> u64 {
-    DEFAULT_LAMPORTS_PER_BYTE_YEAR * (DEFAULT_EXEMPTION_THRESHOLD as u64) * (size as u64)
-}
\ No newline at end of file
+    Rent::default().minimum_balance(size)
+}
+pub fn new_keypair() -> Keypair {
+    Keypair::generate(&mut rand::rngs::OsRng)
+}
+
+pub fn keypair_pubkey(keypair: &Keypair) -> Pubkey {
+    Pubkey::new_from_array(keypair.public.to_bytes())
+}
+
+// `payer.pubkey()` as sdk code calls it
+pub trait Signer {
+    fn pubkey(&self) -> Pubkey;
+}
+
+impl Signer for Keypair {
+    fn pubkey(&self) -> Pubkey {
+        keypair_pubkey(self)
+    }
+}
@@ -0,0 +1,68 @@
+use solana_program::pubkey::Pubkey;
+use crate::{account, mint, mint_account, token_account};
+use crate::runtime::{Account, DataRangeDiff, Program, RuntimeConfig};
+use crate::suit::SPL_PROGRAM_ID;
+
+
+#[test]
+fn test_raw_diff() {
+    let program = Program::from_fn(|_, accounts, _| {
+        **accounts[0].lamports.borrow_mut() -= 10;
+        **accounts[1].lamports.borrow_mut() += 10;
+        let mut data = accounts[1].data.borrow_mut();
+        data[1] = 7;
+        data[2] = 8;
+        Ok(())
+    }, Pubkey::new_rand());
+
+    let mut from = account!(100);
+    let mut to = Account::new(Pubkey::new_rand(), 1000000000, &Pubkey::new_rand(), vec![0, 0, 0, 0]);
+    let mut untouched = account!(1);
+
+    let receipt = program.invoke_with_bytes(&[], vec![
+        from.meta(true, true),
+        to.meta(false, true),
+        untouched.meta(false, false),
+    ]);
+    receipt.expect_ok();
+    receipt.expect_lamports_delta(from.pubkey(), -10);
+    receipt.expect_lamports_delta(to.pubkey(), 10);
+    receipt.expect_unchanged(untouched.pubkey());
+
+    let diff = receipt.diff(to.pubkey()).unwrap();
+    assert_eq!(diff.data_len, (4, 4));
+    assert_eq!(diff.data, vec![DataRangeDiff { offset: 1, before: vec![0, 0], after: vec![7, 8] }]);
+    assert!(diff.fields.is_empty());
+}
+
+#[test]
+fn test_decoded_pack_diff() {
+    let token_program = Program::token_program();
+    let mut owner = account!(1000000000);
+    let mut mint = mint_account!(0, owner);
+    let mut from = token_account!(mint, owner);
+    let mut to = token_account!(Pubkey::new_rand(), mint, owner);
+    mint!(mint, from, 1000);
+
+    let mut config = RuntimeConfig::new();
+    config.layouts.pack_for_owner::<spl_token::state::Account>(&SPL_PROGRAM_ID);
+
+    let instruction = spl_token::instruction::transfer(
+        &SPL_PROGRAM_ID, from.pubkey(), to.pubkey(), owner.pubkey(), &[], 400
+    ).unwrap();
+    let receipt = token_program.invoke_with_config(&instruction.data, vec![
+        from.meta(false, true),
+        to.meta(false, true),
+        owner.meta(true, false),
+    ], config);
+    receipt.expect_ok();
+
+    let diff = receipt.diff(from.pubkey()).unwrap();
+    assert_eq!(diff.fields.len(), 1);
+    assert_eq!(diff.fields[0].name, "amount");
+    assert_eq!(diff.fields[0].before, "1000");
+    assert_eq!(diff.fields[0].after, "600");
+    assert_eq!(receipt.diff(to.pubkey()).unwrap().field("amount").unwrap().after, "400");
+    receipt.expect_unchanged(mint.pubkey());
+    assert!(receipt.to_string().contains("amount: 1000 -> 600"));
+}
@@ -0,0 +1,60 @@
+use borsh::BorshSerialize;
+use simple_pool_contract::pool::Pool;
+use solana_program::{program_pack::Pack, pubkey::Pubkey};
+use crate::{account, mint_account, token_account};
+use crate::runtime::{AccountQuery, Environment};
+use crate::suit::{SPLAccount, TypedAccount, SPL_PROGRAM_ID};
+
+
+fn pool(owner: &Pubkey) -> Pool {
+    Pool::new(*owner, Pubkey::new_rand(), Pubkey::new_rand(), Pubkey::new_rand(), Pubkey::new_rand())
+}
+
+#[test]
+fn test_query_pools() {
+    let pool_program = Pubkey::new_rand();
+    let (alice, bob) = (Pubkey::new_rand(), Pubkey::new_rand());
+    let pool_size = pool(&alice).try_to_vec().unwrap().len();
+    let mut env = Environment::new();
+    env.add_typed_account(TypedAccount::new_borshed(Pubkey::new_rand(), &pool_program, pool(&alice)));
+    env.add_typed_account(TypedAccount::new_borshed(Pubkey::new_rand(), &pool_program, pool(&alice)));
+    env.add_typed_account(TypedAccount::new_borshed(Pubkey::new_rand(), &pool_program, pool(&bob)));
+    env.add_typed_account(TypedAccount::new_borshed(Pubkey::new_rand(), &Pubkey::new_rand(), pool(&alice)));
+    env.add_account(account!(Pubkey::new_rand(), &pool_program, vec![0; 8]));
+
+    let pools = env.query_borshed::<Pool>(&AccountQuery::owned_by(&pool_program).with_data_size(pool_size));
+    assert_eq!(pools.len(), 3);
+
+    let query = AccountQuery::owned_by(&pool_program).with_data_size(pool_size).with_memcmp(0, alice.as_ref());
+    let pools = env.query_borshed::<Pool>(&query);
+    assert_eq!(pools.len(), 2);
+    assert!(pools.iter().all(|pool| pool.owner == alice));
+
+    // Filters see the whole data, results keep the slice
+    let sliced = env.query(&query.with_data_slice(32, 32));
+    assert_eq!(sliced.len(), 2);
+    assert!(sliced.iter().all(|account| account.data.len() == 32));
+    assert_eq!(sliced[0].data.as_slice(), pools[0].mint_left.as_ref());
+}
+
+#[test]
+fn test_query_token_accounts() {
+    let owner = account!();
+    let mint = mint_account!(Pubkey::new_rand(), 6, owner);
+    let other_mint = mint_account!(Pubkey::new_rand(), 6, owner);
+    let mut env = Environment::new();
+    for _ in 0..3 {
+        env.add_typed_account(token_account!(Pubkey::new_rand(), mint, account!()));
+    }
+    env.add_typed_account(token_account!(Pubkey::new_rand(), other_mint, account!()));
+    let mint_key = *mint.pubkey();
+    env.add_typed_account(mint);
+
+    let accounts = AccountQuery::owned_by(&SPL_PROGRAM_ID).with_data_size(SPLAccount::LEN);
+    assert_eq!(env.query_packed::<SPLAccount>(&accounts).len(), 4);
+    assert_eq!(env.query(&AccountQuery::owned_by(&SPL_PROGRAM_ID)).len(), 5);
+
+    let holders = env.query_packed::<SPLAccount>(&accounts.with_memcmp(0, mint_key.as_ref()));
+    assert_eq!(holders.len(), 3);
+    assert!(holders.iter().all(|holder| holder.mint == mint_key));
+}
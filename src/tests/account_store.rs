@@ -0,0 +1,52 @@
+use solana_program::{pubkey::Pubkey, system_instruction};
+use crate::account;
+use crate::runtime::{AccountStore, Environment, Program, Transaction};
+
+
+#[test]
+fn test_forks_share_data_until_written() {
+    let mut store = AccountStore::new();
+    let large = account!(Pubkey::new_rand(), &Pubkey::new_rand(), vec![7; 1024 * 1024]);
+    let small = account!(1000000000);
+    let (large_key, small_key) = (*large.pubkey(), *small.pubkey());
+    store.insert(large);
+    store.insert(small);
+
+    let mut fork = store.fork();
+    assert_eq!(fork.touched(), 0);
+    assert!(std::ptr::eq(store.get(&large_key).unwrap().data.as_ptr(), fork.get(&large_key).unwrap().data.as_ptr()));
+
+    fork.get_mut(&small_key).unwrap().lamports = 1;
+    fork.remove(&large_key);
+    assert_eq!(fork.touched(), 2);
+    assert_eq!(fork.len(), 1);
+    assert!(fork.get(&large_key).is_none());
+    assert_eq!(fork.get(&small_key).unwrap().lamports, 1);
+
+    assert_eq!(store.len(), 2);
+    assert_eq!(store.get(&large_key).unwrap().data.len(), 1024 * 1024);
+    assert_eq!(store.get(&small_key).unwrap().lamports, 1000000000);
+}
+
+#[test]
+fn test_thousands_of_forks() {
+    let count = if cfg!(miri) { 10 } else { 1000 }; // Miri is too slow for the full count
+    let mut env = Environment::new();
+    env.add_program(Program::system_program());
+    let from = account!(1000000000);
+    let from_key = *from.pubkey();
+    env.add_account(from);
+    for _ in 0..count {
+        env.add_account(account!(Pubkey::new_rand(), &Pubkey::new_rand(), vec![0; 1024]));
+    }
+
+    for lamports in (1..=count as u64).map(|n| 1000000 + n) {
+        let mut fork = env.fork();
+        let to = Pubkey::new_rand();
+        fork.execute(&Transaction::new(&[system_instruction::transfer(&from_key, &to, lamports)])).expect_ok();
+        fork.get_account(&to).unwrap().expect_balance(lamports);
+        assert_eq!(fork.accounts.touched(), 2);
+    }
+    env.get_account(&from_key).unwrap().expect_balance(1000000000);
+    assert_eq!(env.accounts().count(), count + 1);
+}
@@ -0,0 +1,171 @@
+use std::mem;
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    hash::Hash,
+    instruction::{AccountMeta, Instruction},
+    program_pack::Pack,
+    pubkey::Pubkey,
+    system_instruction,
+};
+use crate::{account, mint_account};
+use crate::runtime::{block_on, Account, BanksClient, Environment, Program, Transaction, TransactionError};
+use crate::suit::{keypair_pubkey, new_keypair, Signer, SPLMint, SPL_PROGRAM_ID};
+
+
+fn start() -> (BanksClient, Pubkey) {
+    let mut env = Environment::new();
+    env.add_program(Program::system_program());
+    env.add_program(Program::token_program());
+    let payer = account!(1000000000);
+    let payer_key = *payer.pubkey();
+    env.add_account(payer);
+    (BanksClient::new(env), payer_key)
+}
+
+#[test]
+fn test_process_transaction() {
+    let (mut banks_client, payer) = start();
+    let recipient = Pubkey::new_rand();
+
+    block_on(async {
+        let rent = banks_client.get_rent().await.unwrap();
+        let lamports = rent.minimum_balance(0);
+        let transaction = Transaction::new_with_payer(&[system_instruction::transfer(&payer, &recipient, lamports)], Some(&payer));
+        banks_client.process_transaction(transaction).await.unwrap();
+
+        assert_eq!(banks_client.get_balance(recipient).await.unwrap(), lamports);
+        let account = banks_client.get_account(recipient).await.unwrap().unwrap();
+        assert_eq!(account.lamports, lamports);
+        assert!(!account.executable);
+        assert!(banks_client.get_account(Pubkey::new_rand()).await.unwrap().is_none());
+
+        let transaction = Transaction::new(&[system_instruction::transfer(&recipient, &payer, lamports * 2)]);
+        let err = banks_client.process_transaction(transaction).await.unwrap_err();
+        assert!(matches!(err.unwrap(), TransactionError::InstructionError(0, _)));
+    });
+}
+
+#[test]
+fn test_read_programs_and_cluster() {
+    let (mut banks_client, _) = start();
+    let owner = account!();
+    let mint = mint_account!(Pubkey::new_rand(), 9, owner);
+    let mint_key = *mint.pubkey();
+    banks_client.environment().add_typed_account(mint);
+    let blockhash = banks_client.environment().settings().blockhash;
+
+    block_on(async {
+        assert_eq!(banks_client.get_latest_blockhash().await.unwrap(), blockhash);
+        let token_program = banks_client.get_account(SPL_PROGRAM_ID).await.unwrap().unwrap();
+        assert!(token_program.executable);
+
+        let mint = banks_client.get_account(mint_key).await.unwrap().unwrap();
+        assert_eq!(mint.owner, SPL_PROGRAM_ID);
+        assert_eq!(SPLMint::unpack(&mint.data).unwrap().decimals, 9);
+    });
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+struct GreetingAccount {
+    counter: u32,
+}
+
+#[test]
+fn test_ported_program_test() {
+    // Setup: the program and accounts `ProgramTest` would have provided
+    let program_id = Pubkey::new_unique();
+    let greeted_pubkey = Pubkey::new_unique();
+    let program = Program::from_fn(|_, accounts, _| {
+        let mut greeting = GreetingAccount::try_from_slice(&accounts[0].data.borrow())?;
+        greeting.counter += 1;
+        greeting.serialize(&mut &mut accounts[0].data.borrow_mut()[..])?;
+        Ok(())
+    }, program_id);
+    let payer = new_keypair();
+    let mut env = Environment::new();
+    env.add_program(program);
+    env.add_account(account!(keypair_pubkey(&payer), 1000000000));
+    env.add_account(Account::new(greeted_pubkey, 5, &program_id, vec![0_u8; mem::size_of::<u32>()]));
+    let mut banks_client = BanksClient::new(env);
+
+    block_on(async {
+        let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+
+        // Verbatim from example-helloworld's tests
+        // Verify account has zero greetings
+        let greeted_account = banks_client
+            .get_account(greeted_pubkey)
+            .await
+            .expect("get_account")
+            .expect("greeted_account not found");
+        assert_eq!(
+            GreetingAccount::try_from_slice(&greeted_account.data)
+                .unwrap()
+                .counter,
+            0
+        );
+
+        // Greet once
+        let mut transaction = Transaction::new_with_payer(
+            &[Instruction::new_with_bincode(
+                program_id,
+                &[0], // ignored but makes the instruction unique in the slot
+                vec![AccountMeta::new(greeted_pubkey, false)],
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(&[&payer], recent_blockhash);
+        banks_client.process_transaction(transaction).await.unwrap();
+
+        // Verify account has one greeting
+        let greeted_account = banks_client
+            .get_account(greeted_pubkey)
+            .await
+            .expect("get_account")
+            .expect("greeted_account not found");
+        assert_eq!(
+            GreetingAccount::try_from_slice(&greeted_account.data)
+                .unwrap()
+                .counter,
+            1
+        );
+
+        // Greet again
+        let transaction = Transaction::new_signed_with_payer(
+            &[Instruction::new_with_bincode(
+                program_id,
+                &[1], // ignored but makes the instruction unique in the slot
+                vec![AccountMeta::new(greeted_pubkey, false)],
+            )],
+            Some(&payer.pubkey()),
+            &[&payer],
+            recent_blockhash,
+        );
+        banks_client.process_transaction(transaction).await.unwrap();
+
+        // Verify account has two greetings
+        let greeted_account = banks_client
+            .get_account(greeted_pubkey)
+            .await
+            .expect("get_account")
+            .expect("greeted_account not found");
+        assert_eq!(
+            GreetingAccount::try_from_slice(&greeted_account.data)
+                .unwrap()
+                .counter,
+            2
+        );
+        // End of the ported test
+
+        // Signed for another cluster
+        let transaction = Transaction::new_signed_with_payer(
+            &[Instruction::new_with_bincode(program_id, &[2], vec![AccountMeta::new(greeted_pubkey, false)])],
+            Some(&payer.pubkey()),
+            &[&payer],
+            Hash::default(),
+        );
+        let err = banks_client.process_transaction(transaction).await.unwrap_err();
+        assert_eq!(err.unwrap(), TransactionError::BlockhashNotFound);
+        assert_eq!(banks_client.get_balance(payer.pubkey()).await.unwrap(), 1000000000 - 2 * 5000);
+    });
+}
@@ -0,0 +1,120 @@
+use std::hint::black_box;
+use solana_program::compute_budget::ComputeBudgetInstruction;
+use solana_program::instruction::Instruction;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use solana_program::system_instruction;
+use crate::account;
+use crate::runtime::{ComputeBudget, Meta, Program, Receipt, RuntimeConfig, Transaction, TransactionError};
+
+
+// Allocates as many bytes as the little-endian u32 in the instruction data
+fn allocator() -> Program {
+    Program::from_fn(|_, _, data| {
+        let size = u32::from_le_bytes(data[..4].try_into().unwrap()) as usize;
+        black_box(vec![1u8; size]);
+        Ok(())
+    }, Pubkey::new_rand())
+}
+
+#[test]
+fn test_budget_applied_to_transaction() {
+    let mut compute_budget = Program::compute_budget_program();
+    let mut program = allocator();
+    let call = Instruction::new_with_bytes(program.pubkey, &0u32.to_le_bytes(), vec![]);
+
+    let receipt = Transaction::new(&[call.clone(), call.clone()])
+        .execute(vec![program.meta()]);
+    receipt.expect_ok();
+    assert_eq!(receipt.compute_budget().compute_unit_limit, 400000);
+    assert_eq!(receipt.compute_budget().priority_fee(), 0);
+
+    let receipt = Transaction::new(&[
+        ComputeBudgetInstruction::set_compute_unit_limit(300000),
+        ComputeBudgetInstruction::set_compute_unit_price(10001),
+        ComputeBudgetInstruction::request_heap_frame(64 * 1024),
+        ComputeBudgetInstruction::set_loaded_accounts_data_size_limit(1024 * 1024),
+        call,
+    ]).execute(vec![compute_budget.meta(), program.meta()]);
+    receipt.expect_ok();
+    assert!(receipt.to_string().contains("Program consumed: 0 of 300000 compute units"));
+    assert_eq!(receipt.compute_budget(), &ComputeBudget {
+        compute_unit_limit: 300000,
+        compute_unit_price: 10001,
+        heap_size: 64 * 1024,
+        loaded_accounts_data_size_limit: 1024 * 1024,
+    });
+    assert_eq!(receipt.compute_budget().priority_fee(), 3001);
+}
+
+#[test]
+fn test_invalid_budget_requests() {
+    let mut compute_budget = Program::compute_budget_program();
+    let mut user = account!(1000000000);
+    fn run(instructions: &[Instruction], accounts: Vec<(Pubkey, Meta)>) -> Receipt {
+        Transaction::new(instructions).execute(accounts)
+    }
+
+    let receipt = run(&[
+        ComputeBudgetInstruction::set_compute_unit_price(1),
+        ComputeBudgetInstruction::set_compute_unit_price(2),
+    ], vec![compute_budget.meta()]);
+    receipt.expect_transaction_err(TransactionError::DuplicateInstruction(1));
+
+    let receipt = run(&[ComputeBudgetInstruction::request_heap_frame(40 * 1024 + 1)], vec![compute_budget.meta()]);
+    receipt.expect_transaction_err(TransactionError::InstructionError(0, ProgramError::InvalidInstructionData));
+    receipt.expect_err(ProgramError::InvalidInstructionData);
+
+    let receipt = run(&[ComputeBudgetInstruction::set_loaded_accounts_data_size_limit(0)], vec![compute_budget.meta()]);
+    receipt.expect_transaction_err(TransactionError::InvalidLoadedAccountsDataSizeLimit);
+
+    let mut data_account = account!(Pubkey::new_rand(), &Pubkey::new_rand(), vec![0u8; 2048]);
+    let receipt = run(&[ComputeBudgetInstruction::set_loaded_accounts_data_size_limit(1024)], vec![
+        compute_budget.meta(),
+        data_account.meta(false, false),
+    ]);
+    receipt.expect_transaction_err(TransactionError::MaxLoadedAccountsDataSizeExceeded);
+
+    let receipt = run(&[ComputeBudgetInstruction::set_compute_unit_limit(1)], vec![user.meta(true, true)]);
+    receipt.expect_transaction_err(TransactionError::ProgramAccountNotFound);
+}
+
+#[test]
+fn test_heap_frame_request() {
+    let mut compute_budget = Program::compute_budget_program();
+    let mut program = allocator();
+    let call = Instruction::new_with_bytes(program.pubkey, &40000u32.to_le_bytes(), vec![]);
+
+    let config = RuntimeConfig::new().with_heap_tracking();
+    let receipt = Transaction::new(std::slice::from_ref(&call)).execute_with_config(vec![program.meta()], config);
+    receipt.expect_transaction_err(TransactionError::InstructionError(0, ProgramError::InvalidArgument));
+
+    let config = RuntimeConfig::new().with_heap_tracking();
+    let receipt = Transaction::new(&[ComputeBudgetInstruction::request_heap_frame(64 * 1024), call])
+        .execute_with_config(vec![compute_budget.meta(), program.meta()], config);
+    receipt.expect_ok();
+    assert!(receipt.heap_peak().unwrap() >= 40000);
+}
+
+#[test]
+fn test_failed_transaction_rolls_back() {
+    let mut system_program = Program::system_program();
+    let mut program = Program::from_fn(|_, _, _| Err(ProgramError::Custom(7)), Pubkey::new_rand());
+    let mut from = account!(1000000000);
+    let mut to = account!(1000000000);
+
+    let receipt = Transaction::new(&[
+        system_instruction::transfer(from.pubkey(), to.pubkey(), 5000),
+        Instruction::new_with_bytes(program.pubkey, &[], vec![]),
+    ]).execute(vec![
+        from.meta(true, true),
+        to.meta(false, true),
+        system_program.meta(),
+        program.meta(),
+    ]);
+    receipt.expect_transaction_err(TransactionError::InstructionError(1, ProgramError::Custom(7)));
+    receipt.expect_any_err();
+    from.expect_balance(1000000000);
+    to.expect_balance(1000000000);
+    assert!(receipt.account_diffs().is_empty());
+}
@@ -0,0 +1,136 @@
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_instruction,
+    syscalls::Syscalls,
+};
+use crate::{account, mint_account};
+use crate::executor::InlinePackage;
+use crate::runtime::{Environment, EnvironmentError, Program, Transaction};
+use crate::suit::{SPLMint, SYSTEM_PROGRAM_ID};
+
+
+fn increment(_program_id: &Pubkey, accounts: &[AccountInfo], _instruction_data: &[u8], _syscalls: &mut dyn Syscalls) -> ProgramResult {
+    accounts[0].data.borrow_mut()[0] += 1;
+    Ok(())
+}
+
+fn transfer(from: &Pubkey, to: &Pubkey, lamports: u64) -> Transaction {
+    Transaction::new(&[system_instruction::transfer(from, to, lamports)])
+}
+
+
+#[test]
+fn test_save_and_restore() {
+    let mut env = Environment::new();
+    env.add_program(Program::system_program());
+    let from = account!(1000000000).with_label("env_from");
+    let to = account!(1000000000);
+    let (from_key, to_key) = (*from.pubkey(), *to.pubkey());
+    env.add_account(from);
+    env.add_account(to);
+    env.warp_to_slot(42);
+
+    env.execute(&transfer(&from_key, &to_key, 100)).expect_ok();
+
+    let path = std::env::temp_dir().join(format!("fulgurite-env-{}.json", Pubkey::new_rand()));
+    env.save(&path).unwrap();
+    let mut restored = Environment::restore(&path, Vec::new()).unwrap();
+    assert_eq!(restored.settings().slot, 42);
+    assert_eq!(restored.label_of(&from_key), Some("env_from"));
+    restored.get_account(&from_key).unwrap().expect_balance(1000000000 - 100);
+    restored.get_account(&to_key).unwrap().expect_balance(1000000000 + 100);
+
+    restored.execute(&transfer(&from_key, &to_key, 100)).expect_ok();
+    restored.get_account(&to_key).unwrap().expect_balance(1000000000 + 200);
+    assert_eq!(restored.to_json(), {
+        env.execute(&transfer(&from_key, &to_key, 100)).expect_ok();
+        env.to_json()
+    });
+}
+
+#[test]
+fn test_fork_is_isolated() {
+    let mut env = Environment::new();
+    env.add_program(Program::system_program());
+    let from = account!(1000000000);
+    let from_key = *from.pubkey();
+    env.add_account(from);
+
+    let mut fork = env.fork();
+    let new_key = Pubkey::new_rand();
+    fork.execute(&transfer(&from_key, &new_key, 5000000)).expect_ok();
+    fork.warp_to_slot(7);
+
+    fork.get_account(&new_key).unwrap().expect_balance(5000000);
+    fork.get_account(&from_key).unwrap().expect_balance(1000000000 - 5000000);
+    env.get_account(&from_key).unwrap().expect_balance(1000000000);
+    assert!(env.get_account(&new_key).is_none());
+    assert_eq!(env.settings().slot, 1);
+}
+
+#[test]
+fn test_restore_requires_custom_programs() {
+    let program_id = Pubkey::new_rand();
+    let mut env = Environment::new();
+    env.add_program(Program::inline(increment, program_id));
+    let counter = account!(Pubkey::new_rand(), &program_id, vec![0]);
+    let counter_key = *counter.pubkey();
+    env.add_account(counter);
+    let instruction = Instruction::new_with_bytes(program_id, &[], vec![AccountMeta::new(counter_key, false)]);
+    env.execute(&Transaction::new(std::slice::from_ref(&instruction))).expect_ok();
+
+    let state = env.to_json();
+    match Environment::from_json(&state, Vec::new()) {
+        Err(EnvironmentError::MissingProgram(pubkey)) => assert_eq!(pubkey, program_id),
+        _ => panic!("Restored without the program"),
+    }
+
+    let mut restored = Environment::from_json(&state, vec![Program::inline(increment, program_id)]).unwrap();
+    restored.execute(&Transaction::new(&[instruction])).expect_ok();
+    restored.get_account(&counter_key).unwrap().expect_bytes(&[2]);
+    env.get_account(&counter_key).unwrap().expect_bytes(&[1]);
+}
+
+#[test]
+fn test_typed_accounts() {
+    let owner = account!();
+    let mut env = Environment::new();
+    let mut mint = mint_account!(Pubkey::new_rand(), 6, owner);
+    let mint_key = *mint.pubkey();
+    mint.supply = 1000;
+    env.add_typed_account(mint);
+
+    let mut forked = env.fork().get_packed::<SPLMint>(&mint_key).unwrap();
+    assert_eq!(forked.decimals, 6);
+    assert_eq!(forked.supply, 1000);
+    forked.supply = 2000;
+    assert_eq!(env.get_packed::<SPLMint>(&mint_key).unwrap().supply, 1000);
+    assert!(env.get_borshed::<u64>(&Pubkey::new_rand()).is_none());
+}
+
+#[test]
+fn test_upgradeable_program_survives_fork_and_restore() {
+    let program_id = Pubkey::new_rand();
+    let mut env = Environment::new();
+    env.add_upgradeable_program(Box::new(InlinePackage::new(increment)), program_id, None);
+    let counter = account!(Pubkey::new_rand(), &program_id, vec![0]);
+    let counter_key = *counter.pubkey();
+    env.add_account(counter);
+    let call = Transaction::new(&[Instruction::new_with_bytes(program_id, &[], vec![AccountMeta::new(counter_key, false)])]);
+
+    env.execute(&call).expect_ok();
+    let mut fork = env.fork();
+    fork.execute(&call).expect_ok();
+    let mut restored = Environment::from_json(&fork.to_json(), vec![Program::inline(increment, program_id)]).unwrap();
+    restored.execute(&call).expect_ok();
+    assert!(matches!(Environment::from_json(&fork.to_json(), Vec::new()), Err(EnvironmentError::MissingProgram(key)) if key == program_id));
+
+    env.get_account(&counter_key).unwrap().expect_bytes(&[1]);
+    fork.get_account(&counter_key).unwrap().expect_bytes(&[2]);
+    restored.get_account(&counter_key).unwrap().expect_bytes(&[3]);
+    assert!(restored.program_ids().any(|key| key.eq(&program_id)));
+    assert!(!restored.program_ids().any(|key| key.eq(&SYSTEM_PROGRAM_ID)));
+}
@@ -0,0 +1,77 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::instruction::Instruction;
+use solana_program::log::sol_log_data;
+use solana_program::program::invoke;
+use solana_program::pubkey::Pubkey;
+use crate::runtime::{anchor_event_discriminator, Program};
+
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq)]
+struct Deposited {
+    amount: u64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq)]
+struct Withdrawn {
+    amount: u64,
+    fee: u32,
+}
+
+#[test]
+fn test_typed_events_with_call_path() {
+    let emitter = Program::from_fn(|_, _, data| {
+        sol_log_data(&[&Deposited { amount: data[0] as u64 }.try_to_vec().unwrap()]);
+        Ok(())
+    }, Pubkey::new_rand());
+    let emitter_id = emitter.pubkey;
+    let caller = Program::from_fn(move |_, accounts, _| {
+        invoke(&Instruction::new_with_bytes(emitter_id, &[1], vec![]), accounts)?;
+        invoke(&Instruction::new_with_bytes(emitter_id, &[2], vec![]), accounts)?;
+        sol_log_data(&[&Withdrawn { amount: 3, fee: 1 }.try_to_vec().unwrap()]);
+        Ok(())
+    }, Pubkey::new_rand());
+
+    let mut emitter = emitter;
+    let receipt = caller.invoke_with_bytes(&[], vec![emitter.meta()]);
+    receipt.expect_ok();
+
+    let deposits = receipt.events::<Deposited>(&emitter.pubkey);
+    assert_eq!(deposits.len(), 2);
+    assert_eq!(deposits[0].value, Deposited { amount: 1 });
+    assert_eq!(deposits[0].call_path, vec![0]);
+    assert_eq!(deposits[1].value, Deposited { amount: 2 });
+    assert_eq!(deposits[1].call_path, vec![1]);
+    assert_eq!(deposits[1].stack_height(), 2);
+
+    let withdrawals = receipt.events::<Withdrawn>(&caller.pubkey);
+    assert_eq!(withdrawals.len(), 1);
+    assert_eq!(withdrawals[0].index, 2);
+    assert!(withdrawals[0].call_path.is_empty());
+    receipt.expect_event(&caller.pubkey, &Withdrawn { amount: 3, fee: 1 });
+    assert!(receipt.events::<Withdrawn>(&emitter.pubkey).is_empty());
+}
+
+#[test]
+fn test_anchor_events() {
+    let program = Program::from_fn(|_, _, _| {
+        let mut bytes = Vec::from(anchor_event_discriminator("Deposited"));
+        bytes.extend(Deposited { amount: 7 }.try_to_vec().unwrap());
+        sol_log_data(&[&bytes]);
+
+        let mut bytes = Vec::from(anchor_event_discriminator("Withdrawn"));
+        bytes.extend(Withdrawn { amount: 5, fee: 2 }.try_to_vec().unwrap());
+        sol_log_data(&[&bytes]);
+        Ok(())
+    }, Pubkey::new_rand());
+
+    let receipt = program.invoke_with_bytes(&[], vec![]);
+    receipt.expect_ok();
+
+    // sha256("event:Deposited")[..8]
+    assert_eq!(anchor_event_discriminator("Deposited"), [111, 141, 26, 45, 161, 35, 100, 57]);
+    let deposits = receipt.anchor_events::<Deposited>(&program.pubkey, "Deposited");
+    assert_eq!(deposits.len(), 1);
+    assert_eq!(deposits[0].value, Deposited { amount: 7 });
+    receipt.expect_anchor_event(&program.pubkey, "Withdrawn", &Withdrawn { amount: 5, fee: 2 });
+    assert!(receipt.anchor_events::<Withdrawn>(&program.pubkey, "Deposited").is_empty());
+}
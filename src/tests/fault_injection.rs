@@ -0,0 +1,94 @@
+use solana_program::instruction::{AccountMeta, Instruction};
+use solana_program::msg;
+use solana_program::program::invoke;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use crate::runtime::{Account, Fault, FaultMode, Program, RuntimeConfig};
+
+
+// Calls the callee once per instruction byte, passing the byte as data
+fn caller(callee: Pubkey) -> Program {
+    Program::from_fn(move |_, accounts, data| {
+        for byte in data.iter() {
+            invoke(&Instruction::new_with_bytes(callee, &[*byte], vec![]), accounts)?;
+        }
+        Ok(())
+    }, Pubkey::new_rand())
+}
+
+fn callee() -> Program {
+    Program::from_fn(|_, _, data| {
+        msg!("Callee {}", data[0]);
+        Ok(())
+    }, Pubkey::new_rand())
+}
+
+#[test]
+fn test_fault_on_nth_call() {
+    let mut callee = callee();
+    let caller = caller(callee.pubkey);
+
+    let mut config = RuntimeConfig::new();
+    config.faults.inject(Fault::nth(&callee.pubkey, 2, ProgramError::Custom(7)));
+
+    let receipt = caller.invoke_with_config(&[1, 2, 3], vec![callee.meta()], config);
+    receipt.expect_err(ProgramError::Custom(7));
+    receipt.expect_log("Callee 1");
+    receipt.expect_fault_injected(&callee.pubkey);
+    assert!(!receipt.log_messages.contains(&"Program logged: \"Callee 2\"".to_string()));
+
+    let fault = &receipt.injected_faults()[0];
+    assert_eq!(fault.call_index, 2);
+    assert_eq!(fault.stack_height, 2);
+    assert_eq!(fault.mode, FaultMode::FailBeforeExecution);
+}
+
+#[test]
+fn test_fault_after_execution_matching_data() {
+    let mut callee = callee();
+    let caller = caller(callee.pubkey);
+
+    let mut config = RuntimeConfig::new();
+    config.faults.inject(
+        Fault::matching(&callee.pubkey, |data| data[0] == 3, ProgramError::InvalidArgument).after_execution()
+    );
+
+    let receipt = caller.invoke_with_config(&[1, 3], vec![callee.meta()], config);
+    receipt.expect_err(ProgramError::InvalidArgument);
+    receipt.expect_log("Callee 3");
+    assert_eq!(receipt.injected_faults()[0].mode, FaultMode::FailAfterExecution);
+}
+
+#[test]
+fn test_fault_after_execution_keeps_caller_accounts() {
+    let mut callee = Program::from_fn(|_, accounts, _| {
+        accounts[0].data.borrow_mut()[0] += 1;
+        Ok(())
+    }, Pubkey::new_rand());
+    let callee_id = callee.pubkey;
+    let caller = Program::from_fn(move |_, accounts, _| {
+        let instruction = Instruction::new_with_bytes(callee_id, &[], vec![AccountMeta::new(*accounts[0].key, false)]);
+        assert!(invoke(&instruction, accounts).is_err());
+        msg!("Counter {}", accounts[0].data.borrow()[0]);
+        Ok(())
+    }, Pubkey::new_rand());
+
+    let mut counter = Account::new(Pubkey::new_rand(), 1000000000, &callee_id, vec![0]);
+    let mut config = RuntimeConfig::new();
+    config.faults.inject(Fault::nth(&callee_id, 1, ProgramError::Custom(3)).after_execution());
+
+    let receipt = caller.invoke_with_config(&[], vec![counter.meta(false, true), callee.meta()], config);
+    receipt.expect_ok();
+    receipt.expect_log("Counter 0");
+    receipt.expect_unchanged(counter.pubkey());
+}
+
+#[test]
+fn test_no_fault_without_config() {
+    let mut callee = callee();
+    let caller = caller(callee.pubkey);
+
+    let receipt = caller.invoke_with_bytes(&[1, 2], vec![callee.meta()]);
+    receipt.expect_ok();
+    assert!(receipt.injected_faults().is_empty());
+}
@@ -0,0 +1,110 @@
+use solana_program::compute_budget::ComputeBudgetInstruction;
+use solana_program::instruction::Instruction;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::system_instruction;
+use crate::account;
+use crate::runtime::{FeeDetails, Program, RuntimeConfig, Transaction, TransactionError};
+
+
+#[test]
+fn test_fee_charged_to_payer() {
+    let mut system_program = Program::system_program();
+    let mut compute_budget = Program::compute_budget_program();
+    let mut payer = account!(1000000000);
+    let mut from = account!(1000000000);
+    let mut to = account!(1000000000);
+
+    let receipt = Transaction::new_with_payer(&[
+        ComputeBudgetInstruction::set_compute_unit_limit(1000),
+        ComputeBudgetInstruction::set_compute_unit_price(2500),
+        system_instruction::transfer(from.pubkey(), to.pubkey(), 100),
+    ], Some(payer.pubkey())).execute(vec![
+        payer.meta(true, true),
+        from.meta(true, true),
+        to.meta(false, true),
+        system_program.meta(),
+        compute_budget.meta(),
+    ]);
+    receipt.expect_ok();
+    receipt.expect_fee(10003);
+    assert_eq!(receipt.fee(), Some(&FeeDetails {
+        payer: *payer.pubkey(),
+        signatures: 2,
+        signature_fee: 10000,
+        priority_fee: 3,
+        burned: 5001,
+        collected: 5002,
+    }));
+    payer.expect_balance(1000000000 - 10003);
+    receipt.expect_lamports_delta(payer.pubkey(), -10003);
+    from.expect_balance(1000000000 - 100);
+}
+
+#[test]
+fn test_fee_kept_on_failure() {
+    let mut system_program = Program::system_program();
+    let mut program = Program::from_fn(|_, _, _| Err(ProgramError::Custom(1)), Pubkey::new_rand());
+    let mut payer = account!(1000000000);
+    let mut to = account!(1000000000);
+
+    let receipt = Transaction::new_with_payer(&[
+        system_instruction::transfer(payer.pubkey(), to.pubkey(), 100),
+        Instruction::new_with_bytes(program.pubkey, &[], vec![]),
+    ], Some(payer.pubkey())).execute(vec![
+        payer.meta(true, true),
+        to.meta(false, true),
+        system_program.meta(),
+        program.meta(),
+    ]);
+    receipt.expect_transaction_err(TransactionError::InstructionError(1, ProgramError::Custom(1)));
+    receipt.expect_fee(5000);
+    payer.expect_balance(1000000000 - 5000);
+    to.expect_balance(1000000000);
+}
+
+#[test]
+fn test_fee_payer_rejected() {
+    let mut program = Program::from_fn(|_, _, _| Ok(()), Pubkey::new_rand());
+    let call = Instruction::new_with_bytes(program.pubkey, &[], vec![]);
+    let run = |payer: &mut crate::runtime::Account, program: &mut Program| {
+        Transaction::new_with_payer(std::slice::from_ref(&call), Some(payer.pubkey()))
+            .execute(vec![payer.meta(true, true), program.meta()])
+    };
+
+    let mut poor = account!(4999);
+    run(&mut poor, &mut program).expect_transaction_err(TransactionError::InsufficientFundsForFee);
+    poor.expect_balance(4999);
+
+    let mut owned = account!(Pubkey::new_rand(), 1000000000);
+    owned.owner = Pubkey::new_rand();
+    run(&mut owned, &mut program).expect_transaction_err(TransactionError::InvalidAccountForFee);
+
+    let mut with_data = account!(Pubkey::new_rand(), &solana_program::system_program::ID, vec![0u8; 8]);
+    run(&mut with_data, &mut program).expect_transaction_err(TransactionError::InvalidAccountForFee);
+
+    let mut exempt = account!(Rent::default().minimum_balance(0) + 1000);
+    run(&mut exempt, &mut program).expect_transaction_err(TransactionError::InsufficientFundsForRent { account_index: 0 });
+
+    let mut payer = account!(1000000000);
+    let receipt = run(&mut payer, &mut program);
+    receipt.expect_ok();
+    receipt.expect_fee(5000);
+}
+
+#[test]
+fn test_fee_on_program_invocation() {
+    let program = Program::from_fn(|_, _, _| Ok(()), Pubkey::new_rand());
+    let mut payer = account!(1000000000);
+    let mut signer = account!(1000000000);
+
+    let config = RuntimeConfig::new().with_fee_payer(payer.pubkey());
+    let receipt = program.invoke_with_config(&[], vec![payer.meta(true, true), signer.meta(true, false)], config);
+    receipt.expect_ok();
+    receipt.expect_fee(10000);
+    payer.expect_balance(1000000000 - 10000);
+
+    let receipt = program.invoke_with_bytes(&[], vec![payer.meta(true, true)]);
+    receipt.expect_fee(0);
+}
@@ -0,0 +1,61 @@
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use crate::account;
+use crate::runtime::Account;
+use crate::suit::{export_accounts_dir, load_accounts_dir, test_validator_account_args, FixtureError, SPLMint, SPL_PROGRAM_ID};
+
+
+const FIXTURE_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src/tests/fixtures");
+
+#[test]
+fn test_load_cli_account_dump() {
+    let mint = Account::from_json_file(format!("{}/usdc_mint.json", FIXTURE_DIR)).unwrap();
+    assert_eq!(mint.pubkey().to_string(), "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v");
+    mint.expect_balance(388127047454);
+    mint.expect_owner(&SPL_PROGRAM_ID);
+
+    let state = SPLMint::unpack(&mint.data).unwrap();
+    assert_eq!(state.decimals, 6);
+    assert_eq!(state.supply, 9_000_000_000_000_000);
+
+    let accounts = load_accounts_dir(FIXTURE_DIR).unwrap();
+    assert_eq!(accounts.len(), 1);
+    assert_eq!(accounts[0].to_json(), mint.to_json());
+}
+
+#[test]
+fn test_export_accounts() {
+    let dir = std::env::temp_dir().join(format!("fulgurite-fixtures-{}", Pubkey::new_rand()));
+    let user = account!(1000000000);
+    let data = account!(Pubkey::new_rand(), &Pubkey::new_rand(), vec![1, 2, 3]);
+
+    let paths = export_accounts_dir(&[&user, &data], &dir).unwrap();
+    assert_eq!(paths[1], dir.join(format!("{}.json", data.pubkey())));
+    let loaded = Account::from_json_file(&paths[1]).unwrap();
+    assert_eq!(loaded.pubkey(), data.pubkey());
+    loaded.expect_owner(&data.owner);
+    loaded.expect_bytes(&[1, 2, 3]);
+    assert_eq!(load_accounts_dir(&dir).unwrap().len(), 2);
+
+    let args = test_validator_account_args(&[&user], &dir).unwrap();
+    assert_eq!(args, vec![
+        "--account".to_string(),
+        user.pubkey().to_string(),
+        dir.join(format!("{}.json", user.pubkey())).display().to_string(),
+    ]);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_invalid_account_dump() {
+    let err = Account::from_json(r#"{"pubkey": "11111111111111111111111111111111", "account": {
+        "lamports": 1, "data": ["", "base64+zstd"], "owner": "11111111111111111111111111111111",
+        "executable": false, "rentEpoch": 0}}"#).err().unwrap();
+    assert_eq!(err.to_string(), "Invalid account json: Unsupported data encoding base64+zstd");
+
+    match Account::from_json_file("missing.json").err().unwrap() {
+        FixtureError::Io { path, .. } => assert_eq!(path.to_str(), Some("missing.json")),
+        err => panic!("Unexpected error {}", err),
+    }
+}
@@ -0,0 +1,79 @@
+use std::hint::black_box;
+use solana_program::instruction::Instruction;
+use solana_program::program::invoke;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use crate::account;
+use crate::runtime::{Program, RuntimeConfig, HEAP_LENGTH};
+
+
+// Allocates as many bytes as the little-endian u32 in the instruction data
+fn allocator() -> Program {
+    Program::from_fn(|_, _, data| {
+        let size = u32::from_le_bytes(data[..4].try_into().unwrap()) as usize;
+        black_box(vec![1u8; size]);
+        Ok(())
+    }, Pubkey::new_rand())
+}
+
+#[test]
+fn test_heap_peak_reported() {
+    let program = allocator();
+    let mut user = account!(1000000000);
+
+    let receipt = program.invoke_with_bytes(&10000u32.to_le_bytes(), vec![user.meta(true, true)]);
+    receipt.expect_ok();
+    assert_eq!(receipt.heap_peak(), None);
+
+    let config = RuntimeConfig::new().with_heap_tracking();
+    let receipt = program.invoke_with_config(&10000u32.to_le_bytes(), vec![user.meta(true, true)], config);
+    receipt.expect_ok();
+    assert!(receipt.heap_peak().unwrap() >= 10000);
+    receipt.expect_heap_peak_below(HEAP_LENGTH);
+}
+
+#[test]
+fn test_heap_exhausted() {
+    let program = allocator();
+    let mut user = account!(1000000000);
+
+    let config = RuntimeConfig::new().with_heap_tracking();
+    let receipt = program.invoke_with_config(&40000u32.to_le_bytes(), vec![user.meta(true, true)], config);
+    receipt.expect_err(ProgramError::InvalidArgument);
+    assert!(receipt.to_string().contains("failed: memory allocation failed, out of memory"));
+
+    // Freed memory isn't reused
+    let program = Program::from_fn(|_, _, _| {
+        for _ in 0..4 {
+            black_box(vec![1u8; 10000]);
+        }
+        Ok(())
+    }, Pubkey::new_rand());
+    let config = RuntimeConfig::new().with_heap_tracking();
+    let receipt = program.invoke_with_config(&[], vec![user.meta(true, true)], config);
+    receipt.expect_err(ProgramError::InvalidArgument);
+
+    let config = RuntimeConfig::new().with_heap_size(256 * 1024);
+    let receipt = program.invoke_with_config(&[], vec![user.meta(true, true)], config);
+    receipt.expect_ok();
+}
+
+#[test]
+fn test_heap_per_invocation() {
+    let mut callee = allocator();
+    let callee_id = callee.pubkey;
+    let caller = Program::from_fn(move |_, accounts, data| {
+        black_box(vec![1u8; 1000]);
+        invoke(&Instruction::new_with_bytes(callee_id, data, vec![]), accounts)
+    }, Pubkey::new_rand());
+
+    let config = RuntimeConfig::new().with_heap_tracking();
+    let receipt = caller.invoke_with_config(&20000u32.to_le_bytes(), vec![callee.meta()], config);
+    receipt.expect_ok();
+    assert!(receipt.heap_peak().unwrap() >= 20000);
+
+    let config = RuntimeConfig::new().with_heap_tracking();
+    let receipt = caller.invoke_with_config(&40000u32.to_le_bytes(), vec![callee.meta()], config);
+    receipt.expect_err(ProgramError::InvalidArgument);
+    assert!(receipt.to_string().contains(&format!("Program {} failed: memory allocation failed", callee_id)));
+}
@@ -0,0 +1,52 @@
+use solana_program::pubkey::Pubkey;
+use crate::account;
+use crate::runtime::{Environment, Labels, Program};
+
+
+#[test]
+fn test_labels_in_receipt() {
+    let program = Program::from_fn(|_, accounts, _| {
+        **accounts[0].lamports.borrow_mut() -= 5;
+        **accounts[1].lamports.borrow_mut() += 5;
+        Ok(())
+    }, Pubkey::new_rand()).with_label("payer_program");
+    let mut alice = account!(100).with_label("alice");
+    let mut bob = account!(1000000000).with_label("bob");
+
+    let receipt = program.invoke_with_bytes(&[], vec![alice.meta(true, true), bob.meta(false, true)]);
+    receipt.expect_ok();
+
+    let printed = receipt.to_string();
+    assert!(printed.contains("Program invoked: payer_program"));
+    assert!(printed.contains("Account alice\n    lamports: 100 -> 95 (-5)"));
+    assert!(printed.contains("Account bob\n    lamports: 1000000000 -> 1000000005 (+5)"));
+    assert!(!printed.contains(&alice.pubkey().to_string()));
+}
+
+#[test]
+fn test_label_rendering() {
+    let pubkey = Pubkey::new_rand();
+    let mut labels = Labels::new();
+    assert_eq!(labels.get(&pubkey), None);
+    assert_eq!(labels.display_key(&pubkey), pubkey.to_string());
+
+    labels.set(&pubkey, "vault");
+    assert_eq!(labels.display_key(&pubkey), format!("vault ({})", pubkey));
+    assert_eq!(labels.relabel(&format!("Missing Account {}", pubkey)), "Missing Account vault");
+    assert_eq!(labels.relabel("Program invoked: 11111111111111111111111111111111"), "Program invoked: system_program");
+
+    // Keys are matched as whole tokens
+    let glued = format!("{}x", pubkey);
+    assert_eq!(labels.relabel(&glued), glued);
+}
+
+#[test]
+fn test_labels_stay_with_their_environment() {
+    let account = account!(Pubkey::new_rand(), 0).with_label("vault");
+    let pubkey = *account.pubkey();
+    let mut env = Environment::new();
+    env.add_account(account);
+    assert_eq!(env.label_of(&pubkey), Some("vault"));
+    assert_eq!(Environment::new().label_of(&pubkey), None);
+    assert_eq!(Labels::new().get(&pubkey), None);
+}
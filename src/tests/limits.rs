@@ -0,0 +1,99 @@
+use std::borrow::Cow;
+use solana_program::address_lookup_table::{self, state::{AddressLookupTable, LookupTableMeta}, AddressLookupTableAccount};
+use solana_program::instruction::{AccountMeta, Instruction};
+use solana_program::program::invoke;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use crate::account;
+use crate::runtime::{Account, ClusterSettings, Program, RuntimeConfig, Transaction, TransactionError, INCREASED_MAX_TX_ACCOUNT_LOCKS, PACKET_DATA_SIZE};
+use crate::suit::{keypair_pubkey, new_keypair};
+
+
+fn noop() -> Program {
+    Program::from_fn(|_, _, _| Ok(()), Pubkey::new_rand())
+}
+
+#[test]
+fn test_transaction_too_large() {
+    let mut program = noop();
+    let mut accounts: Vec<Account> = (0..40).map(|_| account!(1000000000)).collect();
+    let instruction = Instruction::new_with_bytes(
+        program.pubkey,
+        &[],
+        accounts.iter().map(|account| AccountMeta::new(*account.pubkey(), false)).collect(),
+    );
+
+    let mut metas: Vec<_> = accounts.iter_mut().map(|account| account.meta(false, true)).collect();
+    metas.push(program.meta());
+    let receipt = Transaction::new(&[instruction]).execute(metas);
+    receipt.expect_transaction_err(TransactionError::SanitizeFailure);
+    assert!(receipt.to_string().contains("Transaction too large: 1393 bytes (max: 1232 bytes)"));
+
+    // Direct invocations are only held to the limits when asked to
+    let metas: Vec<_> = accounts.iter_mut().map(|account| account.meta(false, true)).collect();
+    program.invoke_with_bytes(&[], metas).expect_ok();
+    let metas: Vec<_> = accounts.iter_mut().map(|account| account.meta(false, true)).collect();
+    program.invoke_with_config(&[], metas, RuntimeConfig::new().with_transaction_limits())
+        .expect_transaction_err(TransactionError::SanitizeFailure);
+
+    assert_eq!(Transaction::from_wire(&[0u8; PACKET_DATA_SIZE + 1]).err(), Some(TransactionError::SanitizeFailure));
+}
+
+#[test]
+fn test_account_lock_limit() {
+    let mut program = noop();
+    let payer_keypair = new_keypair();
+    let mut payer = account!(keypair_pubkey(&payer_keypair), 1000000000);
+    let mut accounts: Vec<Account> = (0..70).map(|_| account!(1000000000)).collect();
+    let addresses: Vec<Pubkey> = accounts.iter().map(|account| *account.pubkey()).collect();
+    let data = AddressLookupTable { meta: LookupTableMeta::default(), addresses: Cow::Borrowed(&addresses) }
+        .serialize_for_tests()
+        .unwrap();
+    let mut table = account!(Pubkey::new_rand(), &address_lookup_table::ID, data);
+
+    // Loaded addresses take a byte each, the transaction fits but locks too many accounts
+    let instruction = Instruction::new_with_bytes(
+        program.pubkey,
+        &[],
+        addresses.iter().map(|address| AccountMeta::new_readonly(*address, false)).collect(),
+    );
+    let lookup = AddressLookupTableAccount { key: *table.pubkey(), addresses: addresses.clone() };
+    let bytes = Transaction::new(&[instruction])
+        .to_wire_v0(&ClusterSettings::new().blockhash, &[&payer_keypair], &[lookup]);
+    assert!(bytes.len() <= PACKET_DATA_SIZE);
+
+    let mut run = |config: RuntimeConfig| {
+        let mut metas: Vec<_> = accounts.iter_mut().map(|account| account.meta(false, false)).collect();
+        metas.extend([payer.meta(false, false), table.meta(false, false), program.meta()]);
+        Transaction::from_wire(&bytes).unwrap().execute_with_config(metas, config)
+    };
+    let receipt = run(RuntimeConfig::new());
+    receipt.expect_transaction_err(TransactionError::TooManyAccountLocks);
+    assert!(receipt.to_string().contains("Transaction locks 72 accounts (max: 64)"));
+
+    let mut config = RuntimeConfig::new();
+    config.settings.transaction_account_lock_limit = INCREASED_MAX_TX_ACCOUNT_LOCKS;
+    run(config).expect_ok();
+}
+
+#[test]
+fn test_cpi_limits() {
+    let mut callee = noop();
+    let callee_id = callee.pubkey;
+    let caller = Program::from_fn(move |_, accounts, data| {
+        match data[0] {
+            0 => invoke(&Instruction::new_with_bytes(callee_id, &vec![0u8; 10 * 1024 + 1], vec![]), accounts),
+            _ => invoke(&Instruction::new_with_bytes(callee_id, &[], vec![]), &vec![accounts[0].clone(); data[0] as usize]),
+        }
+    }, Pubkey::new_rand());
+
+    let receipt = caller.invoke_with_bytes(&[0], vec![callee.meta()]);
+    receipt.expect_err(ProgramError::MaxInstructionDataLenExceeded);
+    assert!(receipt.to_string().contains("Invoked an instruction with data that is too large (10241 > 10240)"));
+
+    let receipt = caller.invoke_with_bytes(&[65], vec![callee.meta()]);
+    receipt.expect_err(ProgramError::MaxInstructionAccountInfosExceeded);
+    assert!(receipt.to_string().contains("Invoked an instruction with too many account info's (65 > 64)"));
+
+    caller.invoke_with_bytes(&[64], vec![callee.meta()]).expect_ok();
+}
@@ -0,0 +1,83 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::instruction::Instruction;
+use solana_program::log::{sol_log_64, sol_log_data};
+use solana_program::msg;
+use solana_program::program::invoke;
+use solana_program::pubkey::Pubkey;
+use crate::runtime::{LogMatcher, Program, Receipt};
+
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq)]
+struct Swapped {
+    amount_in: u64,
+    amount_out: u64,
+}
+
+fn invoke_logging(callee: &mut Program) -> Receipt {
+    let callee_id = callee.pubkey;
+    let caller = Program::from_fn(move |_, accounts, _| {
+        msg!("Start");
+        sol_log_64(1, 2, 3, 4, 0xff);
+        invoke(&Instruction::new_with_bytes(callee_id, &[], vec![]), accounts)?;
+        msg!("Done in 42 steps");
+        Ok(())
+    }, Pubkey::new_rand());
+
+    caller.invoke_with_bytes(&[], vec![callee.meta()])
+}
+
+fn callee() -> Program {
+    Program::from_fn(|_, _, _| {
+        msg!("Step 1");
+        msg!("Step 2");
+        sol_log_data(&[&Swapped { amount_in: 10, amount_out: 20 }.try_to_vec().unwrap()]);
+        Ok(())
+    }, Pubkey::new_rand())
+}
+
+#[test]
+fn test_log_matchers() {
+    let mut callee = callee();
+    let receipt = invoke_logging(&mut callee);
+    receipt.expect_ok();
+
+    receipt.expect_log_matching("Start");
+    receipt.expect_log_contains("42 steps");
+    receipt.expect_log_regex(r"^Done in \d+ steps$");
+    receipt.expect_no_log("Step 3");
+    receipt.expect_no_log(LogMatcher::contains("panic"));
+    receipt.expect_log_count(LogMatcher::regex(r"^Step \d$"), 2);
+    receipt.expect_logs_in_order(&[
+        LogMatcher::exact("Start"),
+        LogMatcher::exact("Step 1"),
+        LogMatcher::exact("Step 2"),
+        LogMatcher::contains("Done"),
+    ]);
+    receipt.expect_program_log(&callee.pubkey, "Step 1");
+    assert_eq!(receipt.program_logs(Some(&callee.pubkey)), vec!["Step 1", "Step 2"]);
+}
+
+#[test]
+#[should_panic]
+fn test_log_order_mismatch() {
+    let mut callee = callee();
+    invoke_logging(&mut callee).expect_logs_in_order(&[LogMatcher::exact("Step 2"), LogMatcher::exact("Step 1")]);
+}
+
+#[test]
+#[should_panic]
+fn test_log_scoped_to_program() {
+    let mut callee = callee();
+    invoke_logging(&mut callee).expect_program_log(&callee.pubkey, "Start");
+}
+
+#[test]
+fn test_decoded_logs() {
+    let mut callee = callee();
+    let receipt = invoke_logging(&mut callee);
+
+    receipt.expect_log_64([1, 2, 3, 4, 255]);
+    assert_eq!(receipt.log_u64s(Some(&callee.pubkey)), Vec::<[u64; 5]>::new());
+    receipt.expect_data_as(&callee.pubkey, &Swapped { amount_in: 10, amount_out: 20 });
+    assert_eq!(receipt.data_as::<Swapped>(&callee.pubkey).len(), 1);
+}
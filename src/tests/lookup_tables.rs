@@ -0,0 +1,203 @@
+use std::borrow::Cow;
+use solana_program::address_lookup_table::{self, instruction, state::{AddressLookupTable, LookupTableMeta}, AddressLookupTableAccount};
+use solana_program::message::VersionedMessage;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use solana_program::system_instruction;
+use crate::account;
+use crate::runtime::{Account, ClusterSettings, Program, RuntimeConfig, Transaction, TransactionError};
+use crate::suit::{keypair_pubkey, new_keypair};
+
+
+fn at_slot(slot: u64) -> RuntimeConfig {
+    let mut config = RuntimeConfig::new();
+    config.settings.slot = slot;
+    config
+}
+
+fn table_account(meta: LookupTableMeta, addresses: &[Pubkey]) -> Account {
+    let data = AddressLookupTable { meta, addresses: Cow::Borrowed(addresses) }
+        .serialize_for_tests()
+        .unwrap();
+    account!(Pubkey::new_rand(), &address_lookup_table::ID, data)
+}
+
+fn read_table(account: &Account) -> (LookupTableMeta, Vec<Pubkey>) {
+    let table = AddressLookupTable::deserialize(&account.data).unwrap();
+    (table.meta, table.addresses.into_owned())
+}
+
+#[test]
+fn test_lookup_table_lifecycle() {
+    let mut program = Program::address_lookup_table_program();
+    let mut system_program = Program::system_program();
+    let mut authority = account!(0);
+    let mut payer = account!(1000000000);
+    let addresses = [Pubkey::new_rand(), Pubkey::new_rand()];
+
+    let (create, table_key) = instruction::create_lookup_table(*authority.pubkey(), *payer.pubkey(), 10);
+    let extend = instruction::extend_lookup_table(table_key, *authority.pubkey(), Some(*payer.pubkey()), addresses.to_vec());
+    let mut table = account!(table_key, 0);
+    let receipt = Transaction::new(&[create, extend]).execute_with_config(vec![
+        table.meta(false, true),
+        authority.meta(true, false),
+        payer.meta(true, true),
+        system_program.meta(),
+        program.meta(),
+    ], at_slot(12));
+    receipt.expect_ok();
+    table.expect_owner(&address_lookup_table::ID);
+    let (meta, stored) = read_table(&table);
+    assert_eq!(stored, addresses);
+    assert_eq!((meta.last_extended_slot, meta.last_extended_slot_start_index), (12, 0));
+    assert_eq!(meta.authority, Some(*authority.pubkey()));
+
+    // Closing is only possible once the table left the recent slots
+    let deactivate = instruction::deactivate_lookup_table(table_key, *authority.pubkey());
+    let close = instruction::close_lookup_table(table_key, *authority.pubkey(), *payer.pubkey());
+    Transaction::new(&[deactivate]).execute_with_config(vec![
+        table.meta(false, true),
+        authority.meta(true, false),
+        program.meta(),
+    ], at_slot(20)).expect_ok();
+    assert_eq!(read_table(&table).0.deactivation_slot, 20);
+
+    let receipt = Transaction::new(std::slice::from_ref(&close)).execute_with_config(vec![
+        table.meta(false, true),
+        authority.meta(true, false),
+        payer.meta(false, true),
+        program.meta(),
+    ], at_slot(100));
+    receipt.expect_transaction_err(TransactionError::InstructionError(0, ProgramError::InvalidArgument));
+    receipt.expect_log("Table cannot be closed until it's fully deactivated in 433 blocks");
+
+    let balance = payer.lamports + table.lamports;
+    Transaction::new(&[close]).execute_with_config(vec![
+        table.meta(false, true),
+        authority.meta(true, false),
+        payer.meta(false, true),
+        program.meta(),
+    ], at_slot(533)).expect_ok();
+    payer.expect_balance(balance);
+    table.expect_balance(0);
+    table.expect_bytes(&[]);
+}
+
+#[test]
+fn test_lookup_table_rejected_changes() {
+    let mut program = Program::address_lookup_table_program();
+    let mut system_program = Program::system_program();
+    let mut authority = account!(0);
+    let mut payer = account!(1000000000);
+    let mut frozen = table_account(LookupTableMeta { authority: None, ..LookupTableMeta::default() }, &[Pubkey::new_rand()]);
+    let mut table = table_account(LookupTableMeta::new(*authority.pubkey()), &[]);
+
+    let (create, table_key) = instruction::create_lookup_table(*authority.pubkey(), *payer.pubkey(), 10);
+    let mut created = account!(table_key, 0);
+    let receipt = Transaction::new(&[create]).execute_with_config(vec![
+        created.meta(false, true),
+        authority.meta(false, false),
+        payer.meta(true, true),
+        system_program.meta(),
+        program.meta(),
+    ], at_slot(600));
+    receipt.expect_transaction_err(TransactionError::InstructionError(0, ProgramError::InvalidInstructionData));
+    receipt.expect_log("10 is not a recent slot");
+
+    let extend = instruction::extend_lookup_table(*frozen.pubkey(), *authority.pubkey(), None, vec![Pubkey::new_rand()]);
+    let receipt = Transaction::new(&[extend]).execute(vec![
+        frozen.meta(false, true),
+        authority.meta(true, false),
+        program.meta(),
+    ]);
+    receipt.expect_transaction_err(TransactionError::InstructionError(0, ProgramError::Immutable));
+
+    let freeze = instruction::freeze_lookup_table(*table.pubkey(), *authority.pubkey());
+    let receipt = Transaction::new(&[freeze]).execute(vec![
+        table.meta(false, true),
+        authority.meta(true, false),
+        program.meta(),
+    ]);
+    receipt.expect_transaction_err(TransactionError::InstructionError(0, ProgramError::InvalidInstructionData));
+    receipt.expect_log("Empty lookup tables cannot be frozen");
+
+    let extend = instruction::extend_lookup_table(*table.pubkey(), *authority.pubkey(), None, vec![Pubkey::new_rand()]);
+    let receipt = Transaction::new(&[extend]).execute(vec![
+        table.meta(false, true),
+        authority.meta(false, false),
+        program.meta(),
+    ]);
+    receipt.expect_transaction_err(TransactionError::InstructionError(0, ProgramError::MissingRequiredSignature));
+}
+
+#[test]
+fn test_v0_transaction_loads_from_table() {
+    let mut program = Program::address_lookup_table_program();
+    let mut system_program = Program::system_program();
+    let payer_keypair = new_keypair();
+    let mut payer = account!(keypair_pubkey(&payer_keypair), 1000000000);
+    let mut to = account!(1000000000);
+    let mut table = table_account(LookupTableMeta::default(), &[Pubkey::new_rand(), *to.pubkey()]);
+
+    let transfer = system_instruction::transfer(payer.pubkey(), to.pubkey(), 700);
+    let lookup = AddressLookupTableAccount { key: *table.pubkey(), addresses: read_table(&table).1 };
+    let bytes = Transaction::new(&[transfer])
+        .to_wire_v0(&ClusterSettings::new().blockhash, &[&payer_keypair], &[lookup]);
+
+    let transaction = Transaction::from_wire(&bytes).unwrap();
+    match transaction.message().unwrap() {
+        VersionedMessage::V0(message) => {
+            assert!(!message.account_keys.contains(to.pubkey()));
+            assert_eq!(message.address_table_lookups[0].writable_indexes, vec![1]);
+        }
+        message => panic!("Expected a v0 message, got {:?}", message),
+    }
+
+    // The table is read, not locked, it needn't be writable nor among the message keys
+    let receipt = transaction.execute(vec![
+        payer.meta(false, false),
+        to.meta(false, false),
+        table.meta(false, false),
+        system_program.meta(),
+        program.meta(),
+    ]);
+    receipt.expect_ok();
+    payer.expect_balance(1000000000 - 700 - 5000);
+    to.expect_balance(1000000000 + 700);
+}
+
+#[test]
+fn test_v0_transaction_lookup_failures() {
+    let mut system_program = Program::system_program();
+    let payer_keypair = new_keypair();
+    let mut payer = account!(keypair_pubkey(&payer_keypair), 1000000000);
+    let mut to = account!(1000000000);
+    let to_key = *to.pubkey();
+    let transfer = system_instruction::transfer(payer.pubkey(), &to_key, 700);
+
+    let mut run = |mut table: Account, slot: u64| {
+        let lookup = AddressLookupTableAccount { key: *table.pubkey(), addresses: vec![to_key] };
+        let bytes = Transaction::new(std::slice::from_ref(&transfer))
+            .to_wire_v0(&ClusterSettings::new().blockhash, &[&payer_keypair], &[lookup]);
+        Transaction::from_wire(&bytes).unwrap().execute_with_config(vec![
+            payer.meta(false, false),
+            to.meta(false, false),
+            table.meta(false, false),
+            system_program.meta(),
+        ], at_slot(slot))
+    };
+
+    // Addresses extended in the current slot can't be used until the next one
+    let meta = LookupTableMeta { last_extended_slot: 5, ..LookupTableMeta::default() };
+    let receipt = run(table_account(meta.clone(), &[to_key]), 5);
+    receipt.expect_transaction_err(TransactionError::InvalidAddressLookupTableIndex);
+    run(table_account(meta, &[to_key]), 6).expect_ok();
+
+    let meta = LookupTableMeta { deactivation_slot: 5, ..LookupTableMeta::default() };
+    run(table_account(meta.clone(), &[to_key]), 517).expect_ok();
+    let receipt = run(table_account(meta, &[to_key]), 518);
+    receipt.expect_transaction_err(TransactionError::AddressLookupTableNotFound);
+
+    let receipt = run(account!(Pubkey::new_rand(), &solana_program::system_program::ID, vec![0u8; 56]), 1);
+    receipt.expect_transaction_err(TransactionError::InvalidAddressLookupTableOwner);
+}
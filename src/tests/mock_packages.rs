@@ -0,0 +1,149 @@
+use simple_pool_contract::{
+    instruction::PoolInstruction,
+    pool::Pool,
+    token::get_vault_account_address
+};
+use solana_program::program::get_return_data;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use crate::{account, mint_account, token_account};
+use crate::executor::MockResponse;
+use crate::runtime::Program;
+use crate::suit::{SPL_PROGRAM_ID, SYSTEM_PROGRAM_ID, TypedAccount};
+
+
+#[test]
+fn test_mock_records_token_calls() {
+    let pool_program = Program::inline(simple_pool_contract::entrypoint::entrypoint, Pubkey::new_rand());
+    let (mut token_program, token_mock) = Program::mock(SPL_PROGRAM_ID.clone());
+
+    let mut owner = account!(1000000000);
+    let mut mint1 = mint_account!(0, owner);
+    let mut mint2 = mint_account!(3, owner);
+    let mut token_acc1 = token_account!(mint1, owner);
+    let mut token_acc2 = token_account!(mint2, owner);
+
+    let mut pool = TypedAccount::new_borshed(
+        Pool::find_address(pool_program.pubkey(), owner.pubkey(), mint1.pubkey(), mint2.pubkey()),
+        pool_program.pubkey(),
+        Pool::new(
+            owner.pubkey().clone(),
+            mint1.pubkey().clone(),
+            mint2.pubkey().clone(),
+            SYSTEM_PROGRAM_ID.clone(),
+            SYSTEM_PROGRAM_ID.clone(),
+        )
+    );
+    let mut vault1 = token_account!(get_vault_account_address(pool_program.pubkey(), mint1.pubkey()), mint1, pool);
+    let mut vault2 = token_account!(get_vault_account_address(pool_program.pubkey(), mint2.pubkey()), mint2, pool);
+    pool.vault_left = vault1.pubkey().clone();
+    pool.vault_right = vault2.pubkey().clone();
+
+    let receipt = pool_program.invoke_with_borsh(
+        &PoolInstruction::ProvideLiquidity {amount_left: 1000, amount_right: 2000},
+        vec![
+            owner.meta(true, true),
+            token_program.meta(),
+            pool.meta(false, true),
+            mint1.meta(false, true),
+            mint2.meta(false, true),
+            vault1.meta(false, true),
+            vault2.meta(false, true),
+            token_acc1.meta(false, true),
+            token_acc2.meta(false, true)
+        ]
+    );
+
+    receipt.expect_ok();
+    token_mock.expect_calls(2);
+    token_mock.expect_call(0, &spl_token::instruction::transfer(
+        &SPL_PROGRAM_ID, token_acc1.pubkey(), vault1.pubkey(), owner.pubkey(), &[], 1000
+    ).unwrap());
+    token_mock.expect_call(1, &spl_token::instruction::transfer(
+        &SPL_PROGRAM_ID, token_acc2.pubkey(), vault2.pubkey(), owner.pubkey(), &[], 2000
+    ).unwrap());
+    assert_eq!(pool.amount_left, 1000);
+    assert_eq!(pool.amount_right, 2000);
+}
+
+#[test]
+fn test_mock_scripted_failure() {
+    let pool_program = Program::inline(simple_pool_contract::entrypoint::entrypoint, Pubkey::new_rand());
+    let (mut token_program, token_mock) = Program::mock(SPL_PROGRAM_ID.clone());
+    token_mock
+        .respond(MockResponse::ok())
+        .respond(MockResponse::err(ProgramError::Custom(1)).with_log("Insufficient funds"));
+
+    let mut owner = account!(1000000000);
+    let mut mint1 = mint_account!(0, owner);
+    let mut mint2 = mint_account!(3, owner);
+    let mut token_acc1 = token_account!(mint1, owner);
+    let mut token_acc2 = token_account!(mint2, owner);
+
+    let mut pool = TypedAccount::new_borshed(
+        Pool::find_address(pool_program.pubkey(), owner.pubkey(), mint1.pubkey(), mint2.pubkey()),
+        pool_program.pubkey(),
+        Pool::new(
+            owner.pubkey().clone(),
+            mint1.pubkey().clone(),
+            mint2.pubkey().clone(),
+            SYSTEM_PROGRAM_ID.clone(),
+            SYSTEM_PROGRAM_ID.clone(),
+        )
+    );
+    let mut vault1 = token_account!(get_vault_account_address(pool_program.pubkey(), mint1.pubkey()), mint1, pool);
+    let mut vault2 = token_account!(get_vault_account_address(pool_program.pubkey(), mint2.pubkey()), mint2, pool);
+    pool.vault_left = vault1.pubkey().clone();
+    pool.vault_right = vault2.pubkey().clone();
+
+    let receipt = pool_program.invoke_with_borsh(
+        &PoolInstruction::ProvideLiquidity {amount_left: 1000, amount_right: 2000},
+        vec![
+            owner.meta(true, true),
+            token_program.meta(),
+            pool.meta(false, true),
+            mint1.meta(false, true),
+            mint2.meta(false, true),
+            vault1.meta(false, true),
+            vault2.meta(false, true),
+            token_acc1.meta(false, true),
+            token_acc2.meta(false, true)
+        ]
+    );
+
+    receipt.expect_err(ProgramError::Custom(1));
+    receipt.expect_log("Insufficient funds");
+    token_mock.expect_calls(2);
+}
+
+#[test]
+fn test_fn_package_mutations_and_return_data() {
+    let (mut callee, callee_mock) = Program::mock(Pubkey::new_rand());
+    let mut target = account!(1000000000);
+    callee_mock.respond_always(
+        MockResponse::ok()
+            .with_lamports(target.pubkey(), 1000000700)
+            .with_data(target.pubkey(), vec![7, 7])
+            .with_return_data(&[42])
+    );
+
+    let callee_id = callee.pubkey().clone();
+    let caller = Program::from_fn(move |_program_id, accounts, _data| {
+        let instruction = solana_program::instruction::Instruction::new_with_bytes(
+            callee_id,
+            &[1, 2, 3],
+            vec![solana_program::instruction::AccountMeta::new(*accounts[0].key, false)],
+        );
+        solana_program::program::invoke(&instruction, accounts)?;
+        match get_return_data() {
+            Some((program_id, data)) if program_id == callee_id && data == vec![42] => Ok(()),
+            _ => Err(ProgramError::InvalidAccountData),
+        }
+    }, Pubkey::new_rand());
+
+    let receipt = caller.invoke_with_bytes(&[], vec![target.meta(false, true), callee.meta()]);
+    receipt.expect_ok();
+    target.expect_balance(1000000700);
+    target.expect_bytes(&[7, 7]);
+    assert_eq!(callee_mock.call(0).data, vec![1, 2, 3]);
+}
@@ -1,4 +1,33 @@
+use crate::runtime::TrackingAllocator;
+
+#[global_allocator]
+static ALLOCATOR: TrackingAllocator = TrackingAllocator;
+
 // mod external_contracts;
 // mod suit;
 // mod inline_contracts;
-mod spl_pool;
\ No newline at end of file
+mod spl_pool;
+mod package_locator;
+mod upgradeable;
+mod mock_packages;
+mod fault_injection;
+mod observer;
+mod account_diffs;
+mod labels;
+mod snapshots;
+mod log_matchers;
+mod events;
+mod return_data;
+mod rent;
+mod heap;
+mod compute_budget;
+mod fees;
+mod wire_transactions;
+mod lookup_tables;
+mod limits;
+mod fixtures;
+mod environment;
+mod account_store;
+mod banks_client;
+mod rpc;
+mod account_queries;
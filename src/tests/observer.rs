@@ -0,0 +1,62 @@
+use std::sync::{Arc, Mutex};
+use solana_program::instruction::Instruction;
+use solana_program::msg;
+use solana_program::program::{invoke, set_return_data};
+use solana_program::pubkey::Pubkey;
+use crate::runtime::{InvokeStage, Program, RuntimeConfig, RuntimeEvent, Verbosity};
+
+
+#[test]
+fn test_observer_receives_runtime_events() {
+    let callee = Program::from_fn(|_, _, data| {
+        msg!("Callee {}", data[0]);
+        set_return_data(data);
+        Ok(())
+    }, Pubkey::new_rand());
+    let callee_id = callee.pubkey;
+    let caller = Program::from_fn(move |_, accounts, data| {
+        invoke(&Instruction::new_with_bytes(callee_id, data, vec![]), accounts)
+    }, Pubkey::new_rand());
+
+    let events: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
+    let recorded = events.clone();
+    let config = RuntimeConfig::new()
+        .with_verbosity(Verbosity::Quiet)
+        .with_observer(move |event: &RuntimeEvent| {
+            let name = match event {
+                RuntimeEvent::InvokeStarted { stack_height, .. } => format!("start {}", stack_height),
+                RuntimeEvent::InvokeFinished { stack_height, result, .. } => format!("finish {} {:?}", stack_height, result),
+                RuntimeEvent::Accounts { stage: InvokeStage::Before, .. } => "before".to_string(),
+                RuntimeEvent::Accounts { stage: InvokeStage::After, .. } => "after".to_string(),
+                RuntimeEvent::Log { message } => message.to_string(),
+                RuntimeEvent::ReturnData { data, .. } => format!("return {:?}", data),
+                _ => "other".to_string(),
+            };
+            recorded.lock().unwrap().push(name);
+        });
+
+    let mut callee = callee;
+    let receipt = caller.invoke_with_config(&[9], vec![callee.meta()], config);
+    receipt.expect_ok();
+
+    let events = events.lock().unwrap().clone();
+    assert_eq!(events[0], "start 1");
+    assert!(events.contains(&"before".to_string()));
+    assert!(events.contains(&"Program logged: \"Callee 9\"".to_string()));
+    assert!(events.contains(&"return [9]".to_string()));
+    assert!(events.contains(&"after".to_string()));
+    assert_eq!(events.last().unwrap(), "finish 1 Ok(())");
+}
+
+#[test]
+fn test_receipt_display() {
+    let program = Program::from_fn(|_, _, _| {
+        msg!("Hello");
+        Ok(())
+    }, Pubkey::new_rand());
+
+    let receipt = program.invoke_with_bytes(&[], vec![]);
+    let printed = receipt.to_string();
+    assert!(printed.starts_with("Receipt: Ok(())"));
+    assert!(printed.contains("Program logged: \"Hello\""));
+}
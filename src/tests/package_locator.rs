@@ -0,0 +1,51 @@
+use std::{fs, process};
+use std::path::PathBuf;
+use libloading::library_filename;
+use crate::executor::{PackageError, PackageLocator};
+
+
+// Unique per run and test, concurrent runs don't share fixtures
+fn temp_target_dir(test: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("fulgurite_{}_{}", test, process::id()))
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Runs cargo metadata
+fn test_missing_package_lists_tried_paths() {
+    let target_dir = temp_target_dir("missing_target");
+    let err = PackageLocator::new("missing-contract")
+        .target_dir(&target_dir)
+        .profile("release")
+        .locate()
+        .unwrap_err();
+
+    match &err {
+        PackageError::NotFound { package, tried } => {
+            assert_eq!(package, "missing-contract");
+            assert!(tried.contains(&target_dir.join("release").join(library_filename("missing_contract"))));
+            assert!(tried.contains(&target_dir.join("release").join("deps").join(library_filename("missing_contract"))));
+            assert!(tried.iter().any(|path| path.starts_with(target_dir.join("debug"))));
+        }
+        _ => panic!("Unexpected error {}", err),
+    }
+    assert!(err.to_string().contains(&target_dir.join("release").display().to_string()));
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Runs cargo metadata
+fn test_locate_in_custom_target_dir() {
+    let target_dir = temp_target_dir("custom_target");
+    let profile_dir = target_dir.join("release");
+    fs::create_dir_all(&profile_dir).unwrap();
+    let lib_path = profile_dir.join(library_filename("located_contract"));
+    fs::write(&lib_path, []).unwrap();
+
+    let path = PackageLocator::new("located-contract")
+        .target_dir(&target_dir)
+        .profile("release")
+        .locate()
+        .unwrap();
+    assert_eq!(path, lib_path);
+
+    fs::remove_dir_all(&target_dir).unwrap();
+}
@@ -0,0 +1,84 @@
+use solana_program::program::invoke;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::system_instruction;
+use solana_program::sysvar::Sysvar;
+use crate::account;
+use crate::runtime::{Account, Program};
+use crate::suit::rent_exempt_for_size;
+
+
+// Moves lamports from the first account to the second, resizing the second to the instruction byte
+fn mover() -> Program {
+    Program::from_fn(|_, accounts, data| {
+        **accounts[0].lamports.borrow_mut() -= 100;
+        **accounts[1].lamports.borrow_mut() += 100;
+        if let Some(size) = data.first() {
+            accounts[1].realloc(*size as usize, false)?;
+        }
+        Ok(())
+    }, Pubkey::new_rand())
+}
+
+#[test]
+fn test_single_rent_model() {
+    let rent = Rent::default();
+    assert_eq!(rent_exempt_for_size(0), rent.minimum_balance(0));
+    assert_eq!(rent_exempt_for_size(165), rent.minimum_balance(165));
+    assert_eq!(account!(Pubkey::new_rand(), &Pubkey::new_rand(), vec![0u8; 10]).lamports, rent.minimum_balance(10));
+}
+
+#[test]
+fn test_create_account_with_sysvar_rent() {
+    let mut system_program = Program::system_program();
+    let owner = Pubkey::new_rand();
+    let program = Program::from_fn(move |_, accounts, _| {
+        let lamports = Rent::get()?.minimum_balance(42);
+        invoke(&system_instruction::create_account(accounts[0].key, accounts[1].key, lamports, 42, &owner), accounts)
+    }, Pubkey::new_rand());
+
+    let mut payer = account!(1000000000);
+    let mut new_account = account!();
+    let receipt = program.invoke_with_bytes(&[], vec![
+        payer.meta(true, true),
+        new_account.meta(true, true),
+        system_program.meta(),
+    ]);
+    receipt.expect_ok();
+    new_account.expect_balance(rent_exempt_for_size(42));
+}
+
+#[test]
+fn test_rent_state_transitions() {
+    let program = mover();
+    let rent_paying = Rent::default().minimum_balance(0) - 1000;
+
+    // Uninitialized -> rent paying
+    let mut payer = account!(1000000000);
+    let mut empty = account!();
+    let receipt = program.invoke_with_bytes(&[], vec![payer.meta(true, true), empty.meta(false, true)]);
+    receipt.expect_err(ProgramError::AccountNotRentExempt);
+
+    // Rent paying -> rent paying with less lamports
+    let mut paying = account!(rent_paying);
+    let mut exempt = account!(1000000000);
+    let receipt = program.invoke_with_bytes(&[], vec![paying.meta(true, true), exempt.meta(false, true)]);
+    receipt.expect_ok();
+
+    // Rent paying -> rent paying with more lamports
+    let mut paying = account!(rent_paying);
+    let receipt = program.invoke_with_bytes(&[], vec![exempt.meta(true, true), paying.meta(false, true)]);
+    receipt.expect_err(ProgramError::AccountNotRentExempt);
+
+    // Rent exempt -> rent paying by growing
+    let mut growing = Account::new(Pubkey::new_rand(), rent_exempt_for_size(0), &program.pubkey, Vec::new());
+    let receipt = program.invoke_with_bytes(&[200], vec![exempt.meta(true, true), growing.meta(false, true)]);
+    receipt.expect_err(ProgramError::AccountNotRentExempt);
+
+    // Read-only accounts are not checked
+    let mut paying = account!(rent_paying);
+    let receipt = Program::from_fn(|_, _, _| Ok(()), Pubkey::new_rand())
+        .invoke_with_bytes(&[], vec![paying.meta(false, false)]);
+    receipt.expect_ok();
+}
@@ -0,0 +1,80 @@
+use solana_program::instruction::Instruction;
+use solana_program::msg;
+use solana_program::program::{get_return_data, invoke, set_return_data, MAX_RETURN_DATA};
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use crate::{account, mint_account};
+use crate::runtime::Program;
+use crate::suit::SPL_PROGRAM_ID;
+
+
+#[test]
+fn test_token_return_data() {
+    let token_program = Program::token_program();
+    let owner = account!(1000000000);
+    let mut mint = mint_account!(Pubkey::new_rand(), 2, owner);
+
+    let instruction = spl_token::instruction::get_account_data_size(&SPL_PROGRAM_ID, mint.pubkey()).unwrap();
+    let receipt = token_program.invoke_with_bytes(&instruction.data, vec![mint.meta(false, false)]);
+    receipt.expect_ok();
+    // 165 as u64, reported without trailing zeros
+    receipt.expect_return_data(&SPL_PROGRAM_ID, &[165]);
+    assert_eq!(receipt.raw_return_data(), Some((SPL_PROGRAM_ID, vec![165, 0, 0, 0, 0, 0, 0, 0])));
+    receipt.expect_return_data_as(&165u64);
+
+    let instruction = spl_token::instruction::amount_to_ui_amount(&SPL_PROGRAM_ID, mint.pubkey(), 150).unwrap();
+    let receipt = token_program.invoke_with_bytes(&instruction.data, vec![mint.meta(false, false)]);
+    receipt.expect_ok();
+    receipt.expect_return_data(&SPL_PROGRAM_ID, b"1.5");
+}
+
+#[test]
+fn test_return_data_visible_after_cpi() {
+    let mut token_program = Program::token_program();
+    let owner = account!(1000000000);
+    let mut mint = mint_account!(Pubkey::new_rand(), 2, owner);
+    let caller = Program::from_fn(|_, accounts, _| {
+        set_return_data(b"cleared by the callee");
+        invoke(&spl_token::instruction::amount_to_ui_amount(&SPL_PROGRAM_ID, accounts[0].key, 1234).unwrap(), accounts)?;
+        let (program_id, data) = get_return_data().unwrap();
+        msg!("{} returned {}", program_id, String::from_utf8(data).unwrap());
+        Ok(())
+    }, Pubkey::new_rand());
+
+    let receipt = caller.invoke_with_bytes(&[], vec![mint.meta(false, false), token_program.meta()]);
+    receipt.expect_ok();
+    receipt.expect_log(&format!("{} returned 12.34", SPL_PROGRAM_ID));
+    receipt.expect_return_data(&SPL_PROGRAM_ID, b"12.34");
+}
+
+#[test]
+fn test_empty_return_data() {
+    let mut callee = Program::from_fn(|_, _, _| {
+        set_return_data(&[0, 0]);
+        Ok(())
+    }, Pubkey::new_rand());
+    let callee_id = callee.pubkey;
+    let caller = Program::from_fn(move |_, accounts, _| {
+        invoke(&Instruction::new_with_bytes(callee_id, &[], vec![]), accounts)?;
+        msg!("Got {:?}", get_return_data());
+        Ok(())
+    }, Pubkey::new_rand());
+
+    let receipt = caller.invoke_with_bytes(&[], vec![callee.meta()]);
+    receipt.expect_ok();
+    receipt.expect_log(&format!("Got Some(({}, [0, 0]))", callee_id));
+    assert_eq!(receipt.return_data(), None);
+}
+
+#[test]
+fn test_return_data_too_large() {
+    let program = Program::from_fn(|_, _, _| {
+        set_return_data(&[1; MAX_RETURN_DATA + 1]);
+        Ok(())
+    }, Pubkey::new_rand());
+
+    let receipt = program.invoke_with_bytes(&[], vec![]);
+    receipt.expect_err(ProgramError::ReturnDataTooLarge);
+    assert!(receipt.raw_return_data().is_none());
+    assert!(receipt.to_string().contains("failed: Return data too large (1025 > 1024)"));
+}
@@ -0,0 +1,136 @@
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use serde_json::{json, Value};
+use solana_program::{pubkey::Pubkey, system_instruction};
+use crate::account;
+use crate::rpc::RpcServer;
+use crate::runtime::{block_on, Environment, Program, Transaction};
+use crate::suit::{keypair_pubkey, new_keypair};
+
+
+fn post(server: &RpcServer, body: &Value) -> Value {
+    let body = body.to_string();
+    let mut stream = TcpStream::connect(server.address()).unwrap();
+    write!(
+        stream,
+        "POST / HTTP/1.1\r\nHost: localhost\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        body.len(),
+        body,
+    ).unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    let (_, body) = response.split_once("\r\n\r\n").unwrap();
+    serde_json::from_str(body).unwrap()
+}
+
+fn call(server: &RpcServer, method: &str, params: Value) -> Value {
+    post(server, &json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params }))
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Serves over TCP
+fn test_send_and_confirm() {
+    let payer_keypair = new_keypair();
+    let payer = keypair_pubkey(&payer_keypair);
+    let recipient = Pubkey::new_rand();
+    let mut env = Environment::new();
+    env.add_program(Program::system_program());
+    env.add_account(account!(payer, 1000000000));
+    let server = RpcServer::start(env).unwrap();
+
+    let blockhash = call(&server, "getLatestBlockhash", json!([]));
+    let blockhash = blockhash["result"]["value"]["blockhash"].as_str().unwrap().parse().unwrap();
+    let wire = Transaction::new(&[system_instruction::transfer(&payer, &recipient, 1000000)])
+        .to_wire(&blockhash, &[&payer_keypair]);
+    let signature = call(&server, "sendTransaction", json!([bs58::encode(&wire).into_string()]));
+    let signature = signature["result"].as_str().unwrap();
+
+    let statuses = call(&server, "getSignatureStatuses", json!([[signature]]));
+    assert_eq!(statuses["result"]["value"][0]["status"], json!({ "Ok": null }));
+    let balance = call(&server, "getBalance", json!([recipient.to_string()]));
+    assert_eq!(balance["result"]["value"], 1000000);
+
+    let transaction = call(&server, "getTransaction", json!([signature, { "encoding": "json" }]));
+    let meta = &transaction["result"]["meta"];
+    assert_eq!(meta["fee"], 5000);
+    assert_eq!(meta["preBalances"][0], 1000000000);
+    assert_eq!(meta["postBalances"][0], 1000000000 - 1000000 - 5000);
+    assert_eq!(meta["postBalances"][1], 1000000);
+    let system_program = solana_program::system_program::ID.to_string();
+    assert_eq!(meta["logMessages"][0], format!("Program {} invoke [1]", system_program));
+    assert_eq!(meta["logMessages"][1], format!("Program {} consumed 0 of 200000 compute units", system_program));
+    assert_eq!(meta["logMessages"].as_array().unwrap().last().unwrap(), &json!(format!("Program {} success", system_program)));
+    assert_eq!(transaction["result"]["transaction"]["message"]["accountKeys"][0], payer.to_string());
+
+    let resent = call(&server, "sendTransaction", json!([bs58::encode(&wire).into_string()]));
+    assert_eq!(resent["error"]["code"], -32002);
+
+    // The served environment is shared with in-process clients
+    let mut banks_client = server.banks_client();
+    assert_eq!(block_on(banks_client.get_balance(recipient)).unwrap(), 1000000);
+    assert!(server.url().starts_with("http://127.0.0.1:"));
+    server.stop();
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Serves over TCP
+fn test_simulation_does_not_land() {
+    let payer_keypair = new_keypair();
+    let payer = keypair_pubkey(&payer_keypair);
+    let recipient = Pubkey::new_rand();
+    let mut env = Environment::new();
+    env.add_program(Program::system_program());
+    env.add_account(account!(payer, 1000000000));
+    let server = RpcServer::start(env).unwrap();
+
+    let blockhash = server.environment().settings().blockhash;
+    let wire = Transaction::new(&[system_instruction::transfer(&payer, &recipient, 1000000)])
+        .to_wire(&blockhash, &[&payer_keypair]);
+    let config = json!({ "encoding": "base64", "accounts": { "encoding": "base64", "addresses": [recipient.to_string()] } });
+    let simulation = call(&server, "simulateTransaction", json!([base64::Engine::encode(&base64::prelude::BASE64_STANDARD, &wire), config]));
+    assert_eq!(simulation["result"]["value"]["err"], Value::Null);
+    assert_eq!(simulation["result"]["value"]["accounts"][0]["lamports"], 1000000);
+    assert!(server.environment().get_account(&recipient).is_none());
+
+    // Preflight refuses a transfer the payer can't cover
+    let wire = Transaction::new(&[system_instruction::transfer(&payer, &recipient, 2000000000)])
+        .to_wire(&blockhash, &[&payer_keypair]);
+    let sent = call(&server, "sendTransaction", json!([bs58::encode(&wire).into_string()]));
+    assert_eq!(sent["error"]["code"], -32002);
+    assert_eq!(sent["error"]["data"]["err"]["InstructionError"][0], 0);
+    let system_program = solana_program::system_program::ID.to_string();
+    let logs = sent["error"]["data"]["logs"].as_array().unwrap();
+    assert_eq!(logs.last().unwrap(), &json!(format!("Program {} failed: custom program error: 0x1", system_program)));
+    server.environment().get_account(&payer).unwrap().expect_balance(1000000000);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Serves over TCP
+fn test_program_accounts_and_batches() {
+    let program_id = Pubkey::new_rand();
+    let mut env = Environment::new();
+    env.add_account(account!(Pubkey::new_rand(), &program_id, vec![1, 2, 3, 4]));
+    env.add_account(account!(Pubkey::new_rand(), &program_id, vec![1, 9, 3, 4]));
+    env.add_account(account!(Pubkey::new_rand(), &program_id, vec![1, 2]));
+    env.add_account(account!(Pubkey::new_rand(), &Pubkey::new_rand(), vec![1, 2, 3, 4]));
+    let server = RpcServer::start(env).unwrap();
+
+    let config = json!({
+        "encoding": "base64",
+        "dataSlice": { "offset": 2, "length": 2 },
+        "filters": [{ "dataSize": 4 }, { "memcmp": { "offset": 1, "bytes": bs58::encode([2]).into_string() } }],
+    });
+    let accounts = call(&server, "getProgramAccounts", json!([program_id.to_string(), config]));
+    let accounts = accounts["result"].as_array().unwrap();
+    assert_eq!(accounts.len(), 1);
+    assert_eq!(accounts[0]["account"]["data"], json!(["AwQ=", "base64"]));
+    assert_eq!(accounts[0]["account"]["space"], 4);
+
+    let body = json!([
+        { "jsonrpc": "2.0", "id": 1, "method": "getHealth" },
+        { "jsonrpc": "2.0", "id": 2, "method": "getSupply" },
+    ]);
+    let responses = post(&server, &body);
+    assert_eq!(responses[0]["result"], "ok");
+    assert_eq!(responses[1]["error"]["code"], -32601);
+}
@@ -0,0 +1,54 @@
+use solana_program::clock::Clock;
+use solana_program::instruction::Instruction;
+use solana_program::msg;
+use solana_program::program::invoke;
+use solana_program::pubkey::Pubkey;
+use solana_program::sysvar::Sysvar;
+use crate::account;
+use crate::runtime::Program;
+
+
+const SNAPSHOT_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src/tests/snapshots");
+
+fn invoke_transfer() -> crate::runtime::Receipt {
+    let mut callee = Program::from_fn(|_, accounts, _| {
+        **accounts[0].lamports.borrow_mut() -= 30;
+        **accounts[1].lamports.borrow_mut() += 30;
+        msg!("Moved 30 to {}", accounts[1].key);
+        Ok(())
+    }, Pubkey::new_rand()).with_label("transfer_program");
+    let callee_id = callee.pubkey;
+    let caller = Program::from_fn(move |_, accounts, _| {
+        msg!("Now {}", Clock::get()?.unix_timestamp);
+        invoke(&Instruction::new_with_bytes(callee_id, &[], vec![
+            solana_program::instruction::AccountMeta::new(*accounts[0].key, true),
+            solana_program::instruction::AccountMeta::new(*accounts[1].key, false),
+        ]), accounts)
+    }, Pubkey::new_rand()).with_label("caller_program");
+
+    let mut alice = account!(100).with_label("alice");
+    let mut unlabeled = account!(1000000000);
+
+    caller.invoke_with_bytes(&[], vec![alice.meta(true, true), unlabeled.meta(false, true), callee.meta()])
+}
+
+#[test]
+fn test_receipt_snapshot() {
+    let receipt = invoke_transfer();
+    receipt.expect_ok();
+    receipt.assert_snapshot_in(SNAPSHOT_DIR, "transfer");
+}
+
+#[test]
+fn test_snapshot_is_stable() {
+    assert_eq!(invoke_transfer().to_snapshot(), invoke_transfer().to_snapshot());
+}
+
+#[test]
+#[should_panic]
+fn test_snapshot_mismatch() {
+    let dir = std::env::temp_dir().join(format!("fulgurite-snapshots-{}", Pubkey::new_rand()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("transfer.snap.json"), "{}").unwrap();
+    invoke_transfer().assert_snapshot_in(&dir, "transfer");
+}
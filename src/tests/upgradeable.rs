@@ -0,0 +1,152 @@
+use solana_program::{
+    account_info::AccountInfo,
+    bpf_loader_upgradeable::{self, get_program_data_address, UpgradeableLoaderState},
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    syscalls::{self, Syscalls},
+};
+use crate::account;
+use crate::executor::InlinePackage;
+use crate::runtime::{Account, Environment, Program, Transaction};
+
+
+// v1 stores a single counter byte
+fn counter_v1(_program_id: &Pubkey, accounts: &[AccountInfo], _instruction_data: &[u8], _syscalls: &mut dyn Syscalls) -> ProgramResult {
+    accounts[0].data.borrow_mut()[0] += 1;
+    Ok(())
+}
+
+// v2 migrates the counter to a version-prefixed layout on first touch
+fn counter_v2(_program_id: &Pubkey, accounts: &[AccountInfo], _instruction_data: &[u8], syscalls: &mut dyn Syscalls) -> ProgramResult {
+    syscalls::enter(syscalls, || {
+        let data = accounts[0].data.borrow().to_vec();
+        if data.len() == 1 {
+            accounts[0].realloc(2, false)?;
+            accounts[0].data.borrow_mut().copy_from_slice(&[2, data[0] + 10]);
+        } else {
+            accounts[0].data.borrow_mut()[1] += 10;
+        }
+        Ok(())
+    })
+}
+
+fn counter_env(authority: &Pubkey) -> (Environment, Pubkey, Pubkey) {
+    let program_id = Pubkey::new_rand();
+    let state = Pubkey::new_rand();
+    let mut env = Environment::new();
+    env.add_program(Program::upgradeable_loader());
+    env.add_upgradeable_program(Box::new(InlinePackage::new(counter_v1)), program_id, Some(authority));
+    env.add_account(Account::new(state, 1000000000, &program_id, vec![0]));
+    (env, program_id, state)
+}
+
+fn count(program_id: &Pubkey, state: &Pubkey) -> Transaction {
+    Transaction::new(&[Instruction::new_with_bytes(*program_id, &[], vec![AccountMeta::new(*state, false)])])
+}
+
+fn upgrade(program_id: &Pubkey, buffer: &Pubkey, authority: &Pubkey, spill: &Pubkey) -> Transaction {
+    Transaction::new(&[bpf_loader_upgradeable::upgrade(program_id, buffer, authority, spill)])
+}
+
+
+#[test]
+fn test_upgrade_migrates_state() {
+    let authority = Pubkey::new_rand();
+    let (mut env, program_id, state) = counter_env(&authority);
+
+    env.execute(&count(&program_id, &state)).expect_ok();
+    env.get_account(&state).unwrap().expect_bytes(&[1]);
+
+    let buffer = Pubkey::new_rand();
+    env.add_program_buffer(buffer, Box::new(InlinePackage::new(counter_v2)), &authority);
+    let buffer_lamports = env.get_account(&buffer).unwrap().lamports;
+    let spill = Pubkey::new_rand();
+    env.execute(&upgrade(&program_id, &buffer, &authority, &spill)).expect_ok();
+    env.get_account(&buffer).unwrap().expect_balance(0);
+    env.get_account(&spill).unwrap().expect_balance(buffer_lamports);
+    assert_eq!(env.packages.len(), 1);
+
+    env.execute(&count(&program_id, &state)).expect_ok();
+    env.get_account(&state).unwrap().expect_bytes(&[2, 11]);
+    env.execute(&count(&program_id, &state)).expect_ok();
+    env.get_account(&state).unwrap().expect_bytes(&[2, 21]);
+}
+
+#[test]
+fn test_upgrade_authority_checks() {
+    let authority = Pubkey::new_rand();
+    let intruder = Pubkey::new_rand();
+    let (mut env, program_id, _) = counter_env(&authority);
+    let spill = Pubkey::new_rand();
+
+    // Buffer written by someone else
+    let buffer = Pubkey::new_rand();
+    env.add_program_buffer(buffer, Box::new(InlinePackage::new(counter_v2)), &intruder);
+    env.execute(&upgrade(&program_id, &buffer, &intruder, &spill))
+        .expect_err(ProgramError::IncorrectAuthority);
+
+    // Authority did not sign
+    let buffer = Pubkey::new_rand();
+    env.add_program_buffer(buffer, Box::new(InlinePackage::new(counter_v2)), &authority);
+    let mut unsigned = bpf_loader_upgradeable::upgrade(&program_id, &buffer, &authority, &spill);
+    unsigned.accounts.last_mut().unwrap().is_signer = false;
+    env.execute(&Transaction::new(&[unsigned]))
+        .expect_err(ProgramError::MissingRequiredSignature);
+
+    // Program made immutable
+    let instruction = bpf_loader_upgradeable::set_upgrade_authority(&program_id, &authority, None);
+    env.execute(&Transaction::new(&[instruction])).expect_ok();
+    env.execute(&upgrade(&program_id, &buffer, &authority, &spill))
+        .expect_err(ProgramError::Immutable);
+}
+
+#[test]
+fn test_buffer_write_stays_in_bounds() {
+    let authority = Pubkey::new_rand();
+    let (mut env, _, _) = counter_env(&authority);
+    let buffer = Pubkey::new_rand();
+    let mut bytes = bincode::serialize(&UpgradeableLoaderState::Buffer { authority_address: Some(authority) }).unwrap();
+    let metadata_len = bytes.len();
+    bytes.extend([0; 4]);
+    env.add_account(Account::new(buffer, 1000000000, &bpf_loader_upgradeable::ID, bytes));
+
+    let write = |offset: u32, bytes: Vec<u8>| Transaction::new(&[bpf_loader_upgradeable::write(&buffer, &authority, offset, bytes)]);
+    env.execute(&write(0, vec![1, 2, 3, 4])).expect_ok();
+    assert_eq!(&env.get_account(&buffer).unwrap().data[metadata_len..], &[1, 2, 3, 4]);
+
+    // The loader doesn't grow buffers, they're created with their final size
+    env.execute(&write(2, vec![5, 6, 7])).expect_err(ProgramError::AccountDataTooSmall);
+    assert_eq!(env.get_account(&buffer).unwrap().data.len(), metadata_len + 4);
+}
+
+#[test]
+fn test_close_program() {
+    let authority = Pubkey::new_rand();
+    let (mut env, program_id, state) = counter_env(&authority);
+    let programdata = get_program_data_address(&program_id);
+    let programdata_lamports = env.get_account(&programdata).unwrap().lamports;
+    let recipient = account!();
+    let recipient_key = *recipient.pubkey();
+    env.add_account(recipient);
+
+    let instruction = bpf_loader_upgradeable::close_any(&programdata, &recipient_key, Some(&authority), Some(&program_id));
+    env.execute(&Transaction::new(&[instruction])).expect_ok();
+    env.get_account(&recipient_key).unwrap().expect_balance(programdata_lamports);
+    assert!(env.packages.is_empty());
+
+    env.execute(&count(&program_id, &state))
+        .expect_err(ProgramError::InvalidAccountData);
+}
+
+#[test]
+fn test_hot_reload() {
+    let mut program = Program::inline(counter_v1, Pubkey::new_rand());
+    let mut state = Account::new(Pubkey::new_rand(), 1000000000, program.pubkey(), vec![0]);
+
+    program.invoke_with_bytes(&[], vec![state.meta(false, true)]).expect_ok();
+    program.reload(Box::new(InlinePackage::new(counter_v2)));
+    program.invoke_with_bytes(&[], vec![state.meta(false, true)]).expect_ok();
+    assert_eq!(state.data.as_slice(), &[2, 11]);
+}
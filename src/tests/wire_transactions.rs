@@ -0,0 +1,62 @@
+use solana_program::hash::Hash;
+use solana_program::message::Message;
+use solana_program::system_instruction;
+use crate::account;
+use crate::runtime::{ClusterSettings, Program, Transaction, TransactionError};
+use crate::suit::{keypair_pubkey, new_keypair};
+
+
+#[test]
+fn test_wire_transaction_roundtrip() {
+    let mut system_program = Program::system_program();
+    let payer_keypair = new_keypair();
+    let from_keypair = new_keypair();
+    let mut payer = account!(keypair_pubkey(&payer_keypair), 1000000000);
+    let mut from = account!(keypair_pubkey(&from_keypair), 1000000000);
+    let mut to = account!(1000000000);
+
+    let instruction = system_instruction::transfer(from.pubkey(), to.pubkey(), 700);
+    let bytes = Transaction::new_with_payer(std::slice::from_ref(&instruction), Some(payer.pubkey()))
+        .to_wire(&ClusterSettings::new().blockhash, &[&payer_keypair, &from_keypair]);
+
+    let transaction = Transaction::from_wire(&bytes).unwrap();
+    assert_eq!(transaction.signatures().len(), 2);
+    assert_eq!(transaction.instructions(), &[instruction]);
+    assert_eq!(transaction.message().unwrap().static_account_keys()[0], *payer.pubkey());
+
+    // Privileges come from the message, not from the metas
+    let receipt = transaction.execute(vec![
+        payer.meta(false, false),
+        from.meta(false, false),
+        to.meta(false, false),
+        system_program.meta(),
+    ]);
+    receipt.expect_ok();
+    receipt.expect_fee(10000);
+    payer.expect_balance(1000000000 - 10000);
+    from.expect_balance(1000000000 - 700);
+    to.expect_balance(1000000000 + 700);
+}
+
+#[test]
+fn test_wire_transaction_rejected() {
+    let payer_keypair = new_keypair();
+    let payer = keypair_pubkey(&payer_keypair);
+    let instruction = system_instruction::transfer(&payer, &keypair_pubkey(&new_keypair()), 1);
+    let bytes = Transaction::new(std::slice::from_ref(&instruction)).to_wire(&Hash::default(), &[&payer_keypair]);
+
+    let mut tampered = bytes.clone();
+    *tampered.last_mut().unwrap() ^= 1;
+    assert_eq!(Transaction::from_wire(&tampered).err(), Some(TransactionError::SignatureFailure));
+
+    let message = Message::new(&[instruction], Some(&payer)).serialize();
+    let mut unsigned = vec![0u8];
+    unsigned.extend_from_slice(&message);
+    assert_eq!(Transaction::from_wire(&unsigned).err(), Some(TransactionError::SanitizeFailure));
+    assert_eq!(Transaction::from_wire(&bytes[..bytes.len() - 1]).err(), Some(TransactionError::SanitizeFailure));
+
+    let mut system_program = Program::system_program();
+    let mut from = account!(payer, 1000000000);
+    let receipt = Transaction::from_wire(&bytes).unwrap().execute(vec![from.meta(true, true), system_program.meta()]);
+    receipt.expect_transaction_err(TransactionError::BlockhashNotFound);
+}